@@ -1,6 +1,6 @@
 use std::{hash::Hash, marker};
 
-use amethyst_core::ecs::*;
+use amethyst_core::{ecs::*, ClockSource, Clocks};
 use derivative::Derivative;
 use marker::PhantomData;
 
@@ -40,11 +40,22 @@ impl SystemBundle for VertexSkinningBundle {
 /// ### Type parameters:
 ///
 /// - `T`: the component type that sampling should be applied to
-#[derive(Default, Debug)]
+#[derive(Derivative, Debug)]
+#[derivative(Default)]
 pub struct SamplingBundle<T> {
+    clock: ClockSource,
     m: marker::PhantomData<T>,
 }
 
+impl<T> SamplingBundle<T> {
+    /// Advances the animations with the given clock instead of the game `Time`, e.g. to keep UI
+    /// animations running while the game is paused.
+    pub fn with_clock(mut self, clock: ClockSource) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
 impl<'a, T> SystemBundle for SamplingBundle<T>
 where
     T: AnimationSampling + std::fmt::Debug,
@@ -52,10 +63,14 @@ where
     fn load(
         &mut self,
         _world: &mut World,
-        _resources: &mut Resources,
+        resources: &mut Resources,
         builder: &mut DispatcherBuilder,
     ) -> amethyst_core::Result<()> {
-        builder.add_system(crate::systems::sampling::SamplerInterpolationSystem::<T>::default());
+        resources.get_or_default::<Clocks>();
+        builder.add_system(crate::systems::sampling::SamplerInterpolationSystem::<T> {
+            clock: self.clock.clone(),
+            ..Default::default()
+        });
 
         Ok(())
     }
@@ -76,9 +91,19 @@ where
 #[derive(Derivative, Debug)]
 #[derivative(Default)]
 pub struct AnimationBundle<I, T> {
+    clock: ClockSource,
     m: marker::PhantomData<(I, T)>,
 }
 
+impl<I, T> AnimationBundle<I, T> {
+    /// Advances the animations with the given clock instead of the game `Time`, e.g. to keep UI
+    /// animations running while the game is paused.
+    pub fn with_clock(mut self, clock: ClockSource) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
 impl<I, T> SystemBundle for AnimationBundle<I, T>
 where
     I: std::fmt::Debug + PartialEq + Eq + Hash + Copy + Send + Sync + 'static,
@@ -90,7 +115,10 @@ where
         _resources: &mut Resources,
         builder: &mut DispatcherBuilder,
     ) -> amethyst_core::Result<()> {
-        builder.add_bundle(SamplingBundle::<T> { m: PhantomData });
+        builder.add_bundle(SamplingBundle::<T> {
+            clock: self.clock.clone(),
+            m: PhantomData,
+        });
         builder.add_system(crate::systems::control::AnimationControlSystem::<I, T>::default());
 
         Ok(())
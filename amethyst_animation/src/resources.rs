@@ -1,15 +1,18 @@
-use std::{cmp::Ordering, fmt::Debug, hash::Hash, marker, time::Duration};
+use std::{cmp::Ordering, collections::HashMap, fmt::Debug, hash::Hash, marker, time::Duration};
 
 use amethyst_assets::{
     erased_serde::private::serde::{de, de::SeqAccess, ser::SerializeSeq},
     prefab::{
         register_component_type,
         serde_diff::{ApplyContext, DiffContext},
-        SerdeDiff,
+        RemapEntities, SerdeDiff,
     },
-    Asset, AssetStorage, Handle,
+    register_entity_remap, Asset, AssetStorage, Handle,
+};
+use amethyst_core::{
+    ecs::{world::EntityHasher, *},
+    Transform,
 };
-use amethyst_core::{ecs::*, Transform};
 use derivative::Derivative;
 use fnv::FnvHashMap;
 use log::debug;
@@ -211,6 +214,20 @@ impl SerdeDiff for AnimationSet<usize, Transform> {
 
 register_component_type!(AnimationHierarchy<Transform>);
 
+/// Drops the nodes which were not copied along with the hierarchy.
+impl RemapEntities for AnimationHierarchy<Transform> {
+    fn remap_entities(&mut self, entity_map: &HashMap<Entity, Entity, EntityHasher>) -> bool {
+        self.nodes = self
+            .nodes
+            .iter()
+            .filter_map(|(index, node)| entity_map.get(node).map(|node| (*index, *node)))
+            .collect();
+        !self.nodes.is_empty()
+    }
+}
+
+register_entity_remap!(AnimationHierarchy<Transform>);
+
 /// Defines a single animation.
 ///
 /// An animation is a set of [`Sampler`][sampler]s that should always run together as a unit.
@@ -1,12 +1,18 @@
+use std::collections::HashMap;
+
 use amethyst_assets::{
     erased_serde::private::serde::{de, de::SeqAccess, ser::SerializeSeq},
     prefab::{
         register_component_type,
         serde_diff::{ApplyContext, DiffContext},
-        SerdeDiff,
+        RemapEntities, SerdeDiff,
     },
+    register_entity_remap,
+};
+use amethyst_core::{
+    ecs::{world::EntityHasher, *},
+    math::Matrix4,
 };
-use amethyst_core::{ecs::*, math::Matrix4};
 use type_uuid::TypeUuid;
 
 /// Joint, attach to an entity with a `Transform`
@@ -77,6 +83,46 @@ impl SerdeDiff for Skin {
 
 register_component_type!(Skin);
 
+/// Drops the skins which were not copied along with the joint.
+impl RemapEntities for Joint {
+    fn remap_entities(&mut self, entity_map: &HashMap<Entity, Entity, EntityHasher>) -> bool {
+        self.skins = self
+            .skins
+            .iter()
+            .filter_map(|skin| entity_map.get(skin).copied())
+            .collect();
+        !self.skins.is_empty()
+    }
+}
+
+register_entity_remap!(Joint);
+
+/// Drops the meshes which were not copied along with the skin, and the skin itself if any of
+/// its joints was not copied.
+impl RemapEntities for Skin {
+    fn remap_entities(&mut self, entity_map: &HashMap<Entity, Entity, EntityHasher>) -> bool {
+        let joints: Option<Vec<Entity>> = self
+            .joints
+            .iter()
+            .map(|joint| entity_map.get(joint).copied())
+            .collect();
+        match joints {
+            Some(joints) => {
+                self.joints = joints;
+                self.meshes = self
+                    .meshes
+                    .iter()
+                    .filter_map(|mesh| entity_map.get(mesh).copied())
+                    .collect();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+register_entity_remap!(Skin);
+
 // impl Skin {
 //     /// Creates a new `Skin`
 //     pub fn new(
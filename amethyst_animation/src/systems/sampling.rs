@@ -3,7 +3,7 @@ use std::{collections::HashSet, marker::PhantomData, time::Duration};
 use amethyst_assets::AssetStorage;
 use amethyst_core::{
     ecs::{systems::ParallelRunnable, *},
-    Time,
+    ClockSource, Clocks, Time,
 };
 use derivative::Derivative;
 use log::debug;
@@ -22,7 +22,7 @@ use crate::resources::{
 /// on `AnimationControlSystem`.
 ///
 /// Will process all active `SamplerControlSet`, and update the target component for the entity they
-/// belong to.
+/// belong to. Animations advance with the delta time of the selected clock.
 ///
 /// ### Type parameters:
 ///
@@ -30,6 +30,7 @@ use crate::resources::{
 #[derive(Derivative)]
 #[derivative(Default)]
 pub(crate) struct SamplerInterpolationSystem<T: AnimationSampling> {
+    pub(crate) clock: ClockSource,
     _marker: PhantomData<T>,
 }
 
@@ -40,16 +41,20 @@ where
     fn build(self) -> Box<dyn ParallelRunnable> {
         let mut inner = Vec::default();
         let mut channels = Vec::default();
+        let clock = self.clock;
 
         Box::new(
             SystemBuilder::new("SamplerInterpolationSystem")
                 .read_resource::<Time>()
+                .read_resource::<Clocks>()
                 .read_resource::<AssetStorage<Sampler<T::Primitive>>>()
                 .with_query(<(Write<SamplerControlSet<T>>, Write<T>)>::query())
-                .build(move |commands, world, (time, samplers), query| {
+                .build(move |commands, world, (time, clocks, samplers), query| {
                     #[cfg(feature = "profiler")]
                     profile_scope!("sampler_interpolation_system");
 
+                    let delta = clock.delta_time(&time, &clocks);
+
                     for (control_set, comp) in query.iter_mut(world) {
                         debug!("Processing SamplerControlSet: {:?}", control_set);
 
@@ -57,7 +62,7 @@ where
 
                         for control in control_set.samplers.iter_mut() {
                             if let Some(ref sampler) = samplers.get(&control.sampler) {
-                                process_sampler(control, sampler, &time, &mut inner);
+                                process_sampler(control, sampler, delta, &mut inner);
                             }
                         }
                         if !inner.is_empty() {
@@ -98,18 +103,18 @@ where
 /// - `control`: sampler control object
 /// - `sampler`: the sampler reference from the control object
 /// - `component`: the component to update
-/// - `now`: synchronized `Instant` for the current frame
+/// - `delta`: time elapsed on the clock of the system during the current frame
 fn process_sampler<T>(
     control: &mut SamplerControl<T>,
     sampler: &Sampler<T::Primitive>,
-    time: &Time,
+    delta: Duration,
     output: &mut Vec<(f32, T::Channel, T::Primitive)>,
 ) where
     T: AnimationSampling,
 {
     use crate::resources::ControlState::*;
 
-    let (new_state, new_end) = update_duration_and_check(&control, sampler, time);
+    let (new_state, new_end) = update_duration_and_check(&control, sampler, delta);
 
     // If a new end condition has been computed, update in control state
     if let Some(end) = new_end {
@@ -167,7 +172,7 @@ fn process_sampler<T>(
 ///
 /// - `control`: sampler control object
 /// - `sampler`: sampler reference from control
-/// - `now`: synchronized `Instant` for the current frame
+/// - `delta`: time elapsed on the clock of the system during the current frame
 ///
 /// ## Returns
 ///
@@ -175,7 +180,7 @@ fn process_sampler<T>(
 fn update_duration_and_check<T>(
     control: &SamplerControl<T>,
     sampler: &Sampler<T::Primitive>,
-    time: &Time,
+    delta: Duration,
 ) -> (ControlState, Option<EndControl>)
 where
     T: AnimationSampling,
@@ -195,10 +200,8 @@ where
 
         // sampling is running, update duration and check end condition
         Running(duration) => {
-            let current_dur = duration
-                + Duration::from_secs_f32(
-                    time.delta_time().as_secs_f32() * control.rate_multiplier,
-                );
+            let current_dur =
+                duration + Duration::from_secs_f32(delta.as_secs_f32() * control.rate_multiplier);
             let last_frame = sampler
                 .input
                 .last()
@@ -27,6 +27,17 @@ pub trait Asset: Send + Sync + 'static {
     type Data: Send + Sync + 'static;
 }
 
+/// Reports the approximate memory used by an asset.
+///
+/// Implement this for assets which use a lot of memory, like textures, meshes or audio,
+/// so their `AssetStorage` can report its memory use and keep it within a budget.
+/// See `AssetStorage::set_budget`.
+pub trait AssetSize {
+    /// Returns the approximate number of bytes used by this asset, including memory
+    /// on the GPU.
+    fn size_bytes(&self) -> usize;
+}
+
 /// Defines a way to process asset's data into the asset. This allows
 /// using default `Processor` system to process assets that implement that type.
 pub trait ProcessableAsset: Asset + Sized {
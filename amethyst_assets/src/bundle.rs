@@ -3,7 +3,7 @@ use std::default::Default;
 use amethyst_core::ecs::{DispatcherBuilder, Resources, SystemBundle, World};
 use amethyst_error::Error;
 
-use crate::{prefab::ComponentRegistryBuilder, DefaultLoader, Loader};
+use crate::{prefab::ComponentRegistryBuilder, DefaultLoader, Loader, Sources};
 
 fn asset_loading_tick(_: &mut World, resources: &mut Resources) {
     let mut loader = resources
@@ -16,6 +16,10 @@ fn asset_loading_tick(_: &mut World, resources: &mut Resources) {
 }
 
 /// Bundle that initializes Loader as well as related processing systems and resources
+///
+/// If a [Sources] resource is present, it is moved into the loader so assets can be loaded
+/// from those sources without the asset daemon. The same goes for a `Packfile` resource,
+/// which makes the loader load all assets from that packfile.
 pub struct LoaderBundle;
 
 impl SystemBundle for LoaderBundle {
@@ -30,6 +34,15 @@ impl SystemBundle for LoaderBundle {
             .build();
         resources.insert(component_registry);
         let mut loader = DefaultLoader::default();
+        if let Some(sources) = resources.remove::<Sources>() {
+            loader.set_sources(sources);
+        }
+        #[cfg(feature = "packfile")]
+        {
+            if let Some(packfile) = resources.remove::<crate::Packfile>() {
+                loader.set_packfile(packfile);
+            }
+        }
         loader.init_world(resources);
         loader.init_dispatcher(builder);
         resources.insert(loader);
@@ -35,13 +35,19 @@ where
     }
 
     /// Retrieves an asset handle using a given `key`.
+    ///
+    /// The returned handle keeps the asset alive like any other handle.
     pub fn get<K>(&self, key: &K) -> Option<Handle<A>>
     where
         K: ?Sized + Hash + Eq,
         String: Borrow<K>,
     {
         self.map.get(key).map(|weak_handle: &WeakHandle| {
-            Handle::<A>::new(self.tx.clone(), weak_handle.load_handle())
+            let load_handle = weak_handle.load_handle();
+            // `Handle::new` takes over an existing reference, so add one for the new handle.
+            // The receiver only disconnects once the loader is dropped.
+            let _ = self.tx.send(RefOp::Increase(load_handle));
+            Handle::<A>::new(self.tx.clone(), load_handle)
         })
     }
 
@@ -0,0 +1,72 @@
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use distill::loader::handle::RefOp;
+
+use crate::{AssetHandle, Handle, LoadHandle};
+
+/// Load handle of detached handles, which is never allocated by a loader.
+const DETACHED: LoadHandle = LoadHandle(0);
+
+lazy_static::lazy_static! {
+    /// Reference counting channel of detached handles, which no loader listens to.
+    static ref DETACHED_REFS: (Sender<RefOp>, Receiver<RefOp>) = unbounded();
+}
+
+/// Returns a handle which does not refer to any asset.
+///
+/// Detached handles are never loaded, so storages return `None` for them. They are meant as
+/// placeholders in `Default` implementations of components and assets holding handles, which
+/// prefabs need to add a component before applying its serialized fields.
+pub fn detached_handle<A>() -> Handle<A> {
+    // Nobody tracks the references of detached handles, so just drop them.
+    DETACHED_REFS.1.try_iter().for_each(drop);
+    Handle::new(DETACHED_REFS.0.clone(), DETACHED)
+}
+
+/// Returns true if the handle was created by [detached_handle].
+pub fn is_detached<H: AssetHandle>(handle: &H) -> bool {
+    handle.load_handle() == DETACHED
+}
+
+/// Serializes a handle which may be detached as an `Option`, writing detached handles as `None`,
+/// so they are never looked up by a loader. Use it with
+/// `#[serde(with = "amethyst_assets::serde_detached")]`.
+pub mod serde_detached {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{detached_handle, is_detached};
+    use crate::Handle;
+
+    /// Serializes `handle`, or `None` if it is detached.
+    pub fn serialize<A, S: Serializer>(
+        handle: &Handle<A>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if is_detached(handle) {
+            None::<&Handle<A>>.serialize(serializer)
+        } else {
+            Some(handle).serialize(serializer)
+        }
+    }
+
+    /// Deserializes a handle, or a detached handle from `None`.
+    pub fn deserialize<'de, A, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Handle<A>, D::Error> {
+        Ok(Option::<Handle<A>>::deserialize(deserializer)?.unwrap_or_else(detached_handle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detached_handles_can_be_cloned_and_dropped() {
+        let handle = detached_handle::<u32>();
+        let clone = handle.clone();
+        assert!(is_detached(&clone));
+        drop(handle);
+        drop(clone);
+        assert!(is_detached(&detached_handle::<u32>()));
+    }
+}
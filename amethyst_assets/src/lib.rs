@@ -20,11 +20,14 @@ mod bundle;
 mod cache;
 #[cfg(feature = "asset-daemon")]
 mod daemon;
+mod detached;
 /// asset loading specific errors
 pub mod error;
 #[cfg(feature = "json")]
 mod json;
 mod loader;
+#[cfg(feature = "packfile")]
+mod packfile;
 /// helpers for registering prefab components
 pub mod prefab;
 mod processor;
@@ -39,6 +42,7 @@ pub use distill::{
     loader::{
         handle::{AssetHandle, GenericHandle, Handle, WeakHandle},
         storage::LoadHandle,
+        AssetTypeId,
     },
     make_handle,
 };
@@ -52,14 +56,23 @@ pub use {erased_serde, inventory, lazy_static};
 pub use crate::daemon::AssetDaemon;
 #[cfg(feature = "json")]
 pub use crate::json::JsonFormat;
+#[cfg(feature = "packfile")]
+pub use crate::packfile::Packfile;
 pub use crate::{
-    asset::{Asset, Format, FormatValue, ProcessableAsset, SerializableFormat},
+    asset::{Asset, AssetSize, Format, FormatValue, ProcessableAsset, SerializableFormat},
     bundle::LoaderBundle,
     cache::Cache,
-    loader::{create_asset_type, AssetUuid, DefaultLoader, LoadStatus, Loader},
+    detached::{detached_handle, is_detached, serde_detached},
+    loader::{
+        create_asset_type, create_asset_type_with_resources, AssetReloaded, AssetUuid,
+        DefaultLoader, LoadStatus, Loader,
+    },
     processor::{AssetProcessorSystem, ProcessingQueue, ProcessingState},
-    progress::{Completion, Progress, ProgressCounter, Tracker},
-    simple_importer::{SimpleImporter, SourceFileImporter},
-    source::{Directory, Source},
-    storage::AssetStorage,
+    progress::{Completion, LoadProgress, Progress, ProgressCounter, Tracker},
+    simple_importer::{
+        import_simple_source, import_source_with, SimpleImporter, SourceAsset, SourceFileImporter,
+        SourceImporter,
+    },
+    source::{Directory, MemorySource, Source, Sources},
+    storage::{AssetMemoryUsage, AssetStorage, AssetTypeUsage},
 };
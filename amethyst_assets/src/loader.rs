@@ -11,6 +11,8 @@ use std::{
 use amethyst_core::{
     dispatcher::System,
     ecs::{DispatcherBuilder, Resources},
+    shrev::EventChannel,
+    ArcThreadPool,
 };
 use amethyst_error::Error as AmethystError;
 use distill::{
@@ -25,14 +27,23 @@ use distill_loader::{
         AssetLoadOp, AtomicHandleAllocator, HandleAllocator, IndirectIdentifier, IndirectionTable,
         LoaderInfoProvider,
     },
-    AssetTypeId, Loader as DistillLoader, PackfileReader, RpcIO,
+    AssetRef, AssetTypeId, Loader as DistillLoader, PackfileReader, RpcIO,
 };
 pub use distill_loader::{storage::LoadStatus, AssetUuid};
+use fnv::FnvHashMap;
 use log::debug;
+use parking_lot::Mutex;
 use serde::de::Deserialize;
 
+#[cfg(feature = "packfile")]
+use crate::packfile::Packfile;
 use crate::{
-    processor::ProcessingQueue, progress::Progress, storage::AssetStorage, Asset, TypeUuid,
+    processor::{LoadNotifier, ProcessingQueue},
+    progress::{Progress, Tracker},
+    simple_importer::{get_source_importer, SourceAsset},
+    source::{Source, Sources},
+    storage::{AssetMemoryUsage, AssetStorage, AssetTypeUsage},
+    Asset, TypeUuid,
 };
 
 /// Manages asset loading and storage for an application.
@@ -78,11 +89,30 @@ pub trait Loader: Send + Sync {
     ///
     /// This is useful when loading an asset whose Rust type is known.
     ///
+    /// If the loader has a packfile, the asset is loaded from it. Otherwise, if a default
+    /// [Source](crate::Source) was registered in [Sources], the asset is loaded from that
+    /// source, otherwise it is requested from the asset daemon.
+    ///
     /// # Type Parameters
     ///
     /// * `T`: Asset `TypeUuid`.
     fn load<T: TypeUuid>(&self, path: &str) -> Handle<T>;
 
+    /// Returns an asset handle and loads the asset at the given path from the [Source](crate::Source)
+    /// registered under the given name.
+    ///
+    /// The asset is imported by the `SourceImporter` registered for the file extension of `path`.
+    ///
+    /// # Parameters
+    ///
+    /// * `path`: Path of the asset inside the source, using `/` as separator.
+    /// * `source`: Name the source was registered with.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T`: Asset `TypeUuid`.
+    fn load_from<T: TypeUuid>(&self, path: &str, source: &str) -> Handle<T>;
+
     /// Returns a weak handle to the asset of the given UUID, if any.
     ///
     /// # Parameters
@@ -108,6 +138,28 @@ pub trait Loader: Send + Sync {
     /// * `handle`: `LoadHandle` of the asset.
     fn get_load_status_handle(&self, handle: LoadHandle) -> LoadStatus;
 
+    /// Returns the load handles of the assets the asset with the given load handle depends on.
+    ///
+    /// Dependencies are known once the asset data has been loaded: these are the handles
+    /// contained in the asset data, and the prefabs a prefab references.
+    ///
+    /// # Parameters
+    ///
+    /// * `handle`: `LoadHandle` of the asset.
+    fn load_dependencies(&self, _handle: LoadHandle) -> Vec<LoadHandle> {
+        Vec::new()
+    }
+
+    /// Returns the size of the loaded data of the asset with the given load handle in bytes,
+    /// if its data has been loaded.
+    ///
+    /// # Parameters
+    ///
+    /// * `handle`: `LoadHandle` of the asset.
+    fn loaded_bytes(&self, _handle: LoadHandle) -> Option<usize> {
+        None
+    }
+
     /// Returns an immutable reference to the asset if it is committed.
     ///
     /// # Parameters
@@ -155,6 +207,16 @@ pub struct DefaultLoader {
     ref_receiver: Receiver<RefOp>,
     handle_allocator: Arc<AtomicHandleAllocator>,
     pub(crate) indirection_table: IndirectionTable,
+    source_loader: SourceLoader,
+    load_graph: Mutex<LoadGraph>,
+    /// Reference counts of the handles allocated by this loader instead of `distill`.
+    local_refs: Mutex<FnvHashMap<LoadHandle, LocalRefs>>,
+    /// Handles in `local_refs` without references and handles in `retained`, oldest first.
+    unreferenced: Vec<LoadHandle>,
+    /// Handles loaded from the asset daemon whose last reference is held by this loader.
+    retained: FnvHashMap<LoadHandle, RetainedRef>,
+    /// The [ArcThreadPool] resource, which imports assets from sources.
+    thread_pool: Option<ArcThreadPool>,
 }
 
 impl Default for DefaultLoader {
@@ -193,7 +255,569 @@ impl DefaultLoader {
             ref_sender: tx,
             ref_receiver: rx,
             handle_allocator,
+            source_loader: SourceLoader::default(),
+            load_graph: Default::default(),
+            local_refs: Default::default(),
+            unreferenced: Vec::new(),
+            retained: Default::default(),
+            thread_pool: None,
+        }
+    }
+
+    /// Sets the sources assets can be loaded from without the asset daemon.
+    ///
+    /// This replaces any previously set sources. Assets which were already loaded from the
+    /// previous sources stay loaded.
+    pub fn set_sources(&mut self, sources: Sources) {
+        self.source_loader.sources = sources;
+    }
+
+    /// Returns the sources assets can be loaded from without the asset daemon.
+    pub fn sources(&self) -> &Sources {
+        &self.source_loader.sources
+    }
+
+    /// Imports assets from sources on the [ArcThreadPool] resource, if it exists, instead of
+    /// rayon's global pool.
+    fn update_thread_pool(&mut self, resources: &Resources) {
+        self.thread_pool = resources
+            .get::<ArcThreadPool>()
+            .map(|pool| ArcThreadPool::clone(&pool));
+    }
+
+    /// Adds a reference to a handle allocated by this loader, which `distill` doesn't know about.
+    fn add_local_ref(&self, handle: LoadHandle, asset_type: AssetTypeId) {
+        self.local_refs
+            .lock()
+            .entry(handle)
+            .or_insert(LocalRefs {
+                count: 0,
+                asset_type,
+            })
+            .count += 1;
+    }
+
+    /// Keeps the last reference to an asset loaded from the asset daemon instead of releasing
+    /// it, so the asset can stay loaded within the memory budget of its storage, e.g. while only
+    /// a `Cache` refers to it.
+    ///
+    /// Returns false if `handle` still has other references or its asset is not loaded yet.
+    fn retain_last_ref(&mut self, handle: LoadHandle) -> bool {
+        if self
+            .loader
+            .get_load_info(handle)
+            .map_or(true, |info| info.refs > 1)
+        {
+            return false;
+        }
+        let direct = if handle.is_indirect() {
+            self.indirection_table.resolve(handle)
+        } else {
+            Some(handle)
+        };
+        let retained = direct.and_then(|direct| {
+            self.load_graph
+                .lock()
+                .get(&direct)
+                .and_then(|info| info.asset_type)
+                .map(|asset_type| RetainedRef { direct, asset_type })
+        });
+        match retained {
+            Some(retained) => {
+                self.retained.insert(handle, retained);
+                self.unreferenced.push(handle);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Frees unreferenced assets, or only evicts them while above the memory budget of their
+    /// storage if it has one.
+    fn evict_unreferenced(&mut self, resources: &Resources) {
+        if self.unreferenced.is_empty() {
+            return;
+        }
+        let mut candidates_by_type = FnvHashMap::<AssetTypeId, Vec<LoadHandle>>::default();
+        {
+            let local_refs = self.local_refs.lock();
+            let retained = &self.retained;
+            // Handles which were requested again since they became unreferenced are in use.
+            self.unreferenced.retain(|handle| {
+                retained.contains_key(handle)
+                    || local_refs.get(handle).map_or(false, |refs| refs.count == 0)
+            });
+            for handle in &self.unreferenced {
+                let candidate = match retained.get(handle) {
+                    Some(retained) => Some((retained.asset_type, retained.direct)),
+                    None => {
+                        local_refs
+                            .get(handle)
+                            .map(|refs| (refs.asset_type, *handle))
+                    }
+                };
+                if let Some((asset_type, direct)) = candidate {
+                    candidates_by_type
+                        .entry(asset_type)
+                        .or_default()
+                        .push(direct);
+                }
+            }
+        }
+
+        let mut evicted = Vec::new();
+        for (asset_type, candidates) in candidates_by_type {
+            if let Some(asset_type_info) = self.storage_map.storages_by_asset_uuid.get(&asset_type)
+            {
+                (asset_type_info.with_storage)(
+                    resources,
+                    &mut |storage: &mut dyn AssetTypeStorage| {
+                        evicted.extend(storage.evict(&candidates));
+                    },
+                );
+            }
+        }
+        if evicted.is_empty() {
+            return;
+        }
+
+        // Freed assets are loaded again the next time they are requested. Assets loaded from the
+        // asset daemon are unloaded by releasing the reference the loader kept.
+        let retained = &mut self.retained;
+        let loader = &mut self.loader;
+        self.unreferenced.retain(|handle| {
+            match retained.get(handle).map(|retained| retained.direct) {
+                Some(direct) if evicted.contains(&direct) => {
+                    retained.remove(handle);
+                    loader.remove_ref(*handle);
+                    false
+                }
+                Some(_) => true,
+                None => !evicted.contains(handle),
+            }
+        });
+        let mut local_refs = self.local_refs.lock();
+        let mut statuses = self.source_loader.statuses.lock();
+        let mut load_graph = self.load_graph.lock();
+        for handle in &evicted {
+            if local_refs.remove(handle).is_some() {
+                statuses.remove(handle);
+                let packed_dependencies = load_graph
+                    .remove(handle)
+                    .map(|info| info.packed_dependencies)
+                    .unwrap_or_default();
+                for dependency in packed_dependencies {
+                    if let Some(refs) = local_refs.get_mut(&dependency) {
+                        refs.count = refs.count.saturating_sub(1);
+                        if refs.count == 0 {
+                            self.unreferenced.push(dependency);
+                        }
+                    }
+                }
+            }
+        }
+        self.source_loader
+            .loads
+            .lock()
+            .retain(|_, handle| !evicted.contains(handle));
+    }
+
+    /// Refreshes the `AssetMemoryUsage` resource, if present.
+    fn update_memory_usage(&self, resources: &Resources) {
+        if let Some(mut memory_usage) = resources.get_mut::<AssetMemoryUsage>() {
+            memory_usage.types.clear();
+            for asset_type_info in self.storage_map.storages_by_asset_uuid.values() {
+                (asset_type_info.with_storage)(
+                    resources,
+                    &mut |storage: &mut dyn AssetTypeStorage| {
+                        memory_usage.types.push(storage.memory_usage());
+                    },
+                );
+            }
+            memory_usage.types.sort_by_key(|usage| usage.name);
+        }
+    }
+
+    /// Records load dependencies of an asset which are not contained in its data,
+    /// such as the prefabs a prefab references.
+    pub(crate) fn add_load_dependencies<I>(&self, handle: LoadHandle, dependencies: I)
+    where
+        I: IntoIterator<Item = LoadHandle>,
+    {
+        let mut load_graph = self.load_graph.lock();
+        let dependencies_of_handle = &mut load_graph.entry(handle).or_default().dependencies;
+        for dependency in dependencies {
+            if !dependencies_of_handle.contains(&dependency) {
+                dependencies_of_handle.push(dependency);
+            }
+        }
+    }
+
+    /// Creates a loader that loads assets from a baked [Packfile] instead of the asset daemon.
+    #[cfg(feature = "packfile")]
+    pub fn from_packfile(packfile: Packfile) -> Self {
+        let mut loader = Self::default();
+        loader.set_packfile(packfile);
+        loader
+    }
+
+    /// Sets the packfile `Loader::load` loads assets from.
+    ///
+    /// The packfile takes precedence over the default source and the asset daemon.
+    /// Assets missing from the packfile fail to load.
+    #[cfg(feature = "packfile")]
+    pub fn set_packfile(&mut self, packfile: Packfile) {
+        self.source_loader.packfile = Some(Arc::new(packfile));
+    }
+
+    /// Returns the packfile assets are loaded from, if any.
+    #[cfg(feature = "packfile")]
+    pub fn packfile(&self) -> Option<&Packfile> {
+        self.source_loader.packfile.as_deref()
+    }
+
+    fn load_from_source(
+        &self,
+        path: &str,
+        source_name: Option<&str>,
+        source: Option<&Arc<dyn Source>>,
+        asset_type: AssetTypeId,
+    ) -> LoadHandle {
+        let key = source_name.map(str::to_string);
+        let source_name = source_name.unwrap_or_default().to_string();
+        let source = source.cloned();
+        self.spawn_source_load(key, path, asset_type, false, move |path| {
+            let source = source.ok_or_else(|| {
+                AmethystError::from_string(format!("No asset source named {:?}", source_name))
+            })?;
+            let bytes = source.load(path)?;
+            let importer = get_source_importer(path).ok_or_else(|| {
+                AmethystError::from_string(format!(
+                    "No `SourceImporter` is registered for {:?}",
+                    path
+                ))
+            })?;
+            (importer.import)(bytes)
+        })
+    }
+
+    #[cfg(feature = "packfile")]
+    fn load_from_packfile(
+        &self,
+        path: &str,
+        packfile: &Arc<Packfile>,
+        asset_type: AssetTypeId,
+    ) -> LoadHandle {
+        let packfile = packfile.clone();
+        self.spawn_source_load(None, path, asset_type, true, move |path| {
+            packfile.get(path).ok_or_else(|| {
+                AmethystError::from_string(format!("{:?} is not in the packfile", path))
+            })
+        })
+    }
+
+    /// Loads the load dependencies of an asset read from the packfile, which are released
+    /// again when the asset is freed.
+    #[cfg(feature = "packfile")]
+    fn load_packed_dependencies(&self, handle: LoadHandle, path: &str, asset: &SourceAsset) {
+        let packfile = match &self.source_loader.packfile {
+            Some(packfile) => packfile,
+            None => return,
+        };
+        let mut dependencies = Vec::new();
+        for dependency in &asset.load_deps {
+            let asset_type = packfile.resolve(dependency).and_then(|(path, type_id)| {
+                self.storage_map
+                    .storages_by_data_uuid
+                    .get(&type_id)
+                    .map(|asset_type_info| (path, asset_type_info.asset_uuid))
+            });
+            match asset_type {
+                Some((dependency_path, asset_type)) => {
+                    dependencies.push(self.load_from_packfile(
+                        dependency_path,
+                        packfile,
+                        asset_type,
+                    ));
+                }
+                None => {
+                    log::warn!(
+                        "Load dependency {:?} of {:?} is not in the packfile",
+                        dependency,
+                        path
+                    );
+                }
+            }
+        }
+        let mut load_graph = self.load_graph.lock();
+        let info = load_graph.entry(handle).or_default();
+        info.dependencies.extend(dependencies.iter().copied());
+        info.packed_dependencies = dependencies;
+    }
+
+    /// Returns the handle of an asset loaded from a source, reading it on the rayon thread
+    /// pool if it has not been requested before.
+    ///
+    /// The load dependencies of assets read from the packfile are loaded from it as well.
+    fn spawn_source_load<F>(
+        &self,
+        source_name: Option<String>,
+        path: &str,
+        asset_type: AssetTypeId,
+        packed: bool,
+        read: F,
+    ) -> LoadHandle
+    where
+        F: FnOnce(&str) -> Result<Vec<SourceAsset>, AmethystError> + Send + 'static,
+    {
+        let key = (source_name, path.to_string(), asset_type);
+        let mut loads = self.source_loader.loads.lock();
+        if let Some(handle) = loads.get(&key) {
+            let handle = *handle;
+            drop(loads);
+            self.add_local_ref(handle, asset_type);
+            return handle;
+        }
+
+        let handle = self.handle_allocator.alloc();
+        loads.insert(key, handle);
+        drop(loads);
+        self.add_local_ref(handle, asset_type);
+
+        self.source_loader
+            .statuses
+            .lock()
+            .insert(handle, SourceLoadStatus::Loading);
+
+        let sender = self.source_loader.sender.clone();
+        let path = path.to_string();
+        let load = move || {
+            let result = read(&path);
+            // The receiver only disconnects once the loader is dropped.
+            let _ = sender.send(SourceLoad {
+                handle,
+                path,
+                asset_type,
+                packed,
+                result,
+            });
+        };
+        match &self.thread_pool {
+            Some(pool) => pool.spawn(load),
+            None => rayon::spawn(load),
+        }
+
+        handle
+    }
+
+    /// Moves assets which finished importing from a source into their storages.
+    fn process_source_loads(&self, resources: &Resources) {
+        while let Ok(load) = self.source_loader.receiver.try_recv() {
+            let SourceLoad {
+                handle,
+                path,
+                asset_type,
+                packed,
+                result,
+            } = load;
+            if !self.local_refs.lock().contains_key(&handle) {
+                // All handles were dropped while the asset was loading.
+                continue;
+            }
+            let storage_map = &self.storage_map;
+            let asset = result.and_then(|assets| {
+                assets
+                    .into_iter()
+                    .find_map(|asset| {
+                        storage_map
+                            .storages_by_data_uuid
+                            .get(&asset.type_id)
+                            .filter(|asset_type_info| asset_type_info.asset_uuid == asset_type)
+                            .map(|asset_type_info| (asset_type_info, asset))
+                    })
+                    .ok_or_else(|| {
+                        AmethystError::from_string(format!(
+                            "{:?} does not contain an asset of the requested type",
+                            path
+                        ))
+                    })
+            });
+
+            match asset {
+                Ok((asset_type_info, asset)) => {
+                    self.load_graph.lock().entry(handle).or_default().bytes = asset.data.len();
+                    if packed {
+                        #[cfg(feature = "packfile")]
+                        self.load_packed_dependencies(handle, &path, &asset);
+                    }
+                    let tracker = Box::new(SourceLoadTracker {
+                        handle,
+                        statuses: self.source_loader.statuses.clone(),
+                    });
+                    let mut tracker = Some(tracker);
+                    (asset_type_info.with_storage)(
+                        resources,
+                        &mut |storage: &mut dyn AssetTypeStorage| {
+                            if let Err(e) = storage.update_asset_from_source(
+                                handle,
+                                &asset.data,
+                                tracker.take().unwrap(),
+                                0,
+                            ) {
+                                log::error!("Failed to load {:?}: {}", path, e);
+                            }
+                        },
+                    );
+                }
+                Err(e) => {
+                    log::error!("Failed to load {:?}: {}", path, e);
+                    self.source_loader
+                        .statuses
+                        .lock()
+                        .insert(handle, SourceLoadStatus::Failed);
+                }
+            }
+        }
+    }
+}
+
+/// Loads assets from the registered [Sources] or a packfile on the rayon thread pool.
+struct SourceLoader {
+    sources: Sources,
+    #[cfg(feature = "packfile")]
+    packfile: Option<Arc<Packfile>>,
+    /// Handles of assets loaded from sources, keyed by source name, path and asset type.
+    /// Assets loaded with `Loader::load` have no source name.
+    loads: Mutex<FnvHashMap<(Option<String>, String, AssetTypeId), LoadHandle>>,
+    statuses: Arc<Mutex<FnvHashMap<LoadHandle, SourceLoadStatus>>>,
+    sender: Sender<SourceLoad>,
+    receiver: Receiver<SourceLoad>,
+}
+
+impl Default for SourceLoader {
+    fn default() -> Self {
+        let (sender, receiver) = unbounded();
+        Self {
+            sources: Sources::default(),
+            #[cfg(feature = "packfile")]
+            packfile: None,
+            loads: Default::default(),
+            statuses: Default::default(),
+            sender,
+            receiver,
+        }
+    }
+}
+
+/// Asset data that was read and imported from a source.
+struct SourceLoad {
+    handle: LoadHandle,
+    path: String,
+    asset_type: AssetTypeId,
+    /// Whether the asset was read from the packfile.
+    packed: bool,
+    result: Result<Vec<SourceAsset>, AmethystError>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SourceLoadStatus {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+impl From<SourceLoadStatus> for LoadStatus {
+    fn from(status: SourceLoadStatus) -> Self {
+        match status {
+            SourceLoadStatus::Loading => LoadStatus::Loading,
+            SourceLoadStatus::Loaded => LoadStatus::Loaded,
+            SourceLoadStatus::Failed => LoadStatus::DoesNotExist,
+        }
+    }
+}
+
+/// Records the load status of assets loaded from sources once they have been processed.
+struct SourceLoadTracker {
+    handle: LoadHandle,
+    statuses: Arc<Mutex<FnvHashMap<LoadHandle, SourceLoadStatus>>>,
+}
+
+impl Tracker for SourceLoadTracker {
+    fn success(self: Box<Self>) {
+        self.statuses
+            .lock()
+            .insert(self.handle, SourceLoadStatus::Loaded);
+    }
+
+    fn fail(
+        self: Box<Self>,
+        handle_id: u64,
+        asset_type_name: &'static str,
+        asset_name: String,
+        error: AmethystError,
+    ) {
+        log::error!(
+            "Error loading handle {}, {}, with name {}: {}",
+            handle_id,
+            asset_type_name,
+            asset_name,
+            error
+        );
+        self.statuses
+            .lock()
+            .insert(self.handle, SourceLoadStatus::Failed);
+    }
+}
+
+/// Load dependencies and data sizes of loaded assets, keyed by load handle.
+type LoadGraph = FnvHashMap<LoadHandle, AssetLoadInfo>;
+
+#[derive(Debug, Default)]
+struct AssetLoadInfo {
+    dependencies: Vec<LoadHandle>,
+    /// Dependencies the loader loaded from the packfile for the asset, released when the asset
+    /// is freed.
+    packed_dependencies: Vec<LoadHandle>,
+    bytes: usize,
+    version: u32,
+    /// UUID of the asset type of assets loaded from the asset daemon.
+    asset_type: Option<AssetTypeId>,
+}
+
+/// The last reference to an asset loaded from the asset daemon, kept by the loader until the
+/// asset is evicted.
+#[derive(Debug)]
+struct RetainedRef {
+    /// Load handle of the asset in its storage.
+    direct: LoadHandle,
+    /// UUID of the asset type, used to find the storage of the asset.
+    asset_type: AssetTypeId,
+}
+
+#[derive(Debug)]
+struct LocalRefs {
+    count: usize,
+    /// UUID of the asset type, used to find the storage of the asset.
+    asset_type: AssetTypeId,
+}
+
+/// Records the handles deserialized from asset data as load dependencies of the asset.
+struct DependencyRecorder<'a> {
+    loader_info: &'a dyn LoaderInfoProvider,
+    dependencies: Mutex<Vec<LoadHandle>>,
+}
+
+impl<'a> LoaderInfoProvider for DependencyRecorder<'a> {
+    fn get_load_handle(&self, id: &AssetRef) -> Option<LoadHandle> {
+        let handle = self.loader_info.get_load_handle(id);
+        if let Some(handle) = handle {
+            self.dependencies.lock().push(handle);
         }
+        handle
+    }
+
+    fn get_asset_id(&self, load: LoadHandle) -> Option<AssetUuid> {
+        self.loader_info.get_asset_id(load)
     }
 }
 
@@ -205,6 +829,21 @@ impl Loader for DefaultLoader {
         Handle::new(self.ref_sender.clone(), self.loader.add_ref(id))
     }
     fn load<A: TypeUuid>(&self, path: &str) -> Handle<A> {
+        #[cfg(feature = "packfile")]
+        {
+            if let Some(packfile) = &self.source_loader.packfile {
+                return Handle::new(
+                    self.ref_sender.clone(),
+                    self.load_from_packfile(path, packfile, AssetTypeId(A::UUID)),
+                );
+            }
+        }
+        if let Some(source) = self.source_loader.sources.default_source() {
+            return Handle::new(
+                self.ref_sender.clone(),
+                self.load_from_source(path, None, Some(source), AssetTypeId(A::UUID)),
+            );
+        }
         Handle::new(
             self.ref_sender.clone(),
             self.loader
@@ -214,10 +853,34 @@ impl Loader for DefaultLoader {
                 )),
         )
     }
+    fn load_from<A: TypeUuid>(&self, path: &str, source: &str) -> Handle<A> {
+        Handle::new(
+            self.ref_sender.clone(),
+            self.load_from_source(
+                path,
+                Some(source),
+                self.source_loader.sources.get(source),
+                AssetTypeId(A::UUID),
+            ),
+        )
+    }
     fn get_load(&self, id: AssetUuid) -> Option<WeakHandle> {
         self.loader.get_load(id).map(WeakHandle::new)
     }
+    fn load_dependencies(&self, handle: LoadHandle) -> Vec<LoadHandle> {
+        self.load_graph
+            .lock()
+            .get(&handle)
+            .map(|info| info.dependencies.clone())
+            .unwrap_or_default()
+    }
+    fn loaded_bytes(&self, handle: LoadHandle) -> Option<usize> {
+        self.load_graph.lock().get(&handle).map(|info| info.bytes)
+    }
     fn get_load_status_handle(&self, handle: LoadHandle) -> LoadStatus {
+        if let Some(status) = self.source_loader.statuses.lock().get(&handle) {
+            return (*status).into();
+        }
         self.loader.get_load_status(handle)
     }
 
@@ -236,6 +899,7 @@ impl Loader for DefaultLoader {
         let tracker = progress.create_tracker();
         let tracker = Box::new(tracker);
         let handle = self.handle_allocator.alloc();
+        self.add_local_ref(handle, AssetTypeId(A::UUID));
         let version = 0;
         processing_queue.enqueue_from_data(handle, data, tracker, version);
         Handle::<A>::new(self.ref_sender.clone(), handle)
@@ -244,7 +908,11 @@ impl Loader for DefaultLoader {
     fn init_world(&mut self, resources: &mut Resources) {
         for (_, storage) in self.storage_map.storages_by_asset_uuid.iter() {
             (storage.create_storage)(resources, &self.indirection_table);
+            (storage.create_resources)(resources);
         }
+        resources.get_or_insert_with(AssetMemoryUsage::default);
+        resources.get_or_insert_with(EventChannel::<AssetReloaded>::new);
+        self.update_thread_pool(resources);
     }
     fn init_dispatcher(&mut self, builder: &mut DispatcherBuilder) {
         for (_, storage) in self.storage_map.storages_by_asset_uuid.iter() {
@@ -253,14 +921,36 @@ impl Loader for DefaultLoader {
     }
 
     fn process(&mut self, resources: &Resources) -> Result<(), Box<dyn Error + Send>> {
+        self.update_thread_pool(resources);
         loop {
             match self.ref_receiver.try_recv() {
                 Err(TryRecvError::Empty) => break,
                 Err(TryRecvError::Disconnected) => panic!("RefOp receiver disconnected"),
                 Ok(RefOp::Decrease(handle)) => {
-                    self.loader.remove_ref(handle);
+                    if let Some(refs) = self.local_refs.lock().get_mut(&handle) {
+                        refs.count = refs.count.saturating_sub(1);
+                        if refs.count == 0 {
+                            self.unreferenced.push(handle);
+                        }
+                        continue;
+                    }
+                    if !self.retain_last_ref(handle) {
+                        self.loader.remove_ref(handle);
+                    }
                 }
                 Ok(RefOp::Increase(handle)) => {
+                    if let Some(refs) = self.local_refs.lock().get_mut(&handle) {
+                        refs.count += 1;
+                        self.unreferenced
+                            .retain(|unreferenced| *unreferenced != handle);
+                        continue;
+                    }
+                    // The new reference takes over the one the loader kept.
+                    if self.retained.remove(&handle).is_some() {
+                        self.unreferenced
+                            .retain(|unreferenced| *unreferenced != handle);
+                        continue;
+                    }
                     self.loader
                         .get_load_info(handle)
                         .map(|info| self.loader.add_ref(info.asset_id));
@@ -270,7 +960,15 @@ impl Loader for DefaultLoader {
                 }
             }
         }
-        let storages = WorldStorages::new(resources, &self.storage_map, &self.ref_sender);
+        self.process_source_loads(resources);
+        self.evict_unreferenced(resources);
+        self.update_memory_usage(resources);
+        let storages = WorldStorages::new(
+            resources,
+            &self.storage_map,
+            &self.ref_sender,
+            &self.load_graph,
+        );
         self.loader.process(&storages, &AssetIndirectionResolver)
     }
 }
@@ -300,6 +998,32 @@ impl IndirectionResolver for AssetIndirectionResolver {
     }
 }
 
+/// Event published when a loaded asset is replaced by a new version, e.g. when its source file
+/// was edited while the asset daemon is running.
+///
+/// Read it from the `EventChannel<AssetReloaded>` resource to rebuild data derived from an asset.
+///
+/// # Examples
+///
+/// ```ignore
+/// for event in reloaded_channel.read(&mut reader_id) {
+///     if event.type_id == AssetTypeId(TileMap::UUID) && event.handle == map_handle.load_handle() {
+///         rebuild_navmesh();
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AssetReloaded {
+    /// UUID of the asset type.
+    pub type_id: AssetTypeId,
+    /// Load handle of the asset.
+    pub handle: LoadHandle,
+    /// Version of the asset before the reload.
+    pub old_version: u32,
+    /// Version of the asset after the reload.
+    pub new_version: u32,
+}
+
 /// Storage for a particular asset type.
 ///
 /// This trait abtracts over the bridge between `distill_loader` and Amethyst's asset storage. These
@@ -322,13 +1046,72 @@ pub trait AssetTypeStorage {
         version: u32,
     ) -> Result<(), Box<dyn Error + Send>>;
 
+    /// Updates an asset that was imported from a [Source](crate::Source) without the asset
+    /// daemon. The asset is committed as soon as it has been processed.
+    ///
+    /// # Parameters
+    ///
+    /// * `handle`: Load handle of the asset.
+    /// * `data`: Asset data bytes (uncompressed).
+    /// * `tracker`: Tracker to notify when the asset has been processed.
+    /// * `version`: Version of the asset.
+    ///
+    /// The default implementation fails the load, for storages which only load from the asset
+    /// daemon.
+    fn update_asset_from_source(
+        &self,
+        handle: LoadHandle,
+        _data: &[u8],
+        tracker: Box<dyn Tracker>,
+        _version: u32,
+    ) -> Result<(), Box<dyn Error + Send>> {
+        let message = "This asset storage can't load assets from sources";
+        tracker.fail(
+            handle.0,
+            "",
+            String::new(),
+            AmethystError::from_string(message),
+        );
+        Err(AmethystError::from_string(message).into_error())
+    }
+
+    /// Frees assets from `candidates`: all of them if the storage has no memory budget,
+    /// otherwise in order while the storage is above its budget.
+    ///
+    /// Returns the load handles of the freed assets.
+    ///
+    /// # Parameters
+    ///
+    /// * `candidates`: Load handles of assets which are no longer referenced.
+    ///
+    /// The default implementation returns all candidates without removing them, for storages
+    /// which only load from the asset daemon. Their assets are then freed through
+    /// [free](#method.free).
+    fn evict(&mut self, candidates: &[LoadHandle]) -> Vec<LoadHandle> {
+        candidates.to_vec()
+    }
+
+    /// Returns the memory use of the loaded assets.
+    ///
+    /// The default implementation reports no assets, named after the storage type.
+    fn memory_usage(&self) -> AssetTypeUsage {
+        AssetTypeUsage {
+            name: std::any::type_name::<Self>(),
+            count: 0,
+            bytes: None,
+            budget: None,
+        }
+    }
+
     /// Commits an asset.
     ///
+    /// Returns the version of the asset that was replaced, if the asset was reloaded.
+    ///
     /// # Parameters
     ///
     /// * `handle`: Load handle of the asset.
     /// * `version`: Version of the asset -- this will be a new version for each hot reload.
-    fn commit_asset_version(&mut self, handle: LoadHandle, version: u32);
+    fn commit_asset_version(&mut self, handle: LoadHandle, version: u32) -> Option<u32>;
 
     /// Frees an asset.
     ///
@@ -364,9 +1147,54 @@ where
         }
     }
 
-    fn commit_asset_version(&mut self, handle: LoadHandle, version: u32) {
-        self.1.commit_asset(handle, version);
+    fn update_asset_from_source(
+        &self,
+        handle: LoadHandle,
+        data: &[u8],
+        tracker: Box<dyn Tracker>,
+        version: u32,
+    ) -> Result<(), Box<dyn Error + Send>> {
+        match bincode::deserialize::<Intermediate>(data) {
+            Err(err) => {
+                let message = format!("{}", err);
+                tracker.fail(
+                    handle.0,
+                    "",
+                    String::new(),
+                    AmethystError::from_string(message.clone()),
+                );
+                Err(AmethystError::from_string(message).into_error())
+            }
+            Ok(asset) => {
+                self.0.enqueue_processed(
+                    Ok(asset),
+                    handle,
+                    LoadNotifier::new(handle, None, Some(tracker)),
+                    version,
+                    true,
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn evict(&mut self, candidates: &[LoadHandle]) -> Vec<LoadHandle> {
+        self.1.release(candidates)
+    }
+
+    fn memory_usage(&self) -> AssetTypeUsage {
+        AssetTypeUsage {
+            name: std::any::type_name::<Asset>(),
+            count: self.1.len(),
+            bytes: self.1.size_bytes(),
+            budget: self.1.budget(),
+        }
+    }
+
+    fn commit_asset_version(&mut self, handle: LoadHandle, version: u32) -> Option<u32> {
+        let old_version = self.1.commit_asset(handle, version);
         self.0.enqueue_changed(handle);
+        old_version
     }
 
     fn free(&mut self, handle: LoadHandle, version: u32) {
@@ -417,6 +1245,7 @@ struct WorldStorages<'a> {
     storage_map: &'a AssetStorageMap,
     ref_sender: &'a Sender<RefOp>,
     resources: &'a Resources,
+    load_graph: &'a Mutex<LoadGraph>,
 }
 
 impl<'a> WorldStorages<'a> {
@@ -424,11 +1253,13 @@ impl<'a> WorldStorages<'a> {
         resources: &'a Resources,
         storage_map: &'a AssetStorageMap,
         ref_sender: &'a Sender<RefOp>,
+        load_graph: &'a Mutex<LoadGraph>,
     ) -> WorldStorages<'a> {
         WorldStorages {
             storage_map,
             ref_sender,
             resources,
+            load_graph,
         }
     }
 }
@@ -445,10 +1276,15 @@ impl<'a> distill_loader::storage::AssetStorage for WorldStorages<'a> {
     ) -> Result<(), Box<dyn Error + Send>> {
         let moved_op = RefCell::new(Some(load_op));
         let mut result = None;
-        if let Some(asset_type) = self.storage_map.storages_by_data_uuid.get(asset_type) {
+        let recorder = DependencyRecorder {
+            loader_info,
+            dependencies: Mutex::new(Vec::new()),
+        };
+        let asset_type = self.storage_map.storages_by_data_uuid.get(asset_type);
+        if let Some(asset_type) = asset_type {
             (asset_type.with_storage)(self.resources, &mut |storage: &mut dyn AssetTypeStorage| {
                 futures_executor::block_on(SerdeContext::with(
-                    loader_info,
+                    &recorder,
                     self.ref_sender.clone(),
                     async {
                         result = Some(storage.update_asset(
@@ -461,6 +1297,16 @@ impl<'a> distill_loader::storage::AssetStorage for WorldStorages<'a> {
                 ))
             });
         }
+        self.load_graph.lock().insert(
+            load_handle,
+            AssetLoadInfo {
+                dependencies: recorder.dependencies.into_inner(),
+                packed_dependencies: Vec::new(),
+                bytes: data.len(),
+                version,
+                asset_type: asset_type.map(|asset_type| asset_type.asset_uuid),
+            },
+        );
         result.unwrap()
     }
 
@@ -470,17 +1316,36 @@ impl<'a> distill_loader::storage::AssetStorage for WorldStorages<'a> {
         load_handle: LoadHandle,
         version: u32,
     ) {
-        (self
+        let asset_type_info = self
             .storage_map
             .storages_by_data_uuid
             .get(asset_type)
-            .expect("could not find asset type")
-            .with_storage)(self.resources, &mut |storage: &mut dyn AssetTypeStorage| {
-            storage.commit_asset_version(load_handle, version);
-        });
+            .expect("could not find asset type");
+        let mut old_version = None;
+        (asset_type_info.with_storage)(
+            self.resources,
+            &mut |storage: &mut dyn AssetTypeStorage| {
+                old_version = storage.commit_asset_version(load_handle, version);
+            },
+        );
+        if let Some(old_version) = old_version {
+            if let Some(mut reloaded) = self.resources.get_mut::<EventChannel<AssetReloaded>>() {
+                reloaded.single_write(AssetReloaded {
+                    type_id: asset_type_info.asset_uuid,
+                    handle: load_handle,
+                    old_version,
+                    new_version: version,
+                });
+            }
+        }
     }
 
     fn free(&self, asset_type: &AssetTypeId, load_handle: LoadHandle, version: u32) {
+        let mut load_graph = self.load_graph.lock();
+        if load_graph.get(&load_handle).map(|info| info.version) == Some(version) {
+            load_graph.remove(&load_handle);
+        }
+        drop(load_graph);
         (self
             .storage_map
             .storages_by_data_uuid
@@ -502,6 +1367,8 @@ pub struct AssetType {
     /// Function to create the `AssetTypeStorage`'s resources in the `World`.
     pub create_storage: fn(&mut Resources, &IndirectionTable),
     pub register_system: fn(&mut DispatcherBuilder),
+    /// Function to insert further resources the processor system needs.
+    pub create_resources: fn(&mut Resources),
     /// Function that runs another function, passing in the `AssetTypeStorage`.
     pub with_storage: fn(&Resources, &mut dyn FnMut(&mut dyn AssetTypeStorage)),
 }
@@ -523,6 +1390,22 @@ crate::inventory::collect!(AssetType);
 /// This function is not intended to be called be directly. Use the `register_asset_type!` macro
 /// macro instead.
 pub fn create_asset_type<Intermediate, Asset, ProcessorSystem>() -> AssetType
+where
+    Asset: 'static + TypeUuid + Send + Sync,
+    for<'a> Intermediate: 'static + Deserialize<'a> + TypeUuid + Send,
+    ProcessorSystem: System + Default + 'static,
+{
+    create_asset_type_with_resources::<Intermediate, Asset, ProcessorSystem>(|_| {})
+}
+
+/// Creates an `AssetType` to be stored in the `AssetType` `inventory`, whose processor system
+/// needs the resources inserted by `create_resources`.
+///
+/// This function is not intended to be called be directly. Use the `register_asset_type!` macro
+/// macro instead.
+pub fn create_asset_type_with_resources<Intermediate, Asset, ProcessorSystem>(
+    create_resources: fn(&mut Resources),
+) -> AssetType
 where
     Asset: 'static + TypeUuid + Send + Sync,
     for<'a> Intermediate: 'static + Deserialize<'a> + TypeUuid + Send,
@@ -541,6 +1424,7 @@ where
         register_system: |builder| {
             builder.add_system(ProcessorSystem::default());
         },
+        create_resources,
         with_storage: |res, func| {
             func(&mut (
                 res.get::<ProcessingQueue<Intermediate>>()
@@ -556,6 +1440,10 @@ where
 
 /// Registers an asset type which automatically prepares `AssetStorage` and `ProcessingQueue`.
 ///
+/// An optional function after the processor system inserts further resources the processor
+/// system needs, e.g. `register_asset_type!(MeshData => Mesh; MeshProcessorSystem; init)`. It is
+/// called with the other resources of the asset type when the `LoaderBundle` is loaded.
+///
 /// # Examples
 ///
 /// ```
@@ -607,4 +1495,13 @@ macro_rules! register_asset_type {
             $crate::create_asset_type::<$intermediate, $asset, $system>()
         }
     };
+    ($intermediate:ty => $asset:ty; $system:ty; $create_resources:expr) => {
+        $crate::register_asset_type!(amethyst_assets; $intermediate => $asset; $system; $create_resources);
+    };
+    ($krate:ident; $intermediate:ty => $asset:ty; $system:ty; $create_resources:expr) => {
+        $crate::inventory::submit!{
+            #![crate = $krate]
+            $crate::create_asset_type_with_resources::<$intermediate, $asset, $system>($create_resources)
+        }
+    };
 }
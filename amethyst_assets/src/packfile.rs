@@ -0,0 +1,363 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use amethyst_error::{format_err, Error, ResultExt};
+use distill::loader::{AssetRef, AssetTypeId, AssetUuid};
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error,
+    simple_importer::{get_source_importer, SourceAsset},
+};
+
+/// Version of the packfile layout, bumped whenever the layout changes.
+const PACKFILE_VERSION: u32 = 2;
+
+/// Assets baked from one or more asset directories, for release builds that run
+/// without the asset daemon.
+///
+/// Every file is imported with the [SourceImporter](crate::SourceImporter) registered for
+/// its extension when the packfile is baked, so loading from a packfile only has to
+/// deserialize the asset data. Files without a registered importer are skipped.
+///
+/// Importers are collected with `inventory`, so only the importers linked into the baking
+/// binary are used. Bake the packfile from the game itself, e.g. behind a command line flag,
+/// to make sure the importers of all asset types the game uses are available.
+///
+/// Assets keep the UUIDs and dependencies their importer assigned. When an asset is loaded
+/// from the packfile, its load dependencies are loaded from the packfile as well.
+///
+/// # Examples
+///
+/// ```no_run
+/// use amethyst_assets::Packfile;
+///
+/// # fn main() -> Result<(), amethyst_error::Error> {
+/// Packfile::bake(&["assets"])?.write("assets.pack")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Packfile {
+    version: u32,
+    entries: BTreeMap<String, Vec<PackedAsset>>,
+    /// Paths of the files the assets with a UUID were baked from.
+    #[serde(skip)]
+    ids: FnvHashMap<AssetUuid, String>,
+}
+
+impl Default for Packfile {
+    fn default() -> Self {
+        Packfile::new()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PackedAsset {
+    id: Option<AssetUuid>,
+    type_id: [u8; 16],
+    data: Vec<u8>,
+    load_deps: Vec<AssetRef>,
+    build_deps: Vec<AssetRef>,
+}
+
+impl Packfile {
+    /// Creates an empty packfile.
+    pub fn new() -> Self {
+        Packfile {
+            version: PACKFILE_VERSION,
+            entries: BTreeMap::new(),
+            ids: FnvHashMap::default(),
+        }
+    }
+
+    /// Bakes all files in the given asset directories into a packfile.
+    ///
+    /// Assets are keyed by their path relative to the asset directory they were found in,
+    /// with `/` as separator, which is the path they are loaded with.
+    ///
+    /// # Parameters
+    ///
+    /// - `asset_dirs`: Directories to bake, searched recursively.
+    pub fn bake<P: AsRef<Path>>(asset_dirs: &[P]) -> Result<Self, Error> {
+        let mut packfile = Packfile::new();
+        for dir in asset_dirs {
+            let dir = dir.as_ref();
+            for file in files_in(dir)? {
+                let path = packed_path(
+                    file.strip_prefix(dir)
+                        .expect("Baked file is not inside its asset directory"),
+                );
+                if get_source_importer(&path).is_none() {
+                    log::debug!("Skipping {:?}: no `SourceImporter` is registered", path);
+                    continue;
+                }
+                let bytes = fs::read(&file)
+                    .with_context(|_| format_err!("Failed to read file {:?}", file))
+                    .with_context(|_| error::Error::Source)?;
+                packfile.import(path, bytes)?;
+            }
+        }
+        Ok(packfile)
+    }
+
+    /// Imports the bytes of a source file and adds the resulting assets under `path`.
+    ///
+    /// Replaces any assets previously added under the same path.
+    pub fn import(&mut self, path: impl Into<String>, bytes: Vec<u8>) -> Result<(), Error> {
+        let path = path.into();
+        let importer = get_source_importer(&path)
+            .ok_or_else(|| format_err!("No `SourceImporter` is registered for {:?}", path))?;
+        let assets = (importer.import)(bytes)
+            .with_context(|_| error::Error::Asset(path.clone()))?
+            .into_iter()
+            .map(|asset| {
+                PackedAsset {
+                    id: asset.id,
+                    type_id: asset.type_id.0,
+                    data: asset.data,
+                    load_deps: asset.load_deps,
+                    build_deps: asset.build_deps,
+                }
+            })
+            .collect();
+        log::debug!("Baked {:?}", path);
+        self.insert(path, assets);
+        Ok(())
+    }
+
+    /// Adds assets under `path`, replacing the assets previously added under it.
+    fn insert(&mut self, path: String, assets: Vec<PackedAsset>) {
+        if let Some(replaced) = self.entries.insert(path.clone(), assets) {
+            for id in replaced.iter().filter_map(|asset| asset.id) {
+                if self.ids.get(&id) == Some(&path) {
+                    self.ids.remove(&id);
+                }
+            }
+        }
+        for id in self.entries[&path].iter().filter_map(|asset| asset.id) {
+            self.ids.insert(id, path.clone());
+        }
+    }
+
+    /// Reads a packfile written by [write](#method.write).
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|_| format_err!("Failed to open packfile {:?}", path))?;
+        let mut packfile: Packfile = bincode::deserialize_from(BufReader::new(file))
+            .with_context(|_| format_err!("Failed to read packfile {:?}", path))?;
+        if packfile.version != PACKFILE_VERSION {
+            return Err(format_err!(
+                "Packfile {:?} has version {}, expected version {}. Bake it again.",
+                path,
+                packfile.version,
+                PACKFILE_VERSION
+            ));
+        }
+        for (path, assets) in &packfile.entries {
+            for id in assets.iter().filter_map(|asset| asset.id) {
+                packfile.ids.insert(id, path.clone());
+            }
+        }
+        Ok(packfile)
+    }
+
+    /// Writes the packfile to the given path.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|_| format_err!("Failed to create packfile {:?}", path))?;
+        bincode::serialize_into(BufWriter::new(file), self)
+            .with_context(|_| format_err!("Failed to write packfile {:?}", path))?;
+        Ok(())
+    }
+
+    /// Returns the assets baked from the file at `path`.
+    pub fn get(&self, path: &str) -> Option<Vec<SourceAsset>> {
+        self.entries.get(path).map(|assets| {
+            assets
+                .iter()
+                .map(|asset| {
+                    SourceAsset {
+                        id: asset.id,
+                        type_id: AssetTypeId(asset.type_id),
+                        data: asset.data.clone(),
+                        load_deps: asset.load_deps.clone(),
+                        build_deps: asset.build_deps.clone(),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the path of the file the referenced asset was baked from and the UUID of its
+    /// data type, if the packfile contains it.
+    ///
+    /// Paths refer to files by the path they are loaded with. Files with more than one asset
+    /// resolve to their first asset.
+    pub fn resolve(&self, asset_ref: &AssetRef) -> Option<(&str, AssetTypeId)> {
+        let (path, asset) = match asset_ref {
+            AssetRef::Uuid(id) => {
+                let (path, assets) = self.entries.get_key_value(self.ids.get(id)?)?;
+                (path, assets.iter().find(|asset| asset.id == Some(*id))?)
+            }
+            AssetRef::Path(path) => {
+                let (path, assets) = self.entries.get_key_value(&packed_path(path))?;
+                (path, assets.first()?)
+            }
+        };
+        Some((path.as_str(), AssetTypeId(asset.type_id)))
+    }
+
+    /// Returns the paths of all baked files.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Returns the number of baked files.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no files were baked.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Returns the path a file is packed under, with `/` as separator.
+fn packed_path(path: &Path) -> String {
+    path.iter()
+        .map(|c| c.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Recursively collects the files in `dir`, skipping hidden files and asset metadata.
+fn files_in(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = fs::read_dir(&dir)
+            .with_context(|_| format_err!("Failed to read directory {:?}", dir))?;
+        for entry in entries {
+            let path = entry
+                .with_context(|_| format_err!("Failed to read directory {:?}", dir))?
+                .path();
+            let hidden = path
+                .file_name()
+                .map_or(false, |name| name.to_string_lossy().starts_with('.'));
+            if hidden || path.extension().map_or(false, |ext| ext == "meta") {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packfile_round_trips_baked_assets() {
+        let mut packfile = Packfile::new();
+        packfile.insert(
+            "subdir/asset.bin".to_string(),
+            vec![PackedAsset {
+                id: None,
+                type_id: [7; 16],
+                data: vec![1, 2, 3],
+                load_deps: Vec::new(),
+                build_deps: Vec::new(),
+            }],
+        );
+
+        let path = std::env::temp_dir().join("amethyst_assets_packfile_round_trip.pack");
+        packfile.write(&path).expect("Failed to write packfile");
+        let packfile = Packfile::open(&path).expect("Failed to open packfile");
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(packfile.len(), 1);
+        let assets = packfile.get("subdir/asset.bin").unwrap();
+        assert_eq!(assets[0].type_id, AssetTypeId([7; 16]));
+        assert_eq!(assets[0].data, vec![1, 2, 3]);
+        assert!(packfile.get("missing.bin").is_none());
+    }
+
+    #[test]
+    fn packfile_rejects_other_versions() {
+        let packfile = Packfile {
+            version: PACKFILE_VERSION + 1,
+            entries: BTreeMap::new(),
+            ids: FnvHashMap::default(),
+        };
+        let path = std::env::temp_dir().join("amethyst_assets_packfile_version.pack");
+        packfile.write(&path).expect("Failed to write packfile");
+        let result = Packfile::open(&path);
+        let _ = fs::remove_file(&path);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn packfile_resolves_dependencies_between_assets() {
+        let texture = AssetUuid([1; 16]);
+        let material = AssetUuid([2; 16]);
+        let mut packfile = Packfile::new();
+        packfile.insert(
+            "texture.png".to_string(),
+            vec![PackedAsset {
+                id: Some(texture),
+                type_id: [7; 16],
+                data: vec![1],
+                load_deps: Vec::new(),
+                build_deps: Vec::new(),
+            }],
+        );
+        packfile.insert(
+            "materials/material.mtl".to_string(),
+            vec![PackedAsset {
+                id: Some(material),
+                type_id: [8; 16],
+                data: vec![2],
+                load_deps: vec![AssetRef::Uuid(texture)],
+                build_deps: vec![AssetRef::Path(PathBuf::from("texture.png"))],
+            }],
+        );
+
+        let path = std::env::temp_dir().join("amethyst_assets_packfile_dependencies.pack");
+        packfile.write(&path).expect("Failed to write packfile");
+        let packfile = Packfile::open(&path).expect("Failed to open packfile");
+        let _ = fs::remove_file(&path);
+
+        let assets = packfile.get("materials/material.mtl").unwrap();
+        assert_eq!(assets[0].id, Some(material));
+        assert_eq!(assets[0].load_deps, vec![AssetRef::Uuid(texture)]);
+        for dependency in assets[0].load_deps.iter().chain(&assets[0].build_deps) {
+            assert_eq!(
+                packfile.resolve(dependency),
+                Some(("texture.png", AssetTypeId([7; 16])))
+            );
+        }
+        assert_eq!(
+            packfile.resolve(&AssetRef::Uuid(material)),
+            Some(("materials/material.mtl", AssetTypeId([8; 16])))
+        );
+        assert!(packfile
+            .resolve(&AssetRef::Uuid(AssetUuid([3; 16])))
+            .is_none());
+    }
+}
@@ -1,11 +1,10 @@
 use amethyst_core::ecs::World;
 use distill::importer as distill_importer;
 use distill_importer::{typetag, SerdeImportable};
-use fnv::FnvHashSet;
 use serde::{Deserialize, Serialize};
 use type_uuid::TypeUuid;
 
-use crate::{asset::Asset, Handle, WeakHandle};
+use crate::{asset::Asset, Handle};
 
 /// Prefab Asset, containing a cooked world.
 #[derive(TypeUuid, Serialize, Deserialize, SerdeImportable)]
@@ -20,9 +19,6 @@ pub struct Prefab {
     #[serde(skip)]
     pub(crate) dependencies: Vec<Handle<Prefab>>,
 
-    #[serde(skip)]
-    pub(crate) dependers: FnvHashSet<WeakHandle>,
-
     /// Incremented everytime the prefab is cooked.
     #[serde(skip)]
     pub(crate) version: u32,
@@ -43,7 +39,6 @@ impl Default for Prefab {
         Prefab {
             raw: legion_prefab::Prefab::new(World::default()),
             dependencies: Vec::new(),
-            dependers: FnvHashSet::default(),
             cooked: None,
             version: 0,
         }
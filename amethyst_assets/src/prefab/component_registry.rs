@@ -3,7 +3,7 @@ use std::{collections::HashMap, ops::Range};
 use amethyst_core::ecs::{
     storage::{Archetype, Component, ComponentTypeId, ComponentWriter, Components},
     world::EntityHasher,
-    Entity, Resources,
+    Entity, IntoQuery, Resources, World,
 };
 use fnv::{FnvBuildHasher, FnvHashMap};
 use legion_prefab::{
@@ -11,6 +11,59 @@ use legion_prefab::{
 };
 use prefab_format::ComponentTypeUuid;
 
+/// A component which refers to other entities.
+///
+/// When entities are copied into a prefab with `Prefab::from_world` or
+/// `Prefab::from_entity_tree`, its references are pointed at the copied entities. Use the
+/// [register_entity_remap!](crate::register_entity_remap) macro to register an implementation.
+pub trait RemapEntities: Component {
+    /// Points the entity references of the component at the entities they were copied to.
+    ///
+    /// Returns false if the component has to be removed, e.g. because it refers to an entity
+    /// which was not copied.
+    ///
+    /// # Parameters
+    ///
+    /// - `entity_map`: Maps the copied entities to their copies.
+    fn remap_entities(&mut self, entity_map: &HashMap<Entity, Entity, EntityHasher>) -> bool;
+}
+
+/// Registration of a [RemapEntities] implementation, collected with `inventory` by
+/// [register_entity_remap!](crate::register_entity_remap).
+#[derive(Debug)]
+pub struct EntityRemapRegistration {
+    remap: fn(&mut World, &HashMap<Entity, Entity, EntityHasher>),
+}
+
+impl EntityRemapRegistration {
+    /// Creates the registration of the entity references of `T`.
+    pub fn of<T: RemapEntities>() -> Self {
+        EntityRemapRegistration {
+            remap: remap_entities::<T>,
+        }
+    }
+}
+
+inventory::collect!(EntityRemapRegistration);
+
+/// Remaps the entity references of all components of type `T` in `world`.
+fn remap_entities<T: RemapEntities>(
+    world: &mut World,
+    entity_map: &HashMap<Entity, Entity, EntityHasher>,
+) {
+    let mut removed = Vec::new();
+    <(Entity, &mut T)>::query().for_each_mut(world, |(entity, component)| {
+        if !component.remap_entities(entity_map) {
+            removed.push(*entity);
+        }
+    });
+    for entity in removed {
+        if let Some(mut entry) = world.entry(entity) {
+            entry.remove_component::<T>();
+        }
+    }
+}
+
 /// registers prefab components that can be created by prefab
 /// use register_component_type! macro to add new components
 #[derive(Default)]
@@ -18,6 +71,7 @@ pub struct ComponentRegistryBuilder {
     components: FnvHashMap<ComponentTypeId, ComponentRegistration>,
     components_by_uuid: FnvHashMap<ComponentTypeUuid, ComponentRegistration>,
     spawn_handler_set: SpawnCloneImplHandlerSet,
+    entity_remaps: Vec<fn(&mut World, &HashMap<Entity, Entity, EntityHasher>)>,
 }
 
 impl ComponentRegistryBuilder {
@@ -28,6 +82,9 @@ impl ComponentRegistryBuilder {
         for registration in comp_registrations {
             self = self.register_component(registration);
         }
+        for registration in inventory::iter::<EntityRemapRegistration> {
+            self.entity_remaps.push(registration.remap);
+        }
 
         self
     }
@@ -41,6 +98,14 @@ impl ComponentRegistryBuilder {
         self
     }
 
+    /// registers the entity references of a component, which are remapped when entities are
+    /// copied into a prefab
+    pub fn register_entity_remap<T: RemapEntities>(mut self) -> Self {
+        self.entity_remaps
+            .push(EntityRemapRegistration::of::<T>().remap);
+        self
+    }
+
     /// adds a mapping between prefab component and spawned component
     pub fn add_spawn_mapping_into<FromT: Component + Clone + Into<IntoT>, IntoT: Component>(
         mut self,
@@ -83,6 +148,7 @@ impl ComponentRegistryBuilder {
             components: self.components,
             components_by_uuid: self.components_by_uuid,
             spawn_handler_set: self.spawn_handler_set,
+            entity_remaps: self.entity_remaps,
         }
     }
 }
@@ -92,6 +158,7 @@ pub struct ComponentRegistry {
     components: FnvHashMap<ComponentTypeId, ComponentRegistration>,
     components_by_uuid: FnvHashMap<ComponentTypeUuid, ComponentRegistration>,
     spawn_handler_set: SpawnCloneImplHandlerSet,
+    entity_remaps: Vec<fn(&mut World, &HashMap<Entity, Entity, EntityHasher>)>,
 }
 
 impl ComponentRegistry {
@@ -123,4 +190,35 @@ impl ComponentRegistry {
             entity_map,
         )
     }
+
+    /// points the entity references of all registered components in `world` at the entities
+    /// they were copied to
+    pub(crate) fn remap_entities(
+        &self,
+        world: &mut World,
+        entity_map: &HashMap<Entity, Entity, EntityHasher>,
+    ) {
+        for remap in &self.entity_remaps {
+            remap(world, entity_map);
+        }
+    }
+}
+
+/// Registers the [RemapEntities] implementation of a component, so its entity references are
+/// remapped when entities are copied into a prefab.
+///
+/// ```ignore
+/// amethyst_assets::register_entity_remap!(Target);
+/// ```
+#[macro_export]
+macro_rules! register_entity_remap {
+    ($component:ty) => {
+        $crate::register_entity_remap!(amethyst_assets; $component);
+    };
+    ($krate:ident; $component:ty) => {
+        $crate::inventory::submit! {
+            #![crate = $krate]
+            $crate::prefab::EntityRemapRegistration::of::<$component>()
+        }
+    };
 }
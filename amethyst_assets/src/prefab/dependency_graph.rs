@@ -0,0 +1,217 @@
+use fnv::{FnvHashMap, FnvHashSet};
+
+use crate::LoadHandle;
+
+/// Dependencies between prefabs, by load handle.
+///
+/// Used by the prefab processor to detect cyclic dependencies and to re-cook every prefab
+/// depending on a changed prefab exactly once, after the prefabs it depends on.
+#[derive(Debug, Default)]
+pub(crate) struct PrefabDependencyGraph {
+    dependencies: FnvHashMap<LoadHandle, Vec<LoadHandle>>,
+    dependers: FnvHashMap<LoadHandle, FnvHashSet<LoadHandle>>,
+}
+
+impl PrefabDependencyGraph {
+    /// Replaces the prefabs the given prefab depends on.
+    pub(crate) fn set_dependencies(&mut self, handle: LoadHandle, dependencies: Vec<LoadHandle>) {
+        if let Some(previous) = self.dependencies.remove(&handle) {
+            for dependency in previous {
+                if let Some(dependers) = self.dependers.get_mut(&dependency) {
+                    dependers.remove(&handle);
+                }
+            }
+        }
+        for dependency in &dependencies {
+            self.dependers
+                .entry(*dependency)
+                .or_default()
+                .insert(handle);
+        }
+        self.dependencies.insert(handle, dependencies);
+    }
+
+    /// Drops the prefabs for which `keep` returns false, such as prefabs which were freed.
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(LoadHandle) -> bool) {
+        self.dependencies.retain(|handle, _| keep(*handle));
+        let dependencies = &self.dependencies;
+        self.dependers.retain(|_, dependers| {
+            dependers.retain(|depender| dependencies.contains_key(depender));
+            !dependers.is_empty()
+        });
+    }
+
+    /// Returns a dependency cycle reachable from the given prefab, if any.
+    ///
+    /// The cycle is returned as the path of prefabs from the first prefab in the cycle back
+    /// to itself.
+    pub(crate) fn find_cycle(&self, handle: LoadHandle) -> Option<Vec<LoadHandle>> {
+        let mut finished = FnvHashSet::default();
+        let mut path = vec![handle];
+        let mut stack = vec![self.dependencies_of(handle).iter()];
+
+        while let Some(children) = stack.last_mut() {
+            if let Some(child) = children.next() {
+                if let Some(position) = path.iter().position(|h| h == child) {
+                    let mut cycle = path[position..].to_vec();
+                    cycle.push(*child);
+                    return Some(cycle);
+                }
+                if finished.contains(child) {
+                    continue;
+                }
+                path.push(*child);
+                stack.push(self.dependencies_of(*child).iter());
+            } else {
+                stack.pop();
+                finished.insert(path.pop().expect("Path and stack have the same length"));
+            }
+        }
+        None
+    }
+
+    /// Returns every prefab which directly or indirectly depends on one of the changed prefabs,
+    /// ordered so that each prefab comes after the prefabs it depends on.
+    ///
+    /// Prefabs which are part of a dependency cycle are left out.
+    pub(crate) fn dependers_in_cook_order(&self, changed: &[LoadHandle]) -> Vec<LoadHandle> {
+        // Collect all transitive dependers.
+        let mut affected = FnvHashSet::default();
+        let mut pending: Vec<LoadHandle> = changed
+            .iter()
+            .flat_map(|handle| self.dependers_of(*handle))
+            .collect();
+        while let Some(handle) = pending.pop() {
+            if affected.insert(handle) {
+                pending.extend(self.dependers_of(handle));
+            }
+        }
+
+        // Topologically sort them, only counting dependencies which are affected themselves.
+        let mut remaining_dependencies: FnvHashMap<LoadHandle, usize> = affected
+            .iter()
+            .map(|handle| {
+                let count = self
+                    .dependencies_of(*handle)
+                    .iter()
+                    .filter(|dependency| affected.contains(*dependency))
+                    .count();
+                (*handle, count)
+            })
+            .collect();
+        let mut ready: Vec<LoadHandle> = remaining_dependencies
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(handle, _)| *handle)
+            .collect();
+        let mut order = Vec::with_capacity(affected.len());
+        while let Some(handle) = ready.pop() {
+            order.push(handle);
+            for depender in self.dependers_of(handle) {
+                if let Some(count) = remaining_dependencies.get_mut(&depender) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(depender);
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    fn dependencies_of(&self, handle: LoadHandle) -> &[LoadHandle] {
+        self.dependencies
+            .get(&handle)
+            .map_or(&[], |dependencies| dependencies.as_slice())
+    }
+
+    fn dependers_of(&self, handle: LoadHandle) -> impl Iterator<Item = LoadHandle> + '_ {
+        self.dependers
+            .get(&handle)
+            .into_iter()
+            .flat_map(|dependers| dependers.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(u64, &[u64])]) -> PrefabDependencyGraph {
+        let mut graph = PrefabDependencyGraph::default();
+        for (handle, dependencies) in edges {
+            graph.set_dependencies(
+                LoadHandle(*handle),
+                dependencies.iter().map(|h| LoadHandle(*h)).collect(),
+            );
+        }
+        graph
+    }
+
+    #[test]
+    fn diamond_is_not_a_cycle() {
+        let graph = graph(&[(1, &[2, 3]), (2, &[4]), (3, &[4])]);
+        assert_eq!(graph.find_cycle(LoadHandle(1)), None);
+    }
+
+    #[test]
+    fn cycles_are_found() {
+        let graph = graph(&[(1, &[2]), (2, &[3]), (3, &[2])]);
+        assert_eq!(
+            graph.find_cycle(LoadHandle(1)),
+            Some(vec![LoadHandle(2), LoadHandle(3), LoadHandle(2)])
+        );
+
+        let graph = graph(&[(1, &[1])]);
+        assert_eq!(
+            graph.find_cycle(LoadHandle(1)),
+            Some(vec![LoadHandle(1), LoadHandle(1)])
+        );
+    }
+
+    #[test]
+    fn dependers_of_diamond_are_cooked_once_in_order() {
+        let graph = graph(&[(1, &[2, 3]), (2, &[4]), (3, &[4])]);
+        let order = graph.dependers_in_cook_order(&[LoadHandle(4)]);
+
+        assert_eq!(order.len(), 3);
+        let position = |h: u64| order.iter().position(|o| *o == LoadHandle(h)).unwrap();
+        assert!(position(2) < position(1));
+        assert!(position(3) < position(1));
+    }
+
+    #[test]
+    fn replacing_dependencies_removes_dependers() {
+        let mut graph = graph(&[(1, &[2])]);
+        graph.set_dependencies(LoadHandle(1), vec![LoadHandle(3)]);
+
+        assert!(graph.dependers_in_cook_order(&[LoadHandle(2)]).is_empty());
+        assert_eq!(
+            graph.dependers_in_cook_order(&[LoadHandle(3)]),
+            vec![LoadHandle(1)]
+        );
+    }
+
+    #[test]
+    fn retained_graph_forgets_dropped_prefabs() {
+        let mut graph = graph(&[(1, &[2]), (2, &[3]), (4, &[3])]);
+        graph.retain(|handle| handle != LoadHandle(1) && handle != LoadHandle(4));
+
+        assert_eq!(graph.dependencies.len(), 1);
+        assert!(graph.dependers_of(LoadHandle(2)).next().is_none());
+        assert_eq!(
+            graph.dependers_in_cook_order(&[LoadHandle(3)]),
+            vec![LoadHandle(2)]
+        );
+
+        graph.retain(|_| false);
+        assert!(graph.dependencies.is_empty());
+        assert!(graph.dependers.is_empty());
+    }
+
+    #[test]
+    fn cyclic_dependers_are_left_out() {
+        let graph = graph(&[(1, &[2]), (2, &[1, 3])]);
+        assert!(graph.dependers_in_cook_order(&[LoadHandle(3)]).is_empty());
+    }
+}
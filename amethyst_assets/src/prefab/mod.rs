@@ -1,21 +1,40 @@
-#[cfg(feature = "asset-daemon")]
+// The importer only depends on `distill::importer`, which is always enabled, so prefabs can
+// also be imported from sources without the asset daemon.
 mod importers;
-#[cfg(feature = "asset-daemon")]
 pub use importers::PrefabImporter;
 
 mod assets;
 pub use assets::Prefab;
 
+mod overrides;
+pub use overrides::PrefabOverrides;
+
+mod save;
+
 pub(crate) mod system;
 
 mod component_registry;
-pub use component_registry::{ComponentRegistry, ComponentRegistryBuilder};
+pub use component_registry::{
+    ComponentRegistry, ComponentRegistryBuilder, EntityRemapRegistration, RemapEntities,
+};
 pub use legion_prefab::{self, register_component_type, ComponentRegistration};
 pub use serde_diff::{self, SerdeDiff};
 
+mod dependency_graph;
 mod processor;
 
+inventory::submit! {
+    crate::SourceImporter {
+        extension: "prefab",
+        import: crate::import_source_with::<PrefabImporter>,
+    }
+}
+
 // register core components
 register_component_type!(amethyst_core::transform::Transform);
 register_component_type!(amethyst_core::transform::TransformValues);
 register_component_type!(amethyst_core::transform::Parent);
+
+inventory::submit! {
+    EntityRemapRegistration::of::<amethyst_core::transform::Parent>()
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use amethyst_core::ecs::{world::EntityHasher, Entity, World};
+use bincode::Options;
+use prefab_format::{ComponentTypeUuid, EntityUuid};
+use serde::{Deserialize, Serialize};
+use serde_diff::{Diff, SerdeDiff};
+use type_uuid::TypeUuid;
+
+use crate::prefab::ComponentRegistry;
+
+/// Per-instance patches applied to the components spawned from a `Handle<Prefab>`.
+///
+/// Add this component next to a `Handle<Prefab>` to place variants of the same prefab, e.g. with
+/// different positions or tints, without editing the prefab asset. The patches are serde-diff
+/// diffs, so only the changed fields are overridden and changes to the other fields of the prefab
+/// still show up in the instances. They are re-applied every time the prefab is (re)spawned.
+///
+/// Components are found by their `TypeUuid`, so they have to be registered with
+/// `register_component_type!`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut position = prefab_transform.clone();
+/// position.set_translation_xyz(10.0, 0.0, 0.0);
+///
+/// let overrides = PrefabOverrides::new().with_patch(&prefab_transform, &position);
+/// world.push((prefab_handle, overrides));
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PrefabOverrides {
+    patches: Vec<ComponentPatch>,
+}
+
+/// Serde-diff patch of one component of one prefab entity.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ComponentPatch {
+    /// Prefab entity to patch, or `None` for the root entity.
+    entity: Option<EntityUuid>,
+    component_type: ComponentTypeUuid,
+    /// Serde-diff `Diff`, serialized with bincode's default options.
+    diff: Vec<u8>,
+}
+
+impl PrefabOverrides {
+    /// Creates an empty set of overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a patch turning the `from` value of a component of the root entity into `to`.
+    ///
+    /// # Parameters
+    ///
+    /// - `from`: The value of the component in the prefab.
+    /// - `to`: The value of the component for this instance.
+    pub fn with_patch<C>(mut self, from: &C, to: &C) -> Self
+    where
+        C: SerdeDiff + TypeUuid,
+    {
+        self.add_patch(None, from, to);
+        self
+    }
+
+    /// Adds a patch turning the `from` value of a component into `to`.
+    ///
+    /// # Parameters
+    ///
+    /// - `entity`: UUID of the prefab entity to patch, or `None` for the root entity.
+    /// - `from`: The value of the component in the prefab.
+    /// - `to`: The value of the component for this instance.
+    pub fn add_patch<C>(&mut self, entity: Option<EntityUuid>, from: &C, to: &C)
+    where
+        C: SerdeDiff + TypeUuid,
+    {
+        let diff = bincode::DefaultOptions::new()
+            .serialize(&Diff::serializable(from, to))
+            .expect("Failed to serialize prefab override");
+        self.add_raw_patch(entity, C::UUID, diff);
+    }
+
+    /// Adds a serialized patch, e.g. one that was read from a level file.
+    ///
+    /// # Parameters
+    ///
+    /// - `entity`: UUID of the prefab entity to patch, or `None` for the root entity.
+    /// - `component_type`: `TypeUuid` of the component.
+    /// - `diff`: A serde-diff `Diff` serialized with bincode's default options.
+    pub fn add_raw_patch(
+        &mut self,
+        entity: Option<EntityUuid>,
+        component_type: ComponentTypeUuid,
+        diff: Vec<u8>,
+    ) {
+        self.patches.push(ComponentPatch {
+            entity,
+            component_type,
+            diff,
+        });
+    }
+
+    /// Returns the number of patches.
+    pub fn len(&self) -> usize {
+        self.patches.len()
+    }
+
+    /// Returns true if there are no patches.
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    /// Removes all patches.
+    pub fn clear(&mut self) {
+        self.patches.clear();
+    }
+
+    /// Applies the patches to the entities spawned from a prefab.
+    ///
+    /// # Parameters
+    ///
+    /// - `world`: World the prefab was spawned in.
+    /// - `root`: The entity holding the `Handle<Prefab>`.
+    /// - `prefab_entities`: Entities of the cooked prefab, by entity UUID.
+    /// - `entity_map`: Spawned entities, by entity of the cooked prefab.
+    /// - `component_registry`: Registry used to find the components to patch.
+    pub(crate) fn apply(
+        &self,
+        world: &mut World,
+        root: Entity,
+        prefab_entities: &HashMap<EntityUuid, Entity>,
+        entity_map: &HashMap<Entity, Entity, EntityHasher>,
+        component_registry: &ComponentRegistry,
+    ) {
+        for patch in &self.patches {
+            let entity = match patch.entity {
+                None => Some(root),
+                Some(uuid) => {
+                    prefab_entities
+                        .get(&uuid)
+                        .and_then(|prefab_entity| entity_map.get(prefab_entity))
+                        .copied()
+                }
+            };
+            let entity = match entity {
+                Some(entity) => entity,
+                None => {
+                    log::warn!(
+                        "Prefab override for missing entity {:x?} of {:?}",
+                        patch.entity,
+                        root
+                    );
+                    continue;
+                }
+            };
+            match component_registry
+                .components_by_uuid()
+                .get(&patch.component_type)
+            {
+                Some(registration) => {
+                    let mut deserializer = bincode::Deserializer::from_slice(
+                        &patch.diff,
+                        bincode::DefaultOptions::new(),
+                    );
+                    let mut deserializer =
+                        <dyn erased_serde::Deserializer<'_>>::erase(&mut deserializer);
+                    registration.apply_diff(&mut deserializer, world, entity);
+                }
+                None => {
+                    log::warn!(
+                        "Prefab override for unregistered component type {:x?}",
+                        patch.component_type
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::transform::Transform;
+
+    use super::*;
+    use crate::prefab::ComponentRegistryBuilder;
+
+    #[test]
+    fn overrides_patch_components_of_root_entity() {
+        let component_registry = ComponentRegistryBuilder::default()
+            .auto_register_components()
+            .build();
+        let mut world = World::default();
+        let prefab_transform = Transform::default();
+        let entity = world.push((prefab_transform,));
+
+        let mut instance_transform = prefab_transform;
+        instance_transform.set_translation_xyz(1.0, 2.0, 3.0);
+        let overrides = PrefabOverrides::new().with_patch(&prefab_transform, &instance_transform);
+        assert_eq!(overrides.len(), 1);
+
+        overrides.apply(
+            &mut world,
+            entity,
+            &HashMap::new(),
+            &HashMap::default(),
+            &component_registry,
+        );
+
+        let transform = *world
+            .entry_ref(entity)
+            .unwrap()
+            .get_component::<Transform>()
+            .unwrap();
+        assert_eq!(transform, instance_transform);
+    }
+}
@@ -2,15 +2,16 @@ use amethyst_core::{
     dispatcher::System,
     ecs::{systems::ParallelRunnable, SystemBuilder},
 };
+use amethyst_error::format_err;
 use distill::core::AssetUuid;
 use fnv::{FnvHashMap, FnvHashSet};
 use prefab_format::PrefabUuid;
 
 use crate::{
     self as amethyst_assets,
-    loader::{DefaultLoader, Loader},
-    prefab::{ComponentRegistry, Prefab},
-    storage::AssetStorage,
+    loader::{DefaultLoader, LoadStatus, Loader},
+    prefab::{dependency_graph::PrefabDependencyGraph, ComponentRegistry, Prefab},
+    storage::{AssetStorage, MutateAssetInStorage},
     AssetHandle, ProcessingQueue, ProcessingState, WeakHandle,
 };
 
@@ -29,13 +30,18 @@ impl Prefab {
         // prefab_lookup and entity_lookup are populated with all dependent prefabs/entities
         let mut prefab_cook_order: Vec<PrefabUuid> = vec![];
 
+        // Prefabs on the stack, to avoid looping forever on cyclic dependencies. Those are
+        // reported as load errors by the processor before a prefab is cooked.
+        let mut on_stack = FnvHashSet::default();
+        on_stack.insert(prefab.raw.prefab_id());
         let mut dependency_stack = vec![(prefab, prefab.dependencies.iter())];
 
         while let Some((cur_prefab, children)) = dependency_stack.last_mut() {
             if let Some(child_handle) = children.next() {
                 log::debug!("Checking for child prefab {:?}", child_handle);
                 if let Some(child_prefab) = storage.get(child_handle) {
-                    if prefab_lookup.contains_key(&child_prefab.raw.prefab_id()) {
+                    let child_id = child_prefab.raw.prefab_id();
+                    if prefab_lookup.contains_key(&child_id) || !on_stack.insert(child_id) {
                         continue;
                     }
 
@@ -48,6 +54,7 @@ impl Prefab {
                 // pop the stack.
                 prefab_cook_order.push(cur_prefab.raw.prefab_id());
                 prefab_lookup.insert(cur_prefab.raw.prefab_id(), &cur_prefab.raw);
+                on_stack.remove(&cur_prefab.raw.prefab_id());
                 dependency_stack.pop();
             }
         }
@@ -75,16 +82,18 @@ impl System for PrefabProcessorSystem {
                 .write_resource::<ProcessingQueue<Prefab>>()
                 .write_resource::<AssetStorage<Prefab>>()
                 .write_resource::<DefaultLoader>()
-                .build(
+                .build({
+                    let mut dependency_graph = PrefabDependencyGraph::default();
                     move |_, _, (component_registry, processing_queue, storage, loader), _| {
                         prefab_asset_processor(
                             component_registry,
                             processing_queue,
                             storage,
                             loader,
+                            &mut dependency_graph,
                         );
-                    },
-                ),
+                    }
+                }),
         )
     }
 }
@@ -94,40 +103,33 @@ fn prefab_asset_processor(
     processing_queue: &mut ProcessingQueue<Prefab>,
     storage: &mut AssetStorage<Prefab>,
     loader: &mut DefaultLoader,
+    dependency_graph: &mut PrefabDependencyGraph,
 ) -> Vec<crate::Handle<Prefab>> {
-    // Re-cook prefabs with changed dependencies.
-    // FIXME: deal with cyclic and diamond dependencies correctly
-    let mut visited = FnvHashSet::default();
+    // Forget freed prefabs. Prefabs which failed to load are kept while they are requested, so
+    // the prefabs of a cycle which are processed later still see its dependencies.
+    dependency_graph.retain(|handle| {
+        storage.holds(handle)
+            || !matches!(
+                loader.get_load_status_handle(handle),
+                LoadStatus::NotRequested | LoadStatus::Unloading
+            )
+    });
 
+    // Re-cook prefabs depending on changed prefabs, each one once and after its dependencies.
+    let mut changed = Vec::new();
     while let Some(dependee) = processing_queue.changed.pop() {
         log::debug!("Prefab Changed: {:?}", dependee);
-        let updates: Vec<(WeakHandle, legion_prefab::CookedPrefab)> = storage
-            .get_for_load_handle(dependee)
-            .iter()
-            .flat_map(|p| p.dependers.iter())
-            .flat_map(|weak_handle| {
-                storage
-                    .get_asset_with_version(weak_handle)
-                    .map(move |(prefab, _)| (weak_handle, prefab))
-            })
-            .map(|(weak_handle, prefab)| {
-                if visited.insert(weak_handle.load_handle()) {
-                    processing_queue.changed.push(weak_handle.load_handle());
-                }
-                (
-                    weak_handle.clone(),
-                    Prefab::cook_prefab(prefab, storage, component_registry),
-                )
-            })
-            .collect();
-
-        use crate::storage::MutateAssetInStorage;
-        for (handle, cooked_prefab) in updates.into_iter() {
-            storage.mutate_asset_in_storage(&handle, move |prefab| {
-                prefab.cooked = Some(cooked_prefab);
-                prefab.version += 1;
-            });
-        }
+        changed.push(dependee);
+    }
+    for depender in dependency_graph.dependers_in_cook_order(&changed) {
+        let cooked_prefab = match storage.get_for_load_handle(depender) {
+            Some(prefab) => Prefab::cook_prefab(prefab, storage, component_registry),
+            None => continue,
+        };
+        storage.mutate_asset_in_storage(&WeakHandle::new(depender), move |prefab| {
+            prefab.cooked = Some(cooked_prefab);
+            prefab.version += 1;
+        });
     }
 
     let mut loading = Vec::new();
@@ -146,6 +148,30 @@ fn prefab_asset_processor(
                 handle
             })
             .collect();
+        let dependencies: Vec<_> = prefab
+            .dependencies
+            .iter()
+            .map(|dependency| dependency.load_handle())
+            .collect();
+        loader.add_load_dependencies(*handle, dependencies.iter().copied());
+        dependency_graph.set_dependencies(*handle, dependencies);
+
+        if let Some(cycle) = dependency_graph.find_cycle(*handle) {
+            let cycle: Vec<_> = cycle
+                .iter()
+                .map(|handle| {
+                    match storage.get_for_load_handle(*handle) {
+                        Some(prefab) => format!("{:x?}", AssetUuid(prefab.raw.prefab_id())),
+                        None => format!("{:?}", handle),
+                    }
+                })
+                .collect();
+            return Err(format_err!(
+                "Prefab {:x?} has cyclic dependencies: {}",
+                AssetUuid(prefab.raw.prefab_id()),
+                cycle.join(" -> ")
+            ));
+        }
 
         Ok(
             if prefab
@@ -181,7 +207,7 @@ mod tests {
     use crate::{
         prefab::{ComponentRegistryBuilder, Prefab},
         processor::LoadNotifier,
-        Handle,
+        Completion, Handle, Progress, ProgressCounter,
     };
 
     struct Fixture {
@@ -189,6 +215,7 @@ mod tests {
         processing_queue: ProcessingQueue<Prefab>,
         prefab_storage: AssetStorage<Prefab>,
         component_registry: ComponentRegistry,
+        dependency_graph: PrefabDependencyGraph,
     }
 
     static INIT: Once = Once::new();
@@ -215,6 +242,7 @@ mod tests {
                 processing_queue,
                 prefab_storage,
                 component_registry,
+                dependency_graph: PrefabDependencyGraph::default(),
             }
         }
     }
@@ -227,6 +255,7 @@ mod tests {
             mut processing_queue,
             mut prefab_storage,
             component_registry,
+            mut dependency_graph,
         } = Fixture::setup();
 
         let raw_prefab = Prefab::default();
@@ -239,6 +268,7 @@ mod tests {
             &mut processing_queue,
             &mut prefab_storage,
             &mut loader,
+            &mut dependency_graph,
         );
 
         let asset = prefab_storage
@@ -255,6 +285,7 @@ mod tests {
             mut processing_queue,
             mut prefab_storage,
             component_registry,
+            mut dependency_graph,
         } = Fixture::setup();
 
         let mut prefab_root = Prefab::default();
@@ -276,6 +307,7 @@ mod tests {
             &mut processing_queue,
             &mut prefab_storage,
             &mut loader,
+            &mut dependency_graph,
         );
 
         let child_handle = children_handles.get(0).unwrap().load_handle();
@@ -293,15 +325,17 @@ mod tests {
             &mut processing_queue,
             &mut prefab_storage,
             &mut loader,
+            &mut dependency_graph,
         );
 
-        prefab_storage.commit_asset(child_handle, 0);
+        let _ = prefab_storage.commit_asset(child_handle, 0);
 
         prefab_asset_processor(
             &component_registry,
             &mut processing_queue,
             &mut prefab_storage,
             &mut loader,
+            &mut dependency_graph,
         );
 
         let asset = prefab_storage
@@ -309,4 +343,64 @@ mod tests {
             .expect("prefab is not in storage");
         assert!(asset.cooked.is_some());
     }
+
+    #[serial]
+    #[test]
+    fn cyclic_prefab_dependencies_fail_to_load() {
+        let Fixture {
+            mut loader,
+            mut processing_queue,
+            mut prefab_storage,
+            component_registry,
+            mut dependency_graph,
+        } = Fixture::setup();
+
+        let mut prefab_a = Prefab::default();
+        let mut prefab_b = Prefab::default();
+        let (id_a, id_b) = (prefab_a.raw.prefab_id(), prefab_b.raw.prefab_id());
+        prefab_a.raw.prefab_meta.prefab_refs.insert(
+            id_b,
+            PrefabRef {
+                overrides: HashMap::new(),
+            },
+        );
+        prefab_b.raw.prefab_meta.prefab_refs.insert(
+            id_a,
+            PrefabRef {
+                overrides: HashMap::new(),
+            },
+        );
+
+        let handle_a: Handle<Prefab> = loader.load_asset(AssetUuid(id_a));
+        let handle_b: Handle<Prefab> = loader.load_asset(AssetUuid(id_b));
+
+        let mut progress_counter = ProgressCounter::new();
+        for (prefab, handle) in vec![(prefab_a, &handle_a), (prefab_b, &handle_b)] {
+            let mut progress = &mut progress_counter;
+            progress.add_assets(1);
+            let tracker = Box::new(progress.create_tracker());
+            processing_queue.enqueue_processed(
+                Ok(prefab),
+                handle.load_handle(),
+                LoadNotifier::new(handle.load_handle(), None, Some(tracker)),
+                0,
+                true,
+            );
+        }
+
+        for _ in 0..2 {
+            prefab_asset_processor(
+                &component_registry,
+                &mut processing_queue,
+                &mut prefab_storage,
+                &mut loader,
+                &mut dependency_graph,
+            );
+        }
+
+        assert_eq!(progress_counter.num_failed(), 2);
+        assert_eq!(progress_counter.complete(), Completion::Failed);
+        assert!(!prefab_storage.contains(handle_a.load_handle()));
+        assert!(!prefab_storage.contains(handle_b.load_handle()));
+    }
 }
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use amethyst_core::{
+    ecs::{world::EntityHasher, Entity, IntoQuery, World},
+    transform::Parent,
+};
+use amethyst_error::{format_err, Error, ResultExt};
+use fnv::FnvHashMap;
+use legion_prefab::ComponentRegistration;
+use prefab_format::ComponentTypeUuid;
+
+use crate::prefab::{ComponentRegistry, Prefab, RemapEntities};
+
+/// Drops `Parent`s pointing outside of the prefab, so the prefab attaches to the entity it is
+/// spawned on.
+impl RemapEntities for Parent {
+    fn remap_entities(&mut self, entity_map: &HashMap<Entity, Entity, EntityHasher>) -> bool {
+        match entity_map.get(&self.0) {
+            Some(parent) => {
+                self.0 = *parent;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Prefab {
+    /// Creates a prefab from all entities of a live world, e.g. to write a save game.
+    ///
+    /// Components which are not registered in the `ComponentRegistry` are skipped. Entity
+    /// references of components which implement [RemapEntities], like `Parent`, are remapped to
+    /// the entities of the prefab.
+    ///
+    /// # Parameters
+    ///
+    /// - `world`: World to copy the entities from.
+    /// - `component_registry`: Registry of the components to copy.
+    pub fn from_world(world: &World, component_registry: &ComponentRegistry) -> Self {
+        let entities: Vec<Entity> = <Entity>::query().iter(world).copied().collect();
+        Self::from_entities(world, &entities, component_registry)
+    }
+
+    /// Creates a prefab from an entity and all entities below it in the `Parent` hierarchy,
+    /// e.g. to export a part of a level from an in-game editor.
+    ///
+    /// `root` becomes the first entity of the prefab and loses its `Parent`, so the prefab
+    /// attaches to the entity it is spawned on. Components which are not registered in the
+    /// `ComponentRegistry` are skipped, and entity references of components which implement
+    /// [RemapEntities] are remapped to the entities of the prefab.
+    ///
+    /// # Parameters
+    ///
+    /// - `world`: World to copy the entities from.
+    /// - `root`: Root entity of the hierarchy to copy.
+    /// - `component_registry`: Registry of the components to copy.
+    pub fn from_entity_tree(
+        world: &World,
+        root: Entity,
+        component_registry: &ComponentRegistry,
+    ) -> Self {
+        let mut children: FnvHashMap<Entity, Vec<Entity>> = FnvHashMap::default();
+        for (entity, parent) in <(Entity, &Parent)>::query().iter(world) {
+            children.entry(parent.0).or_default().push(*entity);
+        }
+
+        let mut entities = vec![root];
+        let mut next = 0;
+        while let Some(entity) = entities.get(next).copied() {
+            if let Some(children) = children.get(&entity) {
+                entities.extend(children.iter().filter(|child| **child != root));
+            }
+            next += 1;
+        }
+        Self::from_entities(world, &entities, component_registry)
+    }
+
+    fn from_entities(
+        world: &World,
+        entities: &[Entity],
+        component_registry: &ComponentRegistry,
+    ) -> Self {
+        let mut prefab_world = World::default();
+        let mut copy_impl = component_registry.copy_clone_impl();
+        let entity_map: HashMap<Entity, Entity, EntityHasher> = entities
+            .iter()
+            .map(|entity| {
+                (
+                    *entity,
+                    prefab_world.clone_from_single(world, *entity, &mut copy_impl),
+                )
+            })
+            .collect();
+
+        component_registry.remap_entities(&mut prefab_world, &entity_map);
+
+        Prefab::new(legion_prefab::Prefab::new(prefab_world))
+    }
+
+    /// Serializes the prefab to the RON prefab format read by `PrefabImporter`.
+    ///
+    /// Entities are written with the entity UUIDs of the prefab, and references to other
+    /// prefabs are kept.
+    ///
+    /// # Parameters
+    ///
+    /// - `component_registry`: Registry of the components to write.
+    pub fn to_ron(&self, component_registry: &ComponentRegistry) -> Result<String, Error> {
+        let registered_components: HashMap<ComponentTypeUuid, ComponentRegistration> =
+            component_registry
+                .components_by_uuid()
+                .iter()
+                .map(|(uuid, registration)| (*uuid, registration.clone()))
+                .collect();
+        let prefab_serde_context = legion_prefab::PrefabSerdeContext {
+            registered_components: &registered_components,
+        };
+        let prefab_ser =
+            legion_prefab::PrefabFormatSerializer::new(prefab_serde_context, &self.raw);
+
+        let mut output = Vec::new();
+        let mut ron_ser =
+            ron::ser::Serializer::new(&mut output, Some(ron::ser::PrettyConfig::default()), true)
+                .with_context(|_| format_err!("Failed to create RON serializer"))?;
+        prefab_format::serialize(&mut ron_ser, &prefab_ser, self.raw.prefab_id()).with_context(
+            |_| format_err!("Failed to serialize prefab {:x?}", self.raw.prefab_id()),
+        )?;
+        String::from_utf8(output).with_context(|_| format_err!("Serialized prefab is not UTF-8"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::transform::Transform;
+    use legion_prefab::register_component_type;
+    use serde::{Deserialize, Serialize};
+    use serde_diff::SerdeDiff;
+    use type_uuid::TypeUuid;
+
+    use super::*;
+    use crate::prefab::ComponentRegistryBuilder;
+
+    /// Component which is not registered with `register_component_type!`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Unregistered(u32);
+
+    /// Component which refers to another entity.
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, SerdeDiff, TypeUuid)]
+    #[uuid = "0d3a2b0c-5f5e-4b8e-9a43-7f2d2f3c61e4"]
+    struct Target(#[serde_diff(opaque)] Entity);
+    register_component_type!(Target);
+
+    impl RemapEntities for Target {
+        fn remap_entities(&mut self, entity_map: &HashMap<Entity, Entity, EntityHasher>) -> bool {
+            match entity_map.get(&self.0) {
+                Some(target) => {
+                    self.0 = *target;
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    fn registry() -> ComponentRegistry {
+        ComponentRegistryBuilder::default()
+            .auto_register_components()
+            .build()
+    }
+
+    #[test]
+    fn entity_tree_is_copied_with_remapped_parents() {
+        let mut world = World::default();
+        let level = world.push((Transform::default(),));
+        let root = world.push((Transform::default(), Parent(level)));
+        let child = world.push((Transform::default(), Parent(root), Unregistered(1)));
+        let _grandchild = world.push((Transform::default(), Parent(child)));
+        let _sibling = world.push((Transform::default(), Parent(level)));
+
+        let prefab = Prefab::from_entity_tree(&world, root, &registry());
+        let prefab_world = &prefab.raw.world;
+        assert_eq!(prefab_world.len(), 3);
+        assert_eq!(<&Unregistered>::query().iter(prefab_world).count(), 0);
+
+        let parents: Vec<Entity> = <&Parent>::query()
+            .iter(prefab_world)
+            .map(|parent| parent.0)
+            .collect();
+        assert_eq!(parents.len(), 2);
+        for parent in parents {
+            assert!(prefab_world.contains(parent));
+        }
+    }
+
+    #[test]
+    fn entity_references_of_registered_components_are_remapped() {
+        let registry = ComponentRegistryBuilder::default()
+            .auto_register_components()
+            .register_entity_remap::<Target>()
+            .build();
+        let mut world = World::default();
+        let outside = world.push((Transform::default(),));
+        let root = world.push((Transform::default(),));
+        let child = world.push((Transform::default(), Parent(root), Target(outside)));
+        world.entry(root).unwrap().add_component(Target(child));
+
+        let prefab = Prefab::from_entity_tree(&world, root, &registry);
+        let prefab_world = &prefab.raw.world;
+        let targets: Vec<Entity> = <&Target>::query()
+            .iter(prefab_world)
+            .map(|target| target.0)
+            .collect();
+        assert_eq!(targets.len(), 1);
+        assert_ne!(targets[0], child);
+        assert!(prefab_world.contains(targets[0]));
+    }
+
+    #[test]
+    fn world_round_trips_through_ron() {
+        let registry = registry();
+        let mut world = World::default();
+        let root = world.push((Transform::default(),));
+        world.push((Transform::default(), Parent(root)));
+        world.push((Unregistered(2),));
+
+        let prefab = Prefab::from_world(&world, &registry);
+        let ron = prefab
+            .to_ron(&registry)
+            .expect("Failed to serialize prefab");
+
+        let registered_components: HashMap<ComponentTypeUuid, ComponentRegistration> = registry
+            .components_by_uuid()
+            .iter()
+            .map(|(uuid, registration)| (*uuid, registration.clone()))
+            .collect();
+        let prefab_deser =
+            legion_prefab::PrefabFormatDeserializer::new(legion_prefab::PrefabSerdeContext {
+                registered_components: &registered_components,
+            });
+        let mut de = ron::de::Deserializer::from_bytes(ron.as_bytes()).unwrap();
+        prefab_format::deserialize(&mut de, &prefab_deser).expect("Failed to deserialize prefab");
+        let loaded = prefab_deser.prefab();
+
+        assert_eq!(loaded.prefab_id(), prefab.raw.prefab_id());
+        assert_eq!(
+            <&Transform>::query().iter(&loaded.world).count(),
+            <&Transform>::query().iter(&prefab.raw.world).count()
+        );
+    }
+}
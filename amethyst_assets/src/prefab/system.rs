@@ -1,11 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
 use amethyst_core::ecs::{
-    query, world::EntityHasher, Entity, IntoQuery, Resources, TryWrite, World,
+    query, world::EntityHasher, Entity, IntoQuery, Resources, TryRead, TryWrite, World,
 };
 
 use crate::{
-    prefab::{ComponentRegistry, Prefab},
+    prefab::{ComponentRegistry, Prefab, PrefabOverrides},
     AssetStorage, Handle,
 };
 
@@ -15,6 +15,8 @@ struct PrefabInstance {
 }
 
 /// Attaches prefabs to entities that have Handle<Prefab>
+///
+/// `PrefabOverrides` of the entity are applied after every (re)spawn.
 pub fn prefab_spawning_tick(world: &mut World, resources: &mut Resources) {
     let component_registry = resources
         .get::<ComponentRegistry>()
@@ -28,41 +30,50 @@ pub fn prefab_spawning_tick(world: &mut World, resources: &mut Resources) {
         &legion_prefab::CookedPrefab,
         u32,
         HashMap<Entity, Entity, EntityHasher>,
+        Option<PrefabOverrides>,
     )> = Vec::new();
 
     let mut entity_query = <(Entity,)>::query();
 
-    <(Entity, &Handle<Prefab>, TryWrite<PrefabInstance>)>::query().for_each_mut(
-        world,
-        |(entity, handle, instance)| {
-            if let Some(Prefab {
-                cooked: Some(cooked_prefab),
-                version: prefab_version,
-                ..
-            }) = prefab_storage.get(handle)
-            {
-                let instance_version = instance
+    <(
+        Entity,
+        &Handle<Prefab>,
+        TryWrite<PrefabInstance>,
+        TryRead<PrefabOverrides>,
+    )>::query()
+    .for_each_mut(world, |(entity, handle, instance, overrides)| {
+        if let Some(Prefab {
+            cooked: Some(cooked_prefab),
+            version: prefab_version,
+            ..
+        }) = prefab_storage.get(handle)
+        {
+            let instance_version = instance
+                .as_ref()
+                .map(|instance| instance.version)
+                .unwrap_or(0);
+            if instance_version < *prefab_version {
+                let mut entity_map = instance
                     .as_ref()
-                    .map(|instance| instance.version)
-                    .unwrap_or(0);
-                if instance_version < *prefab_version {
-                    let mut entity_map = instance
-                        .as_ref()
-                        .map(|instance| instance.entity_map.clone())
-                        .unwrap_or_default();
-                    if entity_map.is_empty() {
-                        if let Some((root_entity,)) = entity_query.iter(&cooked_prefab.world).next()
-                        {
-                            entity_map.insert(*root_entity, *entity);
-                        }
+                    .map(|instance| instance.entity_map.clone())
+                    .unwrap_or_default();
+                if entity_map.is_empty() {
+                    if let Some((root_entity,)) = entity_query.iter(&cooked_prefab.world).next() {
+                        entity_map.insert(*root_entity, *entity);
                     }
-                    prefabs.push((*entity, cooked_prefab, *prefab_version, entity_map));
                 }
+                prefabs.push((
+                    *entity,
+                    cooked_prefab,
+                    *prefab_version,
+                    entity_map,
+                    overrides.cloned(),
+                ));
             }
-        },
-    );
+        }
+    });
 
-    for (entity, prefab, version, prev_entity_map) in prefabs.into_iter() {
+    for (entity, prefab, version, prev_entity_map, overrides) in prefabs.into_iter() {
         let entity_map = world.clone_from(
             &prefab.world,
             &query::any(),
@@ -83,6 +94,16 @@ pub fn prefab_spawning_tick(world: &mut World, resources: &mut Resources) {
 
         log::debug!("Spawn for {:?}", entity);
 
+        if let Some(overrides) = overrides {
+            overrides.apply(
+                world,
+                entity,
+                &prefab.entities,
+                &entity_map,
+                &component_registry,
+            );
+        }
+
         if let Some(mut entry) = world.entry(entity) {
             entry.add_component(PrefabInstance {
                 version,
@@ -229,7 +229,7 @@ impl<T> ProcessingQueue<T> {
             };
             storage.update_asset(handle, asset, version);
             if commit {
-                storage.commit_asset(handle, version);
+                let _ = storage.commit_asset(handle, version);
             }
         }
 
@@ -4,9 +4,13 @@ use std::sync::{
 };
 
 use amethyst_error::Error;
+use distill::loader::{handle::AssetHandle, storage::LoadStatus, LoadHandle};
+use fnv::FnvHashSet;
 use log::error;
 use parking_lot::Mutex;
 
+use crate::Loader;
+
 /// Completion status, returned by `ProgressCounter::complete`.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Completion {
@@ -157,6 +161,146 @@ impl Tracker for ProgressCounterTracker {
     }
 }
 
+/// Tracks the loading progress of assets loaded through a `Loader`, including the assets
+/// they depend on.
+///
+/// Unlike `ProgressCounter`, which only counts assets passed to `Loader::load_from_data`,
+/// this follows the `LoadStatus` of any handle. The load dependencies of an asset, such as
+/// the textures of a sprite sheet or the prefabs a prefab references, are discovered once
+/// its data has been loaded, so the number of tracked assets grows while loading.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut progress = LoadProgress::new();
+/// progress.track(&loader.load::<Prefab>("level.prefab"));
+///
+/// // Every frame:
+/// progress.update(&*loader);
+/// loading_bar.set_fraction(progress.fraction());
+/// if progress.is_complete() {
+///     // Start the level.
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    roots: Vec<LoadHandle>,
+    num_assets: usize,
+    num_failed: usize,
+    num_loading: usize,
+    bytes_loaded: usize,
+}
+
+impl LoadProgress {
+    /// Creates a new `LoadProgress` which doesn't track any assets.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Starts tracking the asset of the handle and its dependencies.
+    ///
+    /// The handle has to be kept alive by the caller until loading is done.
+    pub fn track<H: AssetHandle>(&mut self, handle: &H) {
+        self.track_load_handle(handle.load_handle());
+    }
+
+    /// Starts tracking the asset with the given load handle and its dependencies.
+    pub fn track_load_handle(&mut self, handle: LoadHandle) {
+        if !self.roots.contains(&handle) {
+            self.roots.push(handle);
+            self.num_assets += 1;
+            self.num_loading += 1;
+        }
+    }
+
+    /// Updates the progress from the load status of the tracked assets and their dependencies.
+    pub fn update<L: Loader>(&mut self, loader: &L) {
+        self.update_with(
+            |handle| loader.get_load_status_handle(handle),
+            |handle| loader.load_dependencies(handle),
+            |handle| loader.loaded_bytes(handle),
+        );
+    }
+
+    fn update_with(
+        &mut self,
+        status: impl Fn(LoadHandle) -> LoadStatus,
+        dependencies: impl Fn(LoadHandle) -> Vec<LoadHandle>,
+        bytes: impl Fn(LoadHandle) -> Option<usize>,
+    ) {
+        self.num_assets = 0;
+        self.num_failed = 0;
+        self.num_loading = 0;
+        self.bytes_loaded = 0;
+
+        let mut visited = FnvHashSet::default();
+        let mut pending = self.roots.clone();
+        while let Some(handle) = pending.pop() {
+            if !visited.insert(handle) {
+                continue;
+            }
+            self.num_assets += 1;
+            match status(handle) {
+                LoadStatus::Loaded => {}
+                LoadStatus::DoesNotExist | LoadStatus::Error(_) => self.num_failed += 1,
+                _ => self.num_loading += 1,
+            }
+            self.bytes_loaded += bytes(handle).unwrap_or(0);
+            pending.extend(dependencies(handle));
+        }
+    }
+
+    /// Returns the number of assets this struct is tracking, including discovered dependencies.
+    pub fn num_assets(&self) -> usize {
+        self.num_assets
+    }
+
+    /// Returns the number of assets that have failed.
+    pub fn num_failed(&self) -> usize {
+        self.num_failed
+    }
+
+    /// Returns the number of assets that are still loading.
+    pub fn num_loading(&self) -> usize {
+        self.num_loading
+    }
+
+    /// Returns the number of assets that have successfully loaded.
+    pub fn num_finished(&self) -> usize {
+        self.num_assets - self.num_loading - self.num_failed
+    }
+
+    /// Returns the number of bytes of asset data loaded so far.
+    pub fn bytes_loaded(&self) -> usize {
+        self.bytes_loaded
+    }
+
+    /// Returns the fraction of assets that are done loading, between `0.0` and `1.0`.
+    ///
+    /// Failed assets count as done.
+    pub fn fraction(&self) -> f32 {
+        if self.num_assets == 0 {
+            1.0
+        } else {
+            (self.num_assets - self.num_loading) as f32 / self.num_assets as f32
+        }
+    }
+
+    /// Returns `Completion::Complete` if all tracked assets are finished.
+    pub fn complete(&self) -> Completion {
+        match (self.num_failed, self.num_loading) {
+            (0, 0) => Completion::Complete,
+            (0, _) => Completion::Loading,
+            (_, _) => Completion::Failed,
+        }
+    }
+
+    /// Returns `true` if all assets have been loaded without error.
+    pub fn is_complete(&self) -> bool {
+        self.complete() == Completion::Complete
+    }
+}
+
 #[derive(Debug)]
 pub struct AssetErrorMeta {
     pub error: Error,
@@ -209,8 +353,9 @@ fn show_error(handle_id: u64, asset_type_name: &'static str, asset_name: &str, e
 #[cfg(test)]
 mod tests {
     use amethyst_error::Error;
+    use distill::loader::{storage::LoadStatus, LoadHandle};
 
-    use super::{Completion, Progress, ProgressCounter, Tracker};
+    use super::{Completion, LoadProgress, Progress, ProgressCounter, Tracker};
 
     #[test]
     fn progress_counter_complete_returns_correct_completion_status_when_loading_or_complete() {
@@ -288,4 +433,68 @@ mod tests {
         tracker_2.success();
         assert_eq!(2, progress.num_finished());
     }
+
+    #[test]
+    fn load_progress_follows_transitive_dependencies() {
+        let mut progress = LoadProgress::new();
+        progress.track_load_handle(LoadHandle(1));
+        assert_eq!(1, progress.num_assets());
+        assert_eq!(Completion::Loading, progress.complete());
+
+        // 1 depends on 2 and 3, 2 depends on 3: a diamond is only counted once.
+        let dependencies = |handle: LoadHandle| {
+            match handle.0 {
+                1 => vec![LoadHandle(2), LoadHandle(3)],
+                2 => vec![LoadHandle(3)],
+                _ => Vec::new(),
+            }
+        };
+        progress.update_with(
+            |handle| {
+                if handle.0 == 3 {
+                    LoadStatus::Loading
+                } else {
+                    LoadStatus::Loaded
+                }
+            },
+            dependencies,
+            |handle| if handle.0 == 3 { None } else { Some(10) },
+        );
+        assert_eq!(3, progress.num_assets());
+        assert_eq!(1, progress.num_loading());
+        assert_eq!(2, progress.num_finished());
+        assert_eq!(20, progress.bytes_loaded());
+        assert!((progress.fraction() - 2.0 / 3.0).abs() < f32::EPSILON);
+
+        progress.update_with(|_| LoadStatus::Loaded, dependencies, |_| Some(10));
+        assert_eq!(3, progress.num_finished());
+        assert_eq!(30, progress.bytes_loaded());
+        assert!(progress.is_complete());
+    }
+
+    #[test]
+    fn load_progress_fails_when_a_dependency_does_not_exist() {
+        let mut progress = LoadProgress::new();
+        progress.track_load_handle(LoadHandle(1));
+        progress.update_with(
+            |handle| {
+                if handle.0 == 2 {
+                    LoadStatus::DoesNotExist
+                } else {
+                    LoadStatus::Loaded
+                }
+            },
+            |handle| {
+                if handle.0 == 1 {
+                    vec![LoadHandle(2)]
+                } else {
+                    Vec::new()
+                }
+            },
+            |_| None,
+        );
+        assert_eq!(1, progress.num_failed());
+        assert_eq!(Completion::Failed, progress.complete());
+        assert!((progress.fraction() - 1.0).abs() < f32::EPSILON);
+    }
 }
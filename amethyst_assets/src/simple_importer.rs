@@ -1,10 +1,14 @@
-use std::io::Read;
+use std::{io::Read, path::Path};
 
-use distill::importer::{
-    self as importer, BoxedImporter, ImportOp, ImportedAsset, Importer, ImporterValue, SerdeObj,
+use amethyst_error::{format_err, Error};
+use distill::{
+    importer::{
+        self as importer, BoxedImporter, ImportOp, ImportedAsset, Importer, ImporterValue, SerdeObj,
+    },
+    loader::{AssetRef, AssetTypeId},
 };
 use serde::{Deserialize, Serialize};
-use type_uuid::TypeUuid;
+use type_uuid::{TypeUuid, TypeUuidDynamic};
 
 use crate::{AssetUuid, Format};
 
@@ -45,7 +49,8 @@ where
     where
         Self: Sized,
     {
-        1
+        // 2: `MeshData` carries optional triangles.
+        2
     }
     fn version(&self) -> u32 {
         Self::version_static()
@@ -97,10 +102,113 @@ pub fn get_source_importers(
         .map(|s| (s.extension.trim_start_matches('.'), (s.instantiator)()))
 }
 
+/// Asset data imported from a source file by a [SourceImporter].
+#[derive(Debug)]
+pub struct SourceAsset {
+    /// UUID of the asset, if the importer assigned one.
+    pub id: Option<AssetUuid>,
+    /// UUID of the asset data type.
+    pub type_id: AssetTypeId,
+    /// Asset data, serialized with bincode.
+    pub data: Vec<u8>,
+    /// Assets which have to be loaded together with this asset.
+    pub load_deps: Vec<AssetRef>,
+    /// Assets which were used to build this asset.
+    pub build_deps: Vec<AssetRef>,
+}
+
+/// Use [inventory::submit!] to import files of an extension when they are loaded from a
+/// [Source](crate::Source) instead of the asset daemon.
+///
+/// `register_importer!` registers one for every `Format` automatically.
+#[derive(Debug)]
+pub struct SourceImporter {
+    /// File extension for this type of file
+    pub extension: &'static str,
+    /// Function that imports the bytes of a file into asset data
+    pub import: fn(Vec<u8>) -> Result<Vec<SourceAsset>, Error>,
+}
+inventory::collect!(SourceImporter);
+
+/// Get the [SourceImporter] registered for the extension of the given path.
+pub(crate) fn get_source_importer(path: &str) -> Option<&'static SourceImporter> {
+    let extension = Path::new(path).extension()?.to_str()?;
+    inventory::iter::<SourceImporter>.into_iter().find(|s| {
+        s.extension
+            .trim_start_matches('.')
+            .eq_ignore_ascii_case(extension)
+    })
+}
+
+/// Imports the bytes of a source file with a `Format`.
+///
+/// Used by `register_importer!`, which registers a [SourceImporter] for every format.
+pub fn import_simple_source<A, T>(format: &T, bytes: Vec<u8>) -> Result<Vec<SourceAsset>, Error>
+where
+    A: Serialize + TypeUuid + 'static,
+    T: Format<A>,
+{
+    let data = format.import_simple(bytes)?;
+    Ok(vec![SourceAsset {
+        id: None,
+        type_id: AssetTypeId(A::UUID),
+        data: bincode::serialize(&data)?,
+        load_deps: Vec::new(),
+        build_deps: Vec::new(),
+    }])
+}
+
+/// Imports the bytes of a source file with an `Importer` using its default options and state.
+///
+/// The imported assets keep the UUIDs the importer assigned and their dependencies, so
+/// assets of the same file can refer to each other.
+///
+/// # Examples
+///
+/// ```ignore
+/// inventory::submit! {
+///     amethyst_assets::SourceImporter {
+///         extension: "glb",
+///         import: amethyst_assets::import_source_with::<GltfImporter>,
+///     }
+/// }
+/// ```
+pub fn import_source_with<I>(bytes: Vec<u8>) -> Result<Vec<SourceAsset>, Error>
+where
+    I: Importer + Default,
+    I::Options: Default,
+    I::State: Default,
+{
+    let importer = I::default();
+    let mut op = ImportOp::default();
+    let value = importer
+        .import(
+            &mut op,
+            &mut bytes.as_slice(),
+            &I::Options::default(),
+            &mut I::State::default(),
+        )
+        .map_err(|e| format_err!("Failed to import asset: {}", e))?;
+
+    value
+        .assets
+        .into_iter()
+        .map(|asset| {
+            Ok(SourceAsset {
+                id: Some(asset.id),
+                type_id: AssetTypeId(asset.asset_data.uuid()),
+                data: bincode::serialize(&asset.asset_data)?,
+                load_deps: asset.load_deps,
+                build_deps: asset.build_deps,
+            })
+        })
+        .collect()
+}
+
 /// Associates the given file extension with a `Format` implementation
 ///
 /// The `AssetDaemon` will automatically re-import the asset when a file of that format is created
-/// or modified.
+/// or modified. Files of that format can also be loaded from a `Source` without the daemon.
 ///
 /// # Parameters
 ///
@@ -146,5 +254,12 @@ macro_rules! register_importer {
                 instantiator: || Box::new($crate::SimpleImporter::from(<$format as Default>::default())),
             }
         }
+        $crate::inventory::submit!{
+            #![crate = $krate]
+            $crate::SourceImporter {
+                extension: $ext,
+                import: |bytes| $crate::import_simple_source(&<$format as Default>::default(), bytes),
+            }
+        }
     };
 }
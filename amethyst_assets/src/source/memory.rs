@@ -0,0 +1,115 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use amethyst_error::{format_err, Error, ResultExt};
+use parking_lot::RwLock;
+
+use crate::{error, source::Source};
+
+/// In-memory source.
+///
+/// Stores the bytes of every asset under its path. This is useful for tests and tools which
+/// generate their assets at runtime, or for assets embedded into the executable.
+#[derive(Debug, Default)]
+pub struct MemorySource {
+    files: RwLock<HashMap<String, (Vec<u8>, u64)>>,
+}
+
+impl MemorySource {
+    /// Creates a new, empty in-memory source.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Inserts the bytes of an asset under the given path, replacing any previous bytes.
+    ///
+    /// The path should always use `/` as separator.
+    pub fn insert<P, B>(&self, path: P, bytes: B)
+    where
+        P: Into<String>,
+        B: Into<Vec<u8>>,
+    {
+        let modified = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.files
+            .write()
+            .insert(path.into(), (bytes.into(), modified));
+    }
+
+    /// Builder-style variant of [insert](#method.insert).
+    pub fn with<P, B>(self, path: P, bytes: B) -> Self
+    where
+        P: Into<String>,
+        B: Into<Vec<u8>>,
+    {
+        self.insert(path, bytes);
+        self
+    }
+
+    /// Removes the asset stored under the given path and returns its bytes.
+    pub fn remove(&self, path: &str) -> Option<Vec<u8>> {
+        self.files.write().remove(path).map(|(bytes, _)| bytes)
+    }
+}
+
+impl Source for MemorySource {
+    fn modified(&self, path: &str) -> Result<u64, Error> {
+        self.files
+            .read()
+            .get(path)
+            .map(|(_, modified)| *modified)
+            .ok_or_else(|| format_err!("No asset stored under {:?}", path))
+            .with_context(|_| error::Error::Source)
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        self.files
+            .read()
+            .get(path)
+            .map(|(bytes, _)| bytes.clone())
+            .ok_or_else(|| format_err!("No asset stored under {:?}", path))
+            .with_context(|_| error::Error::Source)
+    }
+
+    fn load_with_metadata(&self, path: &str) -> Result<(Vec<u8>, u64), Error> {
+        self.files
+            .read()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| format_err!("No asset stored under {:?}", path))
+            .with_context(|_| error::Error::Source)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemorySource;
+    use crate::source::Source;
+
+    #[test]
+    fn loads_inserted_asset() {
+        let source = MemorySource::new().with("subdir/asset", b"data".to_vec());
+
+        assert_eq!(
+            b"data".to_vec(),
+            source
+                .load("subdir/asset")
+                .expect("Failed to load subdir/asset")
+        );
+        assert!(source.modified("subdir/asset").is_ok());
+    }
+
+    #[test]
+    fn fails_to_load_missing_asset() {
+        let source = MemorySource::new();
+        source.insert("asset", "data");
+        source.remove("asset");
+
+        assert!(source.load("asset").is_err());
+        assert!(source.modified("asset").is_err());
+    }
+}
@@ -1,10 +1,14 @@
+use std::{fmt, sync::Arc};
+
 use amethyst_error::Error;
+use fnv::FnvHashMap;
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
-pub use self::dir::Directory;
+pub use self::{dir::Directory, memory::MemorySource};
 
 mod dir;
+mod memory;
 
 /// A trait for asset sources, which provides
 /// methods for loading bytes.
@@ -32,3 +36,60 @@ pub trait Source: Send + Sync + 'static {
         Ok((b, m))
     }
 }
+
+/// Named asset sources the `DefaultLoader` can load assets from without the asset daemon.
+///
+/// Insert this as a resource before adding the `LoaderBundle`, or use
+/// `ApplicationBuilder::with_source` and `ApplicationBuilder::with_default_source`.
+/// Assets are then loaded with `Loader::load` (from the default source) or
+/// `Loader::load_from` (from a named source).
+#[derive(Clone, Default)]
+pub struct Sources {
+    named: FnvHashMap<String, Arc<dyn Source>>,
+    default: Option<Arc<dyn Source>>,
+}
+
+impl Sources {
+    /// Creates an empty set of sources.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers a source under the given name, replacing any source with the same name.
+    pub fn add<I, S>(&mut self, name: I, source: S)
+    where
+        I: Into<String>,
+        S: Source,
+    {
+        self.named.insert(name.into(), Arc::new(source));
+    }
+
+    /// Sets the source used by `Loader::load`.
+    pub fn set_default<S: Source>(&mut self, source: S) {
+        self.default = Some(Arc::new(source));
+    }
+
+    /// Returns the source registered under the given name.
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Source>> {
+        self.named.get(name)
+    }
+
+    /// Returns the default source, if one was set.
+    pub fn default_source(&self) -> Option<&Arc<dyn Source>> {
+        self.default.as_ref()
+    }
+
+    /// Returns `true` if neither a default nor a named source was registered.
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.named.is_empty()
+    }
+}
+
+impl fmt::Debug for Sources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sources")
+            .field("named", &self.named.keys().collect::<Vec<_>>())
+            .field("default", &self.default.is_some())
+            .finish()
+    }
+}
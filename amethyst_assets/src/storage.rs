@@ -2,9 +2,13 @@ use crossbeam_queue::SegQueue;
 use distill::loader::{handle::AssetHandle, storage::IndirectionTable, LoadHandle};
 use fnv::FnvHashMap;
 
+use crate::asset::AssetSize;
+
 struct AssetState<A> {
     version: u32,
     asset: A,
+    /// Size reported by `AssetSize`, or 0 if sizes are not reported.
+    size: usize,
 }
 
 /// An asset storage, storing the actual assets
@@ -17,6 +21,8 @@ pub struct AssetStorage<A> {
     uncommitted: FnvHashMap<LoadHandle, AssetState<A>>,
     to_drop: SegQueue<A>,
     indirection_table: IndirectionTable,
+    size_of: Option<fn(&A) -> usize>,
+    budget: Option<usize>,
 }
 
 impl<A> AssetStorage<A> {
@@ -27,7 +33,108 @@ impl<A> AssetStorage<A> {
             uncommitted: Default::default(),
             to_drop: SegQueue::new(),
             indirection_table,
+            size_of: None,
+            budget: None,
+        }
+    }
+
+    /// Keeps track of the memory used by the assets in this storage using `AssetSize`.
+    pub fn report_sizes(&mut self)
+    where
+        A: AssetSize,
+    {
+        self.size_of = Some(A::size_bytes);
+        for state in self
+            .assets
+            .values_mut()
+            .chain(self.uncommitted.values_mut())
+        {
+            state.size = state.asset.size_bytes();
+        }
+    }
+
+    /// Sets the number of bytes the assets in this storage may use.
+    ///
+    /// When the assets use more memory, the loader evicts assets which are no longer referenced
+    /// by any `Handle`, e.g. assets that are only kept in a `Cache`, until they fit the budget
+    /// again. Evicted assets are loaded again the next time they are requested.
+    ///
+    /// Without a budget, assets are unloaded as soon as they are no longer referenced.
+    ///
+    /// # Parameters
+    ///
+    /// * `budget`: Maximum memory use in bytes as reported by `AssetSize`, or `None` to disable
+    ///   eviction.
+    pub fn set_budget(&mut self, budget: Option<usize>)
+    where
+        A: AssetSize,
+    {
+        self.report_sizes();
+        self.budget = budget;
+    }
+
+    /// Returns the memory budget of this storage in bytes, if any.
+    pub fn budget(&self) -> Option<usize> {
+        self.budget
+    }
+
+    /// Returns the number of loaded assets.
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// Returns true if no assets are loaded.
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    /// Returns the memory used by the loaded assets in bytes, if sizes are reported.
+    ///
+    /// See [report_sizes](#method.report_sizes).
+    pub fn size_bytes(&self) -> Option<usize> {
+        self.size_of
+            .map(|_| self.assets.values().map(|state| state.size).sum())
+    }
+
+    /// Evicts assets from `candidates`, in order, while the storage is above its budget.
+    ///
+    /// Returns the evicted load handles.
+    pub(crate) fn evict(&mut self, candidates: &[LoadHandle]) -> Vec<LoadHandle> {
+        let mut evicted = Vec::new();
+        let (budget, mut size) = match (self.budget, self.size_bytes()) {
+            (Some(budget), Some(size)) => (budget, size),
+            _ => return evicted,
+        };
+        for handle in candidates {
+            if size <= budget {
+                break;
+            }
+            if let Some(state) = self.assets.remove(handle) {
+                log::debug!("Evicting Asset {:?}", handle);
+                size -= state.size;
+                self.to_drop.push(state.asset);
+                evicted.push(*handle);
+            }
         }
+        evicted
+    }
+
+    /// Frees unreferenced assets: all `candidates` if the storage has no budget, otherwise
+    /// only as many as [evict](#method.evict) removes.
+    ///
+    /// Returns the freed load handles.
+    pub(crate) fn release(&mut self, candidates: &[LoadHandle]) -> Vec<LoadHandle> {
+        if self.budget.is_some() {
+            return self.evict(candidates);
+        }
+        for handle in candidates {
+            log::debug!("Freeing Asset {:?}", handle);
+            let states = self.assets.remove(handle).into_iter();
+            for state in states.chain(self.uncommitted.remove(handle)) {
+                self.to_drop.push(state.asset);
+            }
+        }
+        candidates.to_vec()
     }
 
     /// Added to make api compatible with previous storage
@@ -42,10 +149,15 @@ impl<A> AssetStorage<A> {
 
     pub(crate) fn update_asset(&mut self, handle: LoadHandle, asset: A, version: u32) {
         log::debug!("Updating Asset {:?}", handle);
-        if let Some(data) = self
-            .uncommitted
-            .insert(handle, AssetState { version, asset })
-        {
+        let size = self.size_of.map_or(0, |size_of| size_of(&asset));
+        if let Some(data) = self.uncommitted.insert(
+            handle,
+            AssetState {
+                version,
+                asset,
+                size,
+            },
+        ) {
             self.to_drop.push(data.asset);
         }
     }
@@ -65,22 +177,20 @@ impl<A> AssetStorage<A> {
         }
     }
 
-    pub(crate) fn commit_asset(&mut self, handle: LoadHandle, version: u32) {
+    /// Commits the uncommitted version of an asset.
+    ///
+    /// Returns the version of the asset it replaced, if any.
+    pub(crate) fn commit_asset(&mut self, handle: LoadHandle, version: u32) -> Option<u32> {
         if let Some(data) = self.uncommitted.remove(&handle) {
             if data.version != version {
                 panic!("attempted to commit asset version which mismatches with existing uncommitted version")
             }
 
-            if let Some(existing) = self.assets.insert(
-                handle,
-                AssetState {
-                    version,
-                    asset: data.asset,
-                },
-            ) {
+            self.assets.insert(handle, data).map(|existing| {
                 // data already exists for the handle, drop it
                 self.to_drop.push(existing.asset);
-            };
+                existing.version
+            })
         } else {
             panic!("attempted to commit asset which doesn't exist");
         }
@@ -88,25 +198,33 @@ impl<A> AssetStorage<A> {
 
     /// returns true when asset is loaded for this handle
     pub fn contains(&self, load_handle: LoadHandle) -> bool {
-        let load_handle = if load_handle.is_indirect() {
-            if let Some(handle) = self.indirection_table.resolve(load_handle) {
-                handle
-            } else {
-                return false;
-            }
+        self.resolve(load_handle)
+            .map_or(false, |load_handle| self.assets.contains_key(&load_handle))
+    }
+
+    /// Returns true when the storage holds an asset for the direct load handle, either committed
+    /// or processed and waiting to be committed.
+    ///
+    /// Data kept next to the storage for a load handle can be dropped once this returns false.
+    pub fn holds(&self, load_handle: LoadHandle) -> bool {
+        self.assets.contains_key(&load_handle) || self.uncommitted.contains_key(&load_handle)
+    }
+
+    /// Returns the direct load handle an indirect load handle currently refers to, or the load
+    /// handle itself if it is direct.
+    ///
+    /// This is the load handle asset processors receive, so it can be used to look up data kept
+    /// next to the storage while processing an asset.
+    pub fn resolve(&self, load_handle: LoadHandle) -> Option<LoadHandle> {
+        if load_handle.is_indirect() {
+            self.indirection_table.resolve(load_handle)
         } else {
-            load_handle
-        };
-        self.assets.contains_key(&load_handle)
+            Some(load_handle)
+        }
     }
 
     fn get_asset_state(&self, load_handle: LoadHandle) -> Option<&AssetState<A>> {
-        let load_handle = if load_handle.is_indirect() {
-            self.indirection_table.resolve(load_handle)?
-        } else {
-            load_handle
-        };
-        self.assets.get(&load_handle)
+        self.assets.get(&self.resolve(load_handle)?)
     }
 
     /// Returns the asset for the given load handle, or `None` if has not completed loading.
@@ -180,6 +298,36 @@ impl<A> AssetStorage<A> {
     }
 }
 
+/// Memory use of the loaded assets of every asset type, for debugging.
+///
+/// This resource is inserted by the `LoaderBundle` and updated by the loader every frame.
+/// The size of an asset type is only known if its `AssetStorage` reports sizes.
+#[derive(Debug, Default)]
+pub struct AssetMemoryUsage {
+    /// Memory use of every asset type, sorted by name.
+    pub types: Vec<AssetTypeUsage>,
+}
+
+impl AssetMemoryUsage {
+    /// Returns the memory use of all asset types which report sizes in bytes.
+    pub fn total_bytes(&self) -> usize {
+        self.types.iter().filter_map(|usage| usage.bytes).sum()
+    }
+}
+
+/// Memory use of the loaded assets of one asset type.
+#[derive(Clone, Debug)]
+pub struct AssetTypeUsage {
+    /// Rust type name of the asset.
+    pub name: &'static str,
+    /// Number of loaded assets.
+    pub count: usize,
+    /// Approximate memory used by the loaded assets in bytes, if sizes are reported.
+    pub bytes: Option<usize>,
+    /// Memory budget of the asset type in bytes, if any.
+    pub budget: Option<usize>,
+}
+
 impl<A> distill::loader::handle::TypedAssetStorage<A> for AssetStorage<A> {
     fn get<T: AssetHandle>(&self, handle: &T) -> Option<&A> {
         self.get(handle)
@@ -208,6 +356,85 @@ impl<A> MutateAssetInStorage<A> for AssetStorage<A> {
     ) {
         if let Some(asset_state) = self.assets.get_mut(&handle.load_handle()) {
             mutator(&mut asset_state.asset);
+            if let Some(size_of) = self.size_of {
+                asset_state.size = size_of(&asset_state.asset);
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultLoader;
+
+    struct Blob(usize);
+
+    impl AssetSize for Blob {
+        fn size_bytes(&self) -> usize {
+            self.0
+        }
+    }
+
+    fn storage_with_blobs(sizes: &[usize]) -> AssetStorage<Blob> {
+        let loader = DefaultLoader::default();
+        let mut storage = AssetStorage::new(loader.indirection_table.clone());
+        storage.report_sizes();
+        for (i, size) in sizes.iter().enumerate() {
+            let handle = LoadHandle(i as u64 + 1);
+            storage.update_asset(handle, Blob(*size), 0);
+            storage.commit_asset(handle, 0);
+        }
+        storage
+    }
+
+    #[test]
+    fn storage_reports_size_of_loaded_assets() {
+        let storage = storage_with_blobs(&[10, 20, 30]);
+        assert_eq!(storage.len(), 3);
+        assert_eq!(storage.size_bytes(), Some(60));
+    }
+
+    #[test]
+    fn storage_evicts_candidates_until_within_budget() {
+        let mut storage = storage_with_blobs(&[10, 20, 30]);
+        storage.set_budget(Some(35));
+
+        let evicted = storage.evict(&[LoadHandle(3), LoadHandle(1), LoadHandle(2)]);
+        assert_eq!(evicted, vec![LoadHandle(3)]);
+        assert_eq!(storage.size_bytes(), Some(30));
+
+        let mut dropped = 0;
+        storage.process_custom_drop(|_| dropped += 1);
+        assert_eq!(dropped, 1);
+    }
+
+    #[test]
+    fn storage_without_budget_does_not_evict() {
+        let mut storage = storage_with_blobs(&[10, 20, 30]);
+        assert!(storage.evict(&[LoadHandle(1)]).is_empty());
+        assert_eq!(storage.len(), 3);
+    }
+
+    #[test]
+    fn storage_without_budget_releases_all_candidates() {
+        let mut storage = storage_with_blobs(&[10, 20, 30]);
+        let released = storage.release(&[LoadHandle(1), LoadHandle(3)]);
+        assert_eq!(released, vec![LoadHandle(1), LoadHandle(3)]);
+        assert_eq!(storage.len(), 1);
+        assert!(storage.contains(LoadHandle(2)));
+    }
+
+    #[test]
+    fn commit_asset_returns_replaced_version() {
+        let mut storage = storage_with_blobs(&[10]);
+        assert_eq!(storage.get_for_load_handle(LoadHandle(1)).unwrap().0, 10);
+
+        storage.update_asset(LoadHandle(1), Blob(15), 1);
+        assert_eq!(storage.commit_asset(LoadHandle(1), 1), Some(0));
+        assert_eq!(storage.get_for_load_handle(LoadHandle(1)).unwrap().0, 15);
+
+        storage.update_asset(LoadHandle(2), Blob(5), 0);
+        assert_eq!(storage.commit_asset(LoadHandle(2), 0), None);
+    }
+}
@@ -1,10 +1,11 @@
 //! ECS audio bundles
 
 //use amethyst_assets::AssetProcessorSystemBundle;
+use amethyst_assets::AssetStorage;
 use amethyst_core::ecs::*;
 use amethyst_error::Error;
 
-use crate::{output::OutputWrapper, systems::*};
+use crate::{output::OutputWrapper, systems::*, Source};
 
 /// Audio bundle
 ///
@@ -23,6 +24,9 @@ impl SystemBundle for AudioBundle {
     ) -> Result<(), Error> {
         resources.get_or_default::<OutputWrapper>();
         resources.get_or_default::<SelectedListener>();
+        if let Some(mut storage) = resources.get_mut::<AssetStorage<Source>>() {
+            storage.report_sizes();
+        }
 
         builder.add_system(AudioSystem);
         Ok(())
@@ -1,5 +1,7 @@
 //! Provides structures used to load audio files.
-use amethyst_assets::{Asset, AssetStorage, Handle, LoadHandle, ProcessableAsset, ProcessingState};
+use amethyst_assets::{
+    Asset, AssetSize, AssetStorage, Handle, LoadHandle, ProcessableAsset, ProcessingState,
+};
 use amethyst_error::Error;
 use type_uuid::TypeUuid;
 
@@ -29,6 +31,12 @@ impl Asset for Source {
     type Data = AudioData;
 }
 
+impl AssetSize for Source {
+    fn size_bytes(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
 impl ProcessableAsset for Source {
     fn process(
         data: AudioData,
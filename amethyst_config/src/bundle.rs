@@ -0,0 +1,142 @@
+//! Opt-in hot reloading of configuration files into ECS resources.
+
+use std::{fmt, path::Path, time::Duration};
+
+use amethyst_core::{
+    dispatcher::{DispatcherBuilder, System, SystemBundle},
+    ecs::{systems::ParallelRunnable, Resources, SystemBuilder, World},
+    EventChannel,
+};
+use amethyst_error::Error;
+
+use crate::{Config, ConfigReloaded, ConfigWatcher};
+
+/// Reloads the configuration resource `T` whenever its file changes on disk.
+///
+/// If the new file can not be parsed the error is logged and the resource keeps its old value.
+/// Successful reloads are announced with `ConfigReloaded<T>` events.
+pub struct ConfigReloadSystem<T> {
+    watcher: ConfigWatcher<T>,
+}
+
+impl<T> fmt::Debug for ConfigReloadSystem<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigReloadSystem")
+            .field("watcher", &self.watcher)
+            .finish()
+    }
+}
+
+impl<T: Config> ConfigReloadSystem<T> {
+    /// Creates a system reloading `T` with the given watcher.
+    pub fn new(watcher: ConfigWatcher<T>) -> Self {
+        ConfigReloadSystem { watcher }
+    }
+}
+
+impl<T> System for ConfigReloadSystem<T>
+where
+    T: Config + Send + Sync + 'static,
+{
+    fn build(mut self) -> Box<dyn ParallelRunnable> {
+        Box::new(
+            SystemBuilder::new(format!(
+                "ConfigReloadSystem<{}>",
+                std::any::type_name::<T>()
+            ))
+            .write_resource::<T>()
+            .write_resource::<EventChannel<ConfigReloaded<T>>>()
+            .build(move |_, _, (config, reloaded), _| {
+                match self.watcher.poll() {
+                    Some(Ok(value)) => {
+                        log::info!("Reloaded config {:?}", self.watcher.path());
+                        **config = value;
+                        reloaded
+                            .single_write(ConfigReloaded::new(self.watcher.path().to_path_buf()));
+                    }
+                    Some(Err(err)) => {
+                        log::error!(
+                            "Failed to reload config {:?}, keeping the old value: {}",
+                            self.watcher.path(),
+                            err
+                        );
+                    }
+                    None => {}
+                }
+            }),
+        )
+    }
+}
+
+/// Loads the configuration resource `T` from a file and reloads it whenever the file changes.
+///
+/// If `T` is already a resource, e.g. because the value was needed to build another bundle,
+/// that value is kept until the file changes. Otherwise the file is loaded when the bundle is,
+/// falling back to `T::default()` if it can not be loaded.
+///
+/// # Examples
+///
+/// ```ignore
+/// let game_data = DispatcherBuilder::default()
+///     .add_bundle(ConfigReloadBundle::<MovementConfig>::new("config/movement.ron"));
+/// ```
+pub struct ConfigReloadBundle<T> {
+    watcher: Option<ConfigWatcher<T>>,
+}
+
+impl<T> fmt::Debug for ConfigReloadBundle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigReloadBundle")
+            .field("watcher", &self.watcher)
+            .finish()
+    }
+}
+
+impl<T: Config> ConfigReloadBundle<T> {
+    /// Creates a bundle watching the configuration file at `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        ConfigReloadBundle {
+            watcher: Some(ConfigWatcher::new(path)),
+        }
+    }
+
+    /// Sets the minimum time between two checks of the file. The default is one second.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.watcher = self
+            .watcher
+            .take()
+            .map(|watcher| watcher.with_interval(interval));
+        self
+    }
+}
+
+impl<T> SystemBundle for ConfigReloadBundle<T>
+where
+    T: Config + Default + Send + Sync + 'static,
+{
+    fn load(
+        &mut self,
+        _world: &mut World,
+        resources: &mut Resources,
+        builder: &mut DispatcherBuilder,
+    ) -> Result<(), Error> {
+        let watcher = self
+            .watcher
+            .take()
+            .expect("ConfigReloadBundle can only be loaded once");
+        if !resources.contains::<T>() {
+            let config = T::load(watcher.path()).unwrap_or_else(|err| {
+                log::error!(
+                    "Failed to load config {:?}, using the default value: {}",
+                    watcher.path(),
+                    err
+                );
+                T::default()
+            });
+            resources.insert(config);
+        }
+        resources.get_or_insert_with(EventChannel::<ConfigReloaded<T>>::new);
+        builder.add_system(ConfigReloadSystem::new(watcher));
+        Ok(())
+    }
+}
@@ -27,6 +27,14 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "json")]
 use serde_json::error::Error as SerJsonError;
 
+#[cfg(feature = "bundle")]
+pub use self::bundle::{ConfigReloadBundle, ConfigReloadSystem};
+pub use self::reload::{ConfigReloaded, ConfigWatcher};
+
+#[cfg(feature = "bundle")]
+mod bundle;
+mod reload;
+
 /// Error related to anything that manages/creates configurations as well as
 /// "workspace"-related things.
 #[derive(Debug)]
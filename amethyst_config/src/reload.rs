@@ -0,0 +1,161 @@
+//! Detection of changes to configuration files on disk.
+
+use std::{
+    fmt, fs,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{Config, ConfigError};
+
+/// Default time between two checks of a watched configuration file.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Event written to `EventChannel<ConfigReloaded<T>>` after a new value of the configuration
+/// resource `T` was loaded from disk.
+pub struct ConfigReloaded<T> {
+    /// Path of the reloaded configuration file.
+    pub path: PathBuf,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ConfigReloaded<T> {
+    /// Creates an event for a reload of the file at `path`.
+    pub fn new(path: PathBuf) -> Self {
+        ConfigReloaded {
+            path,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for ConfigReloaded<T> {
+    fn clone(&self) -> Self {
+        ConfigReloaded::new(self.path.clone())
+    }
+}
+
+impl<T> fmt::Debug for ConfigReloaded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigReloaded")
+            .field("config", &std::any::type_name::<T>())
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// Watches a configuration file and parses it again when it changes on disk.
+///
+/// Changes are detected by polling the modification time and length of the file, so it works
+/// on every platform without a file system notification service.
+pub struct ConfigWatcher<T> {
+    path: PathBuf,
+    interval: Duration,
+    last_poll: Option<Instant>,
+    last_seen: Option<(SystemTime, u64)>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> fmt::Debug for ConfigWatcher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigWatcher")
+            .field("config", &std::any::type_name::<T>())
+            .field("path", &self.path)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl<T: Config> ConfigWatcher<T> {
+    /// Creates a watcher for the configuration file at `path`.
+    ///
+    /// The current state of the file is considered loaded, so only later changes are reported.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        ConfigWatcher {
+            last_seen: file_state(&path),
+            path,
+            interval: DEFAULT_INTERVAL,
+            last_poll: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Sets the minimum time between two checks of the file. The default is one second.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Returns the path of the watched file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Checks whether the file changed since it was last loaded, and parses it again if it did.
+    ///
+    /// Returns `None` if the file did not change or the poll interval did not pass yet.
+    pub fn poll(&mut self) -> Option<Result<T, ConfigError>> {
+        let now = Instant::now();
+        if let Some(last_poll) = self.last_poll {
+            if now.duration_since(last_poll) < self.interval {
+                return None;
+            }
+        }
+        self.last_poll = Some(now);
+
+        let state = file_state(&self.path);
+        if state.is_none() || state == self.last_seen {
+            return None;
+        }
+        self.last_seen = state;
+        Some(T::load(&self.path))
+    }
+}
+
+/// Modification time and length of a file, or `None` if it can not be read.
+fn file_state(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Write};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct TuningConfig {
+        speed: f32,
+    }
+
+    fn write_file(path: &Path, content: &str) {
+        File::create(path)
+            .and_then(|mut file| file.write_all(content.as_bytes()))
+            .expect("Failed to write config file");
+    }
+
+    #[test]
+    fn watcher_reloads_changed_files() {
+        let path = std::env::temp_dir().join("amethyst_config_watcher_reload.ron");
+        write_file(&path, "(speed: 1.0)");
+
+        let mut watcher =
+            ConfigWatcher::<TuningConfig>::new(&path).with_interval(Duration::from_secs(0));
+        assert!(watcher.poll().is_none());
+
+        write_file(&path, "(speed: 12.5)");
+        let reloaded = watcher.poll().expect("Change was not detected");
+        assert_eq!(reloaded.unwrap(), TuningConfig { speed: 12.5 });
+        assert!(watcher.poll().is_none());
+
+        write_file(&path, "(speed: not a number)");
+        let result = watcher.poll().expect("Change was not detected");
+        let _ = fs::remove_file(&path);
+        assert!(result.is_err());
+    }
+}
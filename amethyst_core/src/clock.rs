@@ -0,0 +1,312 @@
+//! Time scaling, pausing and named secondary clocks.
+
+use std::{collections::HashMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Time;
+
+/// Default of [TimeControl::max_fixed_updates], which does not limit the fixed updates.
+const DEFAULT_MAX_FIXED_UPDATES: u32 = u32::MAX;
+
+/// Controls how fast game time passes relative to real time.
+///
+/// The application applies this resource to [Time] at the end of every frame, when it advances
+/// the time by the duration of that frame. Changes made during a frame therefore show up in the
+/// delta time of the next frame. The scaled delta time also drives the fixed updates, so slow
+/// motion slows down the fixed update systems as well and pausing stops them, one frame after
+/// the change. The real time of [Time], e.g. `delta_real_time`, is not
+/// affected, so UI animations keep running while the game is paused.
+///
+/// Use this resource instead of `Time::set_time_scale`, which is overwritten every frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeControl {
+    time_scale: f32,
+    paused: bool,
+    max_fixed_updates: u32,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        TimeControl {
+            time_scale: 1.0,
+            paused: false,
+            max_fixed_updates: DEFAULT_MAX_FIXED_UPDATES,
+        }
+    }
+}
+
+impl TimeControl {
+    /// Returns the multiplier of game time relative to real time, ignoring the pause.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Sets the multiplier of game time relative to real time, e.g. `0.2` for slow motion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time_scale` is negative or not finite.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        assert!(
+            time_scale >= 0.0 && time_scale.is_finite(),
+            "Invalid time scale: {}",
+            time_scale
+        );
+        self.time_scale = time_scale;
+    }
+
+    /// Returns `true` if game time is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pauses or resumes game time.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Returns the time scale applied to [Time], which is `0.0` while paused.
+    pub fn effective_time_scale(&self) -> f32 {
+        if self.paused {
+            0.0
+        } else {
+            self.time_scale
+        }
+    }
+
+    /// Returns the maximum number of fixed updates run in a single frame.
+    pub fn max_fixed_updates(&self) -> u32 {
+        self.max_fixed_updates
+    }
+
+    /// Sets the maximum number of fixed updates run in a single frame.
+    ///
+    /// After a long hitch, the fixed updates which exceed this number are dropped instead of
+    /// being caught up over the following frames. By default the number is not limited.
+    pub fn set_max_fixed_updates(&mut self, max_fixed_updates: u32) {
+        self.max_fixed_updates = max_fixed_updates;
+    }
+}
+
+/// A secondary clock with its own time scale and pause state, advanced by the application
+/// every frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Clock {
+    time_scale: f32,
+    paused: bool,
+    delta_time: Duration,
+    absolute_time: Duration,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock {
+            time_scale: 1.0,
+            paused: false,
+            delta_time: Duration::default(),
+            absolute_time: Duration::default(),
+        }
+    }
+}
+
+impl Clock {
+    /// Creates a clock which runs at real time.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the time elapsed on this clock during the last frame.
+    pub fn delta_time(&self) -> Duration {
+        self.delta_time
+    }
+
+    /// Returns the time elapsed on this clock since it was created.
+    pub fn absolute_time(&self) -> Duration {
+        self.absolute_time
+    }
+
+    /// Returns the multiplier of this clock relative to real time.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Sets the multiplier of this clock relative to real time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time_scale` is negative or not finite.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        assert!(
+            time_scale >= 0.0 && time_scale.is_finite(),
+            "Invalid time scale: {}",
+            time_scale
+        );
+        self.time_scale = time_scale;
+    }
+
+    /// Returns `true` if this clock is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pauses or resumes this clock.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Advances the clock by a frame which took `real_time`.
+    pub fn advance(&mut self, real_time: Duration) {
+        self.delta_time = if self.paused {
+            Duration::default()
+        } else {
+            real_time.mul_f32(self.time_scale)
+        };
+        self.absolute_time += self.delta_time;
+    }
+}
+
+/// Named secondary clocks, advanced by the application every frame next to the game [Time].
+///
+/// By default this contains the [Clocks::UI] clock, which runs at real time and is not affected
+/// by [TimeControl].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Clocks {
+    clocks: HashMap<String, Clock>,
+}
+
+impl Default for Clocks {
+    fn default() -> Self {
+        let mut clocks = HashMap::new();
+        clocks.insert(Clocks::UI.to_string(), Clock::new());
+        Clocks { clocks }
+    }
+}
+
+impl Clocks {
+    /// Name of the clock used for user interfaces.
+    pub const UI: &'static str = "ui";
+
+    /// Adds a clock, replacing and returning the clock which had the same name.
+    pub fn insert<N: Into<String>>(&mut self, name: N, clock: Clock) -> Option<Clock> {
+        self.clocks.insert(name.into(), clock)
+    }
+
+    /// Removes the clock with the given name.
+    pub fn remove(&mut self, name: &str) -> Option<Clock> {
+        self.clocks.remove(name)
+    }
+
+    /// Returns the clock with the given name.
+    pub fn get(&self, name: &str) -> Option<&Clock> {
+        self.clocks.get(name)
+    }
+
+    /// Returns the clock with the given name mutably.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Clock> {
+        self.clocks.get_mut(name)
+    }
+
+    /// Returns all clocks and their names.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Clock)> {
+        self.clocks
+            .iter()
+            .map(|(name, clock)| (name.as_str(), clock))
+    }
+
+    /// Advances all clocks by a frame which took `real_time`.
+    pub fn advance(&mut self, real_time: Duration) {
+        for clock in self.clocks.values_mut() {
+            clock.advance(real_time);
+        }
+    }
+}
+
+/// Selects the clock a system measures time with.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ClockSource {
+    /// The game [Time], which follows [TimeControl].
+    Game,
+    /// The clock with this name in [Clocks]. If there is no such clock, no time passes.
+    Named(String),
+}
+
+impl Default for ClockSource {
+    fn default() -> Self {
+        ClockSource::Game
+    }
+}
+
+impl ClockSource {
+    /// Returns the time elapsed on the selected clock during the last frame.
+    pub fn delta_time(&self, time: &Time, clocks: &Clocks) -> Duration {
+        match self {
+            ClockSource::Game => time.delta_time(),
+            ClockSource::Named(name) => clocks.get(name).map(Clock::delta_time).unwrap_or_default(),
+        }
+    }
+
+    /// Returns the time elapsed on the selected clock since it was started.
+    pub fn absolute_time(&self, time: &Time, clocks: &Clocks) -> Duration {
+        match self {
+            ClockSource::Game => time.absolute_time(),
+            ClockSource::Named(name) => {
+                clocks
+                    .get(name)
+                    .map(Clock::absolute_time)
+                    .unwrap_or_default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clocks_follow_their_scale_and_pause() {
+        let mut clocks = Clocks::default();
+        let mut slow = Clock::new();
+        slow.set_time_scale(0.5);
+        clocks.insert("slow", slow);
+
+        clocks.advance(Duration::from_millis(100));
+        clocks.get_mut("slow").unwrap().set_paused(true);
+        clocks.advance(Duration::from_millis(100));
+
+        let ui = clocks.get(Clocks::UI).unwrap();
+        assert_eq!(Duration::from_millis(100), ui.delta_time());
+        assert_eq!(Duration::from_millis(200), ui.absolute_time());
+        let slow = clocks.get("slow").unwrap();
+        assert_eq!(Duration::default(), slow.delta_time());
+        assert_eq!(Duration::from_millis(50), slow.absolute_time());
+    }
+
+    #[test]
+    fn clock_source_reads_selected_clock() {
+        let mut time = Time::default();
+        time.advance_frame(Duration::from_millis(16));
+        let mut clocks = Clocks::default();
+        clocks.advance(Duration::from_millis(10));
+
+        assert_eq!(
+            Duration::from_millis(16),
+            ClockSource::Game.delta_time(&time, &clocks)
+        );
+        let ui = ClockSource::Named(Clocks::UI.to_string());
+        assert_eq!(Duration::from_millis(10), ui.delta_time(&time, &clocks));
+        let missing = ClockSource::Named("missing".to_string());
+        assert_eq!(Duration::default(), missing.absolute_time(&time, &clocks));
+    }
+
+    #[test]
+    fn pause_zeroes_effective_time_scale() {
+        let mut control = TimeControl::default();
+        control.set_time_scale(0.25);
+        assert_eq!(0.25, control.effective_time_scale());
+        control.set_paused(true);
+        assert_eq!(0.0, control.effective_time_scale());
+        assert_eq!(0.25, control.time_scale());
+    }
+}
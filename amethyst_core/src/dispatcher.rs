@@ -1,10 +1,49 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
 use amethyst_error::Error;
+use rayon::prelude::*;
 
-use crate::ecs::{
-    systems::{Executor, ParallelRunnable, Step},
-    *,
+use crate::{
+    ecs::{
+        storage::ComponentTypeId,
+        systems::{ParallelRunnable, ResourceTypeId, SystemId, UnsafeResources},
+        world::{ArchetypeAccess, WorldId},
+        *,
+    },
+    system_order::{resolve_stages, sort_items, OrderedItem, StageDecl, SystemOrder},
+    system_timings::{StepKind, SystemTimings, TimedRunnable, TimingRecorder},
+    system_toggles::{bundle_name, SystemToggles, Toggle, ToggledRunnable},
+    ArcThreadPool, Time,
 };
 
+/// Controls how [Dispatcher]s execute their systems.
+///
+/// Insert this as a resource before building a dispatcher to change how it runs. Without the
+/// resource, systems are executed in parallel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// Systems without conflicting accesses are executed in parallel, on the [ArcThreadPool]
+    /// resource if it exists.
+    Parallel,
+    /// Systems are executed one after another on the thread calling [Dispatcher::execute], in
+    /// the order they were added. This makes execution deterministic, which helps debugging.
+    SingleThreaded,
+}
+
+impl Default for ExecutionMode {
+    fn default() -> Self {
+        ExecutionMode::Parallel
+    }
+}
+
 /// A SystemBundle is a structure that adds multiple systems to the [Dispatcher] and loads/unloads all required resources.
 pub trait SystemBundle {
     /// This method is lazily evaluated when [Dispatcher] is built with [DispatcherBuilder::build].
@@ -57,56 +96,443 @@ where
 
 /// This structure is an intermediate step for building [Dispatcher]. When [DispatcherBuilder::build] is called,
 /// all system bundles are evaluated by calling [SystemBundle::load]. This structure is used to split systems
-/// (executable by [Schedule]) and system bundles (used for cleanup with unload).
+/// (executable by [Dispatcher]) and system bundles (used for cleanup with unload).
 #[derive(Default)]
 #[allow(missing_debug_implementations)]
 pub struct DispatcherData<'a> {
-    /// Holds all steps that can be executed by [Schedule].
-    steps: Vec<Step>,
-    /// Temporarily holds systems which are later combined into [Executor].
-    accumulator: Vec<Box<dyn ParallelRunnable + 'static>>,
+    /// Items executed every frame, in the order they were added.
+    main: Vec<OrderedItem>,
+    /// Items executed at the fixed update rate, in the order they were added.
+    fixed: Vec<OrderedItem>,
+    /// Stages added by the builder and its bundles.
+    stages: Vec<StageDecl>,
+    /// Names of the bundles which are currently being loaded, outermost first.
+    bundle_path: Vec<String>,
     /// Bundles that can be later used for cleanup by calling [SystemBundle::unload].
     bundles: Vec<Box<dyn SystemBundle + 'a>>,
+    /// Collects the [SystemTimings] of all systems of the dispatcher.
+    recorder: Arc<TimingRecorder>,
 }
 
-impl<'a> DispatcherData<'a> {
+/// Steps of a single schedule which is still being built.
+#[derive(Default)]
+struct ScheduleData {
+    /// Holds all steps that can be executed by [DispatchSchedule].
+    steps: Vec<DispatchStep>,
+    /// Names of the items of each step, used to print the schedule.
+    names: Vec<Vec<String>>,
+    /// Temporarily holds systems which are later combined into a single step.
+    accumulator: Vec<Box<dyn ParallelRunnable + 'static>>,
+    /// Names of the systems in `accumulator`.
+    accumulator_names: Vec<String>,
+    /// Toggles of all items which can be disabled.
+    toggles: Vec<Toggle>,
+}
+
+impl ScheduleData {
+    /// Collects the steps of the given items, which are sorted according to their ordering
+    /// constraints first.
+    fn sorted(
+        items: Vec<OrderedItem>,
+        stages: &[String],
+        recorder: &Arc<TimingRecorder>,
+    ) -> Result<Self, Error> {
+        let mut data = ScheduleData::default();
+        for (ordered, label) in sort_items(items, stages)? {
+            let mut name = label.clone().unwrap_or_else(|| "<unnamed>".to_string());
+            if let Some(stage) = &ordered.order.stage {
+                name = format!("{} [{}]", name, stage);
+            }
+            let toggle = Toggle::new(label.into_iter().chain(ordered.bundles).collect());
+            data.push_item(ordered.item, name, recorder, &toggle.enabled);
+            data.toggles.push(toggle);
+        }
+        Ok(data)
+    }
+
     fn finalize_executor(&mut self) {
         if !self.accumulator.is_empty() {
             let mut systems = Vec::new();
             std::mem::swap(&mut self.accumulator, &mut systems);
-            let executor = Executor::new(systems);
-            self.steps.push(Step::Systems(executor));
+            self.steps.push(DispatchStep::Sequential(systems));
+            self.names.push(std::mem::take(&mut self.accumulator_names));
+        }
+    }
+
+    fn push_step(&mut self, step: DispatchStep, names: Vec<String>) {
+        self.finalize_executor();
+        self.steps.push(step);
+        self.names.push(names);
+    }
+
+    fn push_item(
+        &mut self,
+        item: DispatcherItem,
+        name: String,
+        recorder: &Arc<TimingRecorder>,
+        enabled: &Arc<AtomicBool>,
+    ) {
+        match item {
+            DispatcherItem::System(s) => {
+                let timed = Box::new(TimedRunnable::new(s, recorder.clone()));
+                self.accumulator
+                    .push(Box::new(ToggledRunnable::new(timed, enabled.clone())));
+                self.accumulator_names.push(name);
+            }
+            DispatcherItem::FlushCmdBuffers => {
+                self.push_step(DispatchStep::FlushCmdBuffers, Vec::new());
+            }
+            DispatcherItem::ThreadLocalFn(mut f) => {
+                let enabled = enabled.clone();
+                let f = Box::new(move |world: &mut World, resources: &mut Resources| {
+                    if enabled.load(Ordering::Relaxed) {
+                        f(world, resources);
+                    }
+                });
+                self.push_step(DispatchStep::ThreadLocalFn(f), vec![name]);
+            }
+            DispatcherItem::ThreadLocalSystem(s) => {
+                let timed = Box::new(TimedRunnable::new(s, recorder.clone()));
+                let system = Box::new(ToggledRunnable::new(timed, enabled.clone()));
+                self.push_step(DispatchStep::ThreadLocalSystem(system), vec![name]);
+            }
+            DispatcherItem::SystemBundle(_) => {
+                unreachable!("System bundles are evaluated by `DispatcherBuilder::load`")
+            }
+        }
+    }
+
+    /// Builds the [DispatchSchedule] executing the steps in the given mode.
+    fn into_schedule(mut self, mode: ExecutionMode) -> DispatchSchedule {
+        self.finalize_executor();
+        let steps = self
+            .steps
+            .into_iter()
+            .map(|step| {
+                match (step, mode) {
+                    (DispatchStep::Sequential(systems), ExecutionMode::Parallel) => {
+                        DispatchStep::Parallel(ParallelStep::new(systems))
+                    }
+                    (step, _) => step,
+                }
+            })
+            .collect();
+        DispatchSchedule {
+            steps,
+            names: self.names,
+        }
+    }
+
+    /// Builds the fixed update [DispatchSchedule], or `None` when no fixed update systems were added.
+    fn into_fixed_schedule(mut self, mode: ExecutionMode) -> Option<DispatchSchedule> {
+        self.finalize_executor();
+        if self.steps.is_empty() {
+            None
+        } else {
+            self.push_step(DispatchStep::FlushCmdBuffers, Vec::new());
+            Some(self.into_schedule(mode))
+        }
+    }
+}
+
+/// A single step of a [DispatchSchedule].
+enum DispatchStep {
+    /// Systems executed in parallel on a thread pool.
+    Parallel(ParallelStep),
+    /// Systems executed one after another in the order they were added.
+    Sequential(Vec<Box<dyn ParallelRunnable + 'static>>),
+    /// Flushes the command buffers of the systems executed since the previous flush.
+    FlushCmdBuffers,
+    /// A function executed on the calling thread.
+    ThreadLocalFn(Box<dyn FnMut(&mut World, &mut Resources) + 'static>),
+    /// A system executed on the calling thread.
+    ThreadLocalSystem(Box<dyn Runnable + 'static>),
+}
+
+impl DispatchStep {
+    fn kind(&self) -> StepKind {
+        match self {
+            DispatchStep::Parallel(_) | DispatchStep::Sequential(_) => StepKind::Systems,
+            DispatchStep::FlushCmdBuffers => StepKind::FlushCmdBuffers,
+            DispatchStep::ThreadLocalFn(_) | DispatchStep::ThreadLocalSystem(_) => {
+                StepKind::ThreadLocal
+            }
+        }
+    }
+
+    fn flush_command_buffers(&mut self, world: &mut World, resources: &mut Resources) {
+        match self {
+            DispatchStep::Parallel(step) => flush_systems(&mut step.systems, world, resources),
+            DispatchStep::Sequential(systems) => flush_systems(systems, world, resources),
+            _ => {}
+        }
+    }
+}
+
+/// Steps executed in order by a [Dispatcher].
+///
+/// This mirrors legion's `Schedule`, but runs each parallel step on a given thread pool while
+/// thread local steps stay on the calling thread.
+struct DispatchSchedule {
+    steps: Vec<DispatchStep>,
+    /// Names of the items of each step.
+    names: Vec<Vec<String>>,
+}
+
+impl fmt::Display for DispatchSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (step, names)) in self.steps.iter().zip(&self.names).enumerate() {
+            let header = match step {
+                DispatchStep::Parallel(_) => "parallel systems:",
+                DispatchStep::Sequential(_) => "sequential systems:",
+                DispatchStep::FlushCmdBuffers => "flush command buffers",
+                DispatchStep::ThreadLocalFn(_) => "thread local function:",
+                DispatchStep::ThreadLocalSystem(_) => "thread local system:",
+            };
+            writeln!(f, "  {}. {}", index + 1, header)?;
+            for name in names {
+                writeln!(f, "       {}", name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DispatchSchedule {
+    fn execute(
+        &mut self,
+        world: &mut World,
+        resources: &mut Resources,
+        pool: Option<&ArcThreadPool>,
+        recorder: &TimingRecorder,
+    ) {
+        let mut waiting_flush = Vec::new();
+        for index in 0..self.steps.len() {
+            let start = recorder.step_start();
+            let kind = self.steps[index].kind();
+            if let DispatchStep::FlushCmdBuffers = self.steps[index] {
+                for waiting in waiting_flush.drain(..) {
+                    self.steps[waiting].flush_command_buffers(world, resources);
+                }
+            } else {
+                match &mut self.steps[index] {
+                    DispatchStep::Parallel(step) => {
+                        step.pool = pool.cloned();
+                        step.run(world, resources);
+                        waiting_flush.push(index);
+                    }
+                    DispatchStep::Sequential(systems) => {
+                        for system in systems.iter_mut() {
+                            system.run(world, resources);
+                        }
+                        waiting_flush.push(index);
+                    }
+                    DispatchStep::ThreadLocalFn(function) => function(world, resources),
+                    DispatchStep::ThreadLocalSystem(system) => {
+                        system.run(world, resources);
+                        if let Some(cmd) = system.command_buffer_mut(world.id()) {
+                            cmd.flush(world, resources);
+                        }
+                    }
+                    DispatchStep::FlushCmdBuffers => unreachable!(),
+                }
+            }
+            recorder.record_step(kind, start);
+        }
+    }
+}
+
+/// Flushes the command buffers of the given systems.
+fn flush_systems(
+    systems: &mut [Box<dyn ParallelRunnable + 'static>],
+    world: &mut World,
+    resources: &mut Resources,
+) {
+    for system in systems {
+        if let Some(cmd) = system.command_buffer_mut(world.id()) {
+            cmd.flush(world, resources);
+        }
+    }
+}
+
+/// Returns `true` if the two systems can't run at the same time, because one of them writes a
+/// resource or component the other one accesses.
+fn conflicts(a: &dyn ParallelRunnable, b: &dyn ParallelRunnable) -> bool {
+    let ((a_reads, a_component_reads), (a_writes, a_component_writes)) = (a.reads(), a.writes());
+    let ((b_reads, b_component_reads), (b_writes, b_component_writes)) = (b.reads(), b.writes());
+    a_writes
+        .iter()
+        .any(|resource| b_reads.contains(resource) || b_writes.contains(resource))
+        || b_writes.iter().any(|resource| a_reads.contains(resource))
+        || a_component_writes.iter().any(|component| {
+            b_component_reads.contains(component) || b_component_writes.contains(component)
+        })
+        || b_component_writes
+            .iter()
+            .any(|component| a_component_reads.contains(component))
+}
+
+/// Systems executed in parallel on a thread pool.
+///
+/// The systems are split into batches of consecutive systems which don't conflict with each
+/// other. The batches run one after another, and the systems of a batch in parallel. The whole
+/// step runs inside of a single `ThreadPool::install`, which only receives the world and the
+/// resources as legion shares them with its systems, so the resources never leave the calling
+/// thread.
+///
+/// The step is a [Runnable] itself, so legion hands it these shared resources.
+struct ParallelStep {
+    systems: Vec<Box<dyn ParallelRunnable + 'static>>,
+    batches: Vec<Range<usize>>,
+    /// The pool of the current execution, or `None` for rayon's global pool.
+    pool: Option<ArcThreadPool>,
+    reads: (Vec<ResourceTypeId>, Vec<ComponentTypeId>),
+    writes: (Vec<ResourceTypeId>, Vec<ComponentTypeId>),
+    archetypes: ArchetypeAccess,
+}
+
+impl ParallelStep {
+    fn new(systems: Vec<Box<dyn ParallelRunnable + 'static>>) -> Self {
+        let mut batches: Vec<Range<usize>> = Vec::new();
+        for index in 0..systems.len() {
+            match batches.last_mut() {
+                Some(batch)
+                    if !systems[batch.clone()]
+                        .iter()
+                        .any(|other| conflicts(other.as_ref(), systems[index].as_ref())) =>
+                {
+                    batch.end = index + 1;
+                }
+                _ => batches.push(index..index + 1),
+            }
+        }
+
+        let mut reads = (Vec::new(), Vec::new());
+        let mut writes = (Vec::new(), Vec::new());
+        for system in &systems {
+            let (resources, components) = system.reads();
+            reads.0.extend_from_slice(resources);
+            reads.1.extend_from_slice(components);
+            let (resources, components) = system.writes();
+            writes.0.extend_from_slice(resources);
+            writes.1.extend_from_slice(components);
+        }
+
+        ParallelStep {
+            systems,
+            batches,
+            pool: None,
+            reads,
+            writes,
+            archetypes: ArchetypeAccess::All,
+        }
+    }
+}
+
+impl Runnable for ParallelStep {
+    fn name(&self) -> Option<&SystemId> {
+        None
+    }
+
+    fn reads(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        (&self.reads.0, &self.reads.1)
+    }
+
+    fn writes(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        (&self.writes.0, &self.writes.1)
+    }
+
+    fn prepare(&mut self, world: &World) {
+        for system in &mut self.systems {
+            system.prepare(world);
+        }
+    }
+
+    fn accesses_archetypes(&self) -> &ArchetypeAccess {
+        &self.archetypes
+    }
+
+    unsafe fn run_unsafe(&mut self, world: &World, resources: &UnsafeResources) {
+        self.prepare(world);
+        let systems = &mut self.systems;
+        let batches = &self.batches;
+        let run = move || {
+            for batch in batches {
+                match &mut systems[batch.clone()] {
+                    [system] => system.run_unsafe(world, resources),
+                    batch => {
+                        batch
+                            .par_iter_mut()
+                            .for_each(|system| system.run_unsafe(world, resources))
+                    }
+                }
+            }
+        };
+        match &self.pool {
+            Some(pool) => pool.install(run),
+            None => run(),
         }
     }
+
+    fn command_buffer_mut(&mut self, _world: WorldId) -> Option<&mut CommandBuffer> {
+        None
+    }
 }
 
 /// A builder which is used to construct [Dispatcher] from multiple systems and system bundles.
+///
+/// Systems run in the order they were added, unless they are added with one of the
+/// `add_*_ordered` methods. Those take a [SystemOrder] which labels the system, puts it into a
+/// stage (see [stage](crate::stage)) or orders it before or after other systems, even ones added
+/// by other bundles. Systems without a stage run in the `simulation` stage. When the dispatcher is
+/// built, all items are sorted to satisfy these constraints while keeping the insertion order of
+/// unconstrained items.
+///
+/// Systems added with the `add_fixed_*` methods form a separate group which is executed by
+/// [Dispatcher::execute_fixed] at the fixed update rate instead of once per frame.
 #[derive(Default)]
 #[allow(missing_debug_implementations)]
 pub struct DispatcherBuilder {
-    items: Vec<DispatcherItem>,
+    items: Vec<OrderedItem>,
+    fixed_items: Vec<OrderedItem>,
+    stages: Vec<StageDecl>,
 }
 
 impl<'a> DispatcherBuilder {
     /// Adds a system to the schedule.
     pub fn add_system<S: System + 'a>(&mut self, system: S) -> &mut Self {
+        self.add_system_ordered(system, SystemOrder::default())
+    }
+
+    /// Adds a system to the schedule, ordered by the given constraints.
+    pub fn add_system_ordered<S: System + 'a>(
+        &mut self,
+        system: S,
+        order: SystemOrder,
+    ) -> &mut Self {
         log::debug!("Building system");
-        self.items.push(DispatcherItem::System(system.build()));
-        self
+        self.push(DispatcherItem::System(system.build()), order)
     }
 
     /// Adds a thread local system to the schedule. This system will be executed on the main thread.
     pub fn add_thread_local<T: ThreadLocalSystem<'a> + 'a>(&mut self, system: T) -> &mut Self {
-        self.items
-            .push(DispatcherItem::ThreadLocalSystem(system.build()));
-        self
+        self.add_thread_local_ordered(system, SystemOrder::default())
+    }
+
+    /// Adds a thread local system to the schedule, ordered by the given constraints. This system
+    /// will be executed on the main thread.
+    pub fn add_thread_local_ordered<T: ThreadLocalSystem<'a> + 'a>(
+        &mut self,
+        system: T,
+        order: SystemOrder,
+    ) -> &mut Self {
+        self.push(DispatcherItem::ThreadLocalSystem(system.build()), order)
     }
 
     /// Waits for executing systems to complete, and the flushes all outstanding system
     /// command buffers.
     pub fn flush(&mut self) -> &mut Self {
-        self.items.push(DispatcherItem::FlushCmdBuffers);
-        self
+        self.push(DispatcherItem::FlushCmdBuffers, SystemOrder::default())
     }
 
     /// Adds a thread local function to the schedule. This function will be executed on the main thread.
@@ -114,17 +540,110 @@ impl<'a> DispatcherBuilder {
         &mut self,
         f: F,
     ) -> &mut Self {
-        self.items.push(DispatcherItem::ThreadLocalFn(
+        self.add_thread_local_fn_ordered(f, SystemOrder::default())
+    }
+
+    /// Adds a thread local function to the schedule, ordered by the given constraints. This
+    /// function will be executed on the main thread.
+    pub fn add_thread_local_fn_ordered<F: FnMut(&mut World, &mut Resources) + 'static>(
+        &mut self,
+        f: F,
+        order: SystemOrder,
+    ) -> &mut Self {
+        self.push(
+            DispatcherItem::ThreadLocalFn(
+                Box::new(f) as Box<dyn FnMut(&mut World, &mut Resources) + 'static>
+            ),
+            order,
+        )
+    }
+
+    /// Adds a system to the fixed update schedule.
+    pub fn add_fixed_system<S: System + 'a>(&mut self, system: S) -> &mut Self {
+        log::debug!("Building fixed update system");
+        self.push_fixed(DispatcherItem::System(system.build()))
+    }
+
+    /// Adds a thread local system to the fixed update schedule. This system will be executed on
+    /// the main thread.
+    pub fn add_fixed_thread_local<T: ThreadLocalSystem<'a> + 'a>(
+        &mut self,
+        system: T,
+    ) -> &mut Self {
+        self.push_fixed(DispatcherItem::ThreadLocalSystem(system.build()))
+    }
+
+    /// Adds a thread local function to the fixed update schedule. This function will be executed
+    /// on the main thread.
+    pub fn add_fixed_thread_local_fn<F: FnMut(&mut World, &mut Resources) + 'static>(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.push_fixed(DispatcherItem::ThreadLocalFn(
             Box::new(f) as Box<dyn FnMut(&mut World, &mut Resources) + 'static>
-        ));
-        self
+        ))
+    }
+
+    /// Waits for executing fixed update systems to complete, and then flushes all outstanding
+    /// system command buffers.
+    pub fn flush_fixed(&mut self) -> &mut Self {
+        self.push_fixed(DispatcherItem::FlushCmdBuffers)
     }
 
     /// Adds [SystemBundle] to the dispatcher. System bundles allow inserting multiple systems
     /// and initialize any required entities or resources.
+    ///
+    /// The systems of the bundle can be disabled at runtime by the name of the bundle type, see
+    /// [SystemToggles].
     pub fn add_bundle<T: SystemBundle + 'static>(&mut self, bundle: T) -> &mut Self {
-        self.items
-            .push(DispatcherItem::SystemBundle(Box::new(bundle)));
+        self.push(
+            DispatcherItem::SystemBundle(Box::new(bundle)),
+            SystemOrder::new().label(bundle_name::<T>()),
+        )
+    }
+
+    /// Adds a stage which runs after all existing stages.
+    ///
+    /// Adding a stage which already exists has no effect.
+    pub fn add_stage<N: Into<String>>(&mut self, name: N) -> &mut Self {
+        self.stages.push(StageDecl {
+            name: name.into(),
+            after: None,
+        });
+        self
+    }
+
+    /// Adds a stage which runs right after the stage `after`.
+    ///
+    /// Adding a stage which already exists has no effect. Building the dispatcher fails if
+    /// `after` is not a stage.
+    pub fn add_stage_after<N: Into<String>, A: Into<String>>(
+        &mut self,
+        name: N,
+        after: A,
+    ) -> &mut Self {
+        self.stages.push(StageDecl {
+            name: name.into(),
+            after: Some(after.into()),
+        });
+        self
+    }
+
+    fn push(&mut self, item: DispatcherItem, order: SystemOrder) -> &mut Self {
+        self.items.push(OrderedItem {
+            item,
+            order,
+            bundles: Vec::new(),
+        });
+        self
+    }
+
+    fn push_fixed(&mut self, item: DispatcherItem) -> &mut Self {
+        self.fixed_items.push(OrderedItem {
+            item,
+            order: SystemOrder::default(),
+            bundles: Vec::new(),
+        });
         self
     }
 
@@ -135,29 +654,32 @@ impl<'a> DispatcherBuilder {
         resources: &mut Resources,
         data: &mut DispatcherData<'static>,
     ) -> Result<(), Error> {
-        for item in self.items.drain(..) {
-            match item {
-                DispatcherItem::System(s) => data.accumulator.push(s),
-                DispatcherItem::FlushCmdBuffers => {
-                    data.finalize_executor();
-                    data.steps.push(Step::FlushCmdBuffers);
-                }
-                DispatcherItem::ThreadLocalFn(f) => {
-                    data.finalize_executor();
-                    data.steps.push(Step::ThreadLocalFn(f));
-                }
-                DispatcherItem::ThreadLocalSystem(s) => {
-                    data.finalize_executor();
-                    data.steps.push(Step::ThreadLocalSystem(s));
-                }
+        data.stages.append(&mut self.stages);
+        for mut ordered in self.fixed_items.drain(..) {
+            ordered.bundles = data.bundle_path.clone();
+            data.fixed.push(ordered);
+        }
+
+        for ordered in self.items.drain(..) {
+            match ordered.item {
                 DispatcherItem::SystemBundle(mut bundle) => {
                     {
                         let mut builder = DispatcherBuilder::default();
                         bundle.load(world, resources, &mut builder)?;
+                        data.bundle_path
+                            .push(ordered.order.label.unwrap_or_default());
                         builder.load(world, resources, data)?;
+                        data.bundle_path.pop();
                     }
                     data.bundles.push(bundle);
                 }
+                item => {
+                    data.main.push(OrderedItem {
+                        item,
+                        order: ordered.order,
+                        bundles: data.bundle_path.clone(),
+                    })
+                }
             }
         }
 
@@ -165,6 +687,11 @@ impl<'a> DispatcherBuilder {
     }
 
     /// Finalizes the builder into a [Dispatcher]. This also evaluates all system bundles by calling [SystemBundle::load].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a bundle fails to load, or if the ordering constraints of the systems
+    /// refer to unknown labels or stages or contain a cycle.
     pub fn build(
         &mut self,
         world: &mut World,
@@ -172,16 +699,33 @@ impl<'a> DispatcherBuilder {
     ) -> Result<Dispatcher, Error> {
         let mut data = DispatcherData::default();
 
-        self.flush().load(world, resources, &mut data)?;
+        self.load(world, resources, &mut data)?;
+
+        let stages = resolve_stages(&data.stages)?;
+        let mut main = ScheduleData::sorted(data.main, &stages, &data.recorder)?;
+        main.push_step(DispatchStep::FlushCmdBuffers, Vec::new());
+        let mut fixed = ScheduleData::sorted(data.fixed, &stages, &data.recorder)?;
+        let mut toggles = std::mem::take(&mut main.toggles);
+        toggles.append(&mut fixed.toggles);
 
-        Ok(Dispatcher {
-            schedule: Schedule::from(data.steps),
+        let mode = resources
+            .get::<ExecutionMode>()
+            .map_or_else(ExecutionMode::default, |mode| *mode);
+
+        let dispatcher = Dispatcher {
+            schedule: main.into_schedule(mode),
+            fixed_schedule: fixed.into_fixed_schedule(mode),
             bundles: data.bundles,
-        })
+            recorder: data.recorder,
+            toggles,
+            disabled: HashSet::new(),
+        };
+        log::debug!("Built dispatcher:\n{}", dispatcher);
+        Ok(dispatcher)
     }
 }
 
-/// Dispatcher items. This is different from [Step] in that it contains [SystemBundle].
+/// Dispatcher items. This is different from legion's `Step` in that it contains [SystemBundle].
 #[allow(missing_debug_implementations)]
 pub enum DispatcherItem {
     /// A simple system.
@@ -196,19 +740,132 @@ pub enum DispatcherItem {
     SystemBundle(Box<dyn SystemBundle + 'static>),
 }
 
-/// Dispatcher is created by [DispatcherBuilder] and contains [Schedule] used to execute all systems.
+/// Dispatcher is created by [DispatcherBuilder] and contains the steps used to execute all systems.
+///
+/// The [ExecutionMode] resource present when the dispatcher is built decides whether systems run
+/// in parallel or one after another.
+///
+/// Formatting a dispatcher with `{}` prints its steps and the systems in them, in execution
+/// order, which helps to debug the ordering of systems.
 #[allow(missing_debug_implementations)]
 pub struct Dispatcher {
     // Used to execute unload on system bundles once dispatcher is disposed.
     bundles: Vec<Box<dyn SystemBundle>>,
-    schedule: Schedule,
+    schedule: DispatchSchedule,
+    fixed_schedule: Option<DispatchSchedule>,
+    recorder: Arc<TimingRecorder>,
+    toggles: Vec<Toggle>,
+    /// Names of the systems and bundles disabled with [Dispatcher::set_enabled].
+    disabled: HashSet<String>,
 }
 
 impl Dispatcher {
-    /// Executes systems according to the [Schedule].
+    /// Executes all systems once.
+    ///
+    /// Parallel systems run on the [ArcThreadPool] resource, or on rayon's global pool when the
+    /// resource does not exist. Thread local systems always run on the calling thread.
+    ///
+    /// If the [SystemTimings] resource exists, the execution times of the systems and steps are
+    /// added to it.
+    ///
+    /// Systems disabled with [Dispatcher::set_enabled] or the [SystemToggles] resource are
+    /// skipped.
     pub fn execute(&mut self, world: &mut World, resources: &mut Resources) {
-        // TODO: use ArcThreadPool from resources to dispatch legion
-        self.schedule.execute(world, resources);
+        self.update_toggles(resources);
+        let pool = thread_pool(resources);
+        let timed = self.begin_timing(resources);
+        self.schedule
+            .execute(world, resources, pool.as_ref(), &self.recorder);
+        self.end_timing(timed, resources);
+    }
+
+    /// Executes the fixed update systems. This should be called once per fixed update step.
+    pub fn execute_fixed(&mut self, world: &mut World, resources: &mut Resources) {
+        if self.fixed_schedule.is_none() {
+            return;
+        }
+        self.update_toggles(resources);
+        let pool = thread_pool(resources);
+        let timed = self.begin_timing(resources);
+        if let Some(schedule) = &mut self.fixed_schedule {
+            schedule.execute(world, resources, pool.as_ref(), &self.recorder);
+        }
+        self.end_timing(timed, resources);
+    }
+
+    /// Enables or disables the systems or bundles with the given name in this dispatcher.
+    ///
+    /// The name refers to the label of a system, which defaults to the name of the system, or to
+    /// the type name of a bundle. A system runs only if neither this dispatcher nor the
+    /// [SystemToggles] resource disables it. Returns `false` if no system or bundle of this
+    /// dispatcher has the name.
+    ///
+    /// ```
+    /// use amethyst_core::ecs::*;
+    ///
+    /// let mut world = World::default();
+    /// let mut resources = Resources::default();
+    /// resources.insert(0u32);
+    ///
+    /// let mut dispatcher = DispatcherBuilder::default()
+    ///     .add_system(|| {
+    ///         SystemBuilder::new("fly_movement")
+    ///             .write_resource::<u32>()
+    ///             .build(|_, _, count, _| **count += 1)
+    ///     })
+    ///     .build(&mut world, &mut resources)
+    ///     .unwrap();
+    ///
+    /// assert!(dispatcher.set_enabled("fly_movement", false));
+    /// dispatcher.execute(&mut world, &mut resources);
+    /// assert_eq!(0, *resources.get::<u32>().unwrap());
+    /// ```
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        if enabled {
+            self.disabled.remove(name);
+        } else {
+            self.disabled.insert(name.to_string());
+        }
+        self.toggles
+            .iter()
+            .any(|toggle| toggle.names.iter().any(|toggle_name| toggle_name == name))
+    }
+
+    /// Returns `false` if the systems or bundles with the given name are disabled in this
+    /// dispatcher with [Dispatcher::set_enabled].
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.contains(name)
+    }
+
+    fn update_toggles(&self, resources: &Resources) {
+        let toggles = resources.get::<SystemToggles>();
+        for toggle in &self.toggles {
+            toggle.update(&self.disabled, toggles.as_deref());
+        }
+    }
+
+    fn begin_timing(&self, resources: &Resources) -> bool {
+        let timed = resources.contains::<SystemTimings>();
+        self.recorder.set_enabled(timed);
+        timed
+    }
+
+    fn end_timing(&self, timed: bool, resources: &Resources) {
+        if !timed {
+            return;
+        }
+        let (systems, steps) = self.recorder.drain();
+        let frame = resources
+            .get::<Time>()
+            .map_or(0, |time| time.frame_number());
+        if let Some(mut timings) = resources.get_mut::<SystemTimings>() {
+            timings.record(frame, systems, steps);
+        }
+    }
+
+    /// Returns `true` if any systems were added to the fixed update schedule.
+    pub fn has_fixed_systems(&self) -> bool {
+        self.fixed_schedule.is_some()
     }
 
     /// Unloads any resources by calling [SystemBundle::unload] for stored system bundles and returns [DispatcherBuilder]
@@ -222,6 +879,25 @@ impl Dispatcher {
     }
 }
 
+impl fmt::Display for Dispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "schedule:")?;
+        self.schedule.fmt(f)?;
+        if let Some(fixed_schedule) = &self.fixed_schedule {
+            writeln!(f, "fixed update schedule:")?;
+            fixed_schedule.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the [ArcThreadPool] resource, if it exists.
+fn thread_pool(resources: &Resources) -> Option<ArcThreadPool> {
+    resources
+        .get::<ArcThreadPool>()
+        .map(|pool| ArcThreadPool::clone(&pool))
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -302,4 +978,375 @@ pub mod tests {
 
         assert_eq!(resources.get::<MyResource>().unwrap().0, true);
     }
+
+    #[test]
+    fn dispatcher_records_system_timings_while_resource_exists() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+
+        resources.insert(MyResource(false));
+        resources.insert(Time::default());
+
+        let mut dispatcher = DispatcherBuilder::default()
+            .add_system(MySystem)
+            .add_thread_local_fn(|_, _| {})
+            .build(&mut world, &mut resources)
+            .unwrap();
+
+        dispatcher.execute(&mut world, &mut resources);
+
+        resources.insert(SystemTimings::default());
+        dispatcher.execute(&mut world, &mut resources);
+        resources
+            .get_mut::<Time>()
+            .unwrap()
+            .advance_frame(std::time::Duration::from_millis(16));
+        dispatcher.execute(&mut world, &mut resources);
+
+        let timings = resources.get::<SystemTimings>().unwrap();
+        assert_eq!(2, timings.frames().count());
+
+        let frame = timings.latest().unwrap();
+        assert_eq!(1, frame.systems.len());
+        assert_eq!("test", &*frame.systems[0].name);
+        let kinds: Vec<_> = frame.steps.iter().map(|step| step.kind).collect();
+        assert_eq!(
+            vec![
+                StepKind::Systems,
+                StepKind::ThreadLocal,
+                StepKind::FlushCmdBuffers
+            ],
+            kinds
+        );
+    }
+
+    struct ThreadRecord(Vec<(usize, std::thread::ThreadId, Option<String>)>);
+
+    fn record_thread(index: usize) -> impl ParallelRunnable {
+        SystemBuilder::new(format!("record_{}", index))
+            .write_resource::<ThreadRecord>()
+            .build(move |_, _, record, _| {
+                let thread = std::thread::current();
+                record
+                    .0
+                    .push((index, thread.id(), thread.name().map(String::from)));
+            })
+    }
+
+    #[test]
+    fn dispatcher_runs_systems_on_thread_pool_resource() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+
+        resources.insert(ThreadRecord(Vec::new()));
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .thread_name(|index| format!("dispatcher-test-{}", index))
+            .build()
+            .unwrap();
+        resources.insert::<ArcThreadPool>(Arc::new(pool));
+
+        let mut builder = DispatcherBuilder::default();
+        for index in 0..3 {
+            builder.add_system(move || record_thread(index));
+        }
+        let mut dispatcher = builder.build(&mut world, &mut resources).unwrap();
+
+        dispatcher.execute(&mut world, &mut resources);
+
+        // The systems conflict on `ThreadRecord`, so they run one after another.
+        let record = resources.get::<ThreadRecord>().unwrap();
+        let order: Vec<_> = record.0.iter().map(|(index, _, _)| *index).collect();
+        assert_eq!(vec![0, 1, 2], order);
+        assert!(record
+            .0
+            .iter()
+            .all(|(_, _, name)| name.as_deref() == Some("dispatcher-test-0")));
+    }
+
+    #[test]
+    fn single_threaded_dispatcher_runs_systems_in_order_on_calling_thread() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+
+        resources.insert(ThreadRecord(Vec::new()));
+        resources.insert(ExecutionMode::SingleThreaded);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        resources.insert::<ArcThreadPool>(Arc::new(pool));
+
+        let mut builder = DispatcherBuilder::default();
+        for index in 0..4 {
+            builder.add_system(move || record_thread(index));
+        }
+        let mut dispatcher = builder.build(&mut world, &mut resources).unwrap();
+
+        dispatcher.execute(&mut world, &mut resources);
+
+        let caller = std::thread::current().id();
+        let record = resources.get::<ThreadRecord>().unwrap();
+        let order: Vec<_> = record.0.iter().map(|(index, _, _)| *index).collect();
+        assert_eq!(vec![0, 1, 2, 3], order);
+        assert!(record.0.iter().all(|(_, thread, _)| *thread == caller));
+    }
+
+    struct Trace(Vec<&'static str>);
+
+    fn trace(name: &'static str) -> impl FnMut(&mut World, &mut Resources) {
+        move |_, resources| resources.get_mut::<Trace>().unwrap().0.push(name)
+    }
+
+    fn run_traced(builder: &mut DispatcherBuilder) -> Result<Vec<&'static str>, Error> {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Trace(Vec::new()));
+
+        let mut dispatcher = builder.build(&mut world, &mut resources)?;
+        dispatcher.execute(&mut world, &mut resources);
+
+        let trace = resources.remove::<Trace>().unwrap();
+        Ok(trace.0)
+    }
+
+    #[test]
+    fn ordering_constraints_apply_across_bundles() {
+        struct Late;
+
+        impl SystemBundle for Late {
+            fn load(
+                &mut self,
+                _world: &mut World,
+                _resources: &mut Resources,
+                builder: &mut DispatcherBuilder,
+            ) -> Result<(), Error> {
+                builder.add_thread_local_fn_ordered(
+                    trace("late"),
+                    SystemOrder::new().label("late").after("early"),
+                );
+                Ok(())
+            }
+        }
+
+        let mut builder = DispatcherBuilder::default();
+        builder
+            .add_bundle(Late)
+            .add_thread_local_fn(trace("unordered"))
+            .add_thread_local_fn_ordered(trace("early"), SystemOrder::new().label("early"))
+            .add_thread_local_fn_ordered(trace("first"), SystemOrder::new().before("late"));
+
+        assert_eq!(
+            vec!["unordered", "early", "first", "late"],
+            run_traced(&mut builder).unwrap()
+        );
+    }
+
+    #[test]
+    fn stages_run_in_order() {
+        let mut builder = DispatcherBuilder::default();
+        builder
+            .add_stage_after("physics", stage::SIMULATION)
+            .add_thread_local_fn_ordered(trace("render"), SystemOrder::new().stage(stage::RENDER))
+            .add_thread_local_fn(trace("unstaged"))
+            .add_thread_local_fn_ordered(
+                trace("transform"),
+                SystemOrder::new().stage(stage::TRANSFORM),
+            )
+            .add_thread_local_fn_ordered(trace("physics"), SystemOrder::new().stage("physics"))
+            .add_thread_local_fn_ordered(trace("input"), SystemOrder::new().stage(stage::INPUT));
+
+        assert_eq!(
+            vec!["input", "unstaged", "physics", "transform", "render"],
+            run_traced(&mut builder).unwrap()
+        );
+    }
+
+    #[test]
+    fn flushes_are_barriers_and_bundles_can_be_referred_to() {
+        struct Early;
+
+        impl SystemBundle for Early {
+            fn load(
+                &mut self,
+                _world: &mut World,
+                _resources: &mut Resources,
+                builder: &mut DispatcherBuilder,
+            ) -> Result<(), Error> {
+                builder
+                    .add_thread_local_fn(trace("bundle a"))
+                    .add_thread_local_fn(trace("bundle b"));
+                Ok(())
+            }
+        }
+
+        let mut builder = DispatcherBuilder::default();
+        builder
+            .add_thread_local_fn_ordered(trace("after bundle"), SystemOrder::new().after("Early"))
+            .add_bundle(Early)
+            .flush()
+            .add_thread_local_fn_ordered(trace("input"), SystemOrder::new().stage(stage::INPUT))
+            .add_thread_local_fn(trace("after flush"));
+
+        assert_eq!(
+            vec![
+                "input",
+                "bundle a",
+                "bundle b",
+                "after bundle",
+                "after flush"
+            ],
+            run_traced(&mut builder).unwrap()
+        );
+
+        let mut builder = DispatcherBuilder::default();
+        builder
+            .add_thread_local_fn_ordered(trace("a"), SystemOrder::new().label("a"))
+            .flush()
+            .add_thread_local_fn_ordered(trace("b"), SystemOrder::new().label("b").before("a"));
+        let error = run_traced(&mut builder).unwrap_err().to_string();
+        assert_eq!(
+            "Cycle in the ordering constraints of systems `a`, `<flush #1>`, `b`",
+            error
+        );
+    }
+
+    #[test]
+    fn ordering_errors_are_reported() {
+        let mut builder = DispatcherBuilder::default();
+        builder
+            .add_thread_local_fn_ordered(trace("a"), SystemOrder::new().label("a").after("b"))
+            .add_thread_local_fn_ordered(trace("b"), SystemOrder::new().label("b").after("a"));
+        let error = run_traced(&mut builder).unwrap_err().to_string();
+        assert_eq!(
+            "Cycle in the ordering constraints of systems `a`, `b`",
+            error
+        );
+
+        let mut builder = DispatcherBuilder::default();
+        builder.add_thread_local_fn_ordered(trace("a"), SystemOrder::new().label("a").before("c"));
+        let error = run_traced(&mut builder).unwrap_err().to_string();
+        assert_eq!("System `a` is ordered before unknown label `c`", error);
+
+        let mut builder = DispatcherBuilder::default();
+        builder.add_thread_local_fn_ordered(trace("a"), SystemOrder::new().stage("nowhere"));
+        let error = run_traced(&mut builder).unwrap_err().to_string();
+        assert_eq!("System `<unnamed #0>` is in unknown stage `nowhere`", error);
+
+        let mut builder = DispatcherBuilder::default();
+        builder
+            .add_thread_local_fn_ordered(trace("a"), SystemOrder::new().label("a").before("b"))
+            .add_thread_local_fn_ordered(
+                trace("b"),
+                SystemOrder::new().label("b").stage(stage::INPUT),
+            );
+        let error = run_traced(&mut builder).unwrap_err().to_string();
+        assert_eq!(
+            "System `a` in stage `simulation` is ordered before `b` in stage `input`, which runs \
+             earlier",
+            error
+        );
+    }
+
+    #[test]
+    fn dispatcher_prints_schedule() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(MyResource(false));
+
+        let dispatcher = DispatcherBuilder::default()
+            .add_system_ordered(MySystem, SystemOrder::new().stage(stage::SIMULATION))
+            .add_thread_local_fn(|_, _| {})
+            .build(&mut world, &mut resources)
+            .unwrap();
+
+        assert_eq!(
+            "schedule:\n  \
+             1. parallel systems:\n       \
+             test [simulation]\n  \
+             2. thread local function:\n       \
+             <unnamed>\n  \
+             3. flush command buffers\n",
+            dispatcher.to_string()
+        );
+    }
+
+    #[test]
+    fn systems_and_bundles_can_be_toggled() {
+        struct Gameplay;
+
+        impl SystemBundle for Gameplay {
+            fn load(
+                &mut self,
+                _world: &mut World,
+                _resources: &mut Resources,
+                builder: &mut DispatcherBuilder,
+            ) -> Result<(), Error> {
+                builder
+                    .add_thread_local_fn_ordered(
+                        trace("movement"),
+                        SystemOrder::new().label("movement"),
+                    )
+                    .add_system(MySystem);
+                Ok(())
+            }
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Trace(Vec::new()));
+        resources.insert(MyResource(false));
+
+        let mut dispatcher = DispatcherBuilder::default()
+            .add_bundle(Gameplay)
+            .add_thread_local_fn_ordered(trace("menu"), SystemOrder::new().label("menu"))
+            .build(&mut world, &mut resources)
+            .unwrap();
+        let mut run = |dispatcher: &mut Dispatcher, resources: &mut Resources| {
+            resources.get_mut::<Trace>().unwrap().0.clear();
+            resources.get_mut::<MyResource>().unwrap().0 = false;
+            dispatcher.execute(&mut world, resources);
+            let trace = resources.get::<Trace>().unwrap().0.clone();
+            (trace, resources.get::<MyResource>().unwrap().0)
+        };
+
+        assert!(dispatcher.set_enabled("movement", false));
+        assert!(!dispatcher.is_enabled("movement"));
+        assert_eq!((vec!["menu"], true), run(&mut dispatcher, &mut resources));
+
+        assert!(dispatcher.set_enabled("movement", true));
+        let mut toggles = SystemToggles::default();
+        toggles.set_enabled("Gameplay", false);
+        resources.insert(toggles);
+        assert_eq!((vec!["menu"], false), run(&mut dispatcher, &mut resources));
+
+        resources.get_mut::<SystemToggles>().unwrap().enable_all();
+        assert_eq!(
+            (vec!["movement", "menu"], true),
+            run(&mut dispatcher, &mut resources)
+        );
+
+        assert!(!dispatcher.set_enabled("unknown", false));
+    }
+
+    #[test]
+    fn dispatcher_fixed_systems_run_only_on_fixed_update() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+
+        resources.insert(MyResource(false));
+
+        let mut dispatcher = DispatcherBuilder::default()
+            .add_fixed_system(MySystem)
+            .build(&mut world, &mut resources)
+            .unwrap();
+
+        assert!(dispatcher.has_fixed_systems());
+
+        dispatcher.execute(&mut world, &mut resources);
+        assert_eq!(resources.get::<MyResource>().unwrap().0, false);
+
+        dispatcher.execute_fixed(&mut world, &mut resources);
+        assert_eq!(resources.get::<MyResource>().unwrap().0, true);
+    }
 }
@@ -1,6 +1,12 @@
 //!
 //! Geometry helper functionality.
-use nalgebra::{one, zero, Point3, RealField, Vector3};
+use nalgebra::{
+    convert, one, zero, Matrix3, Matrix4, Point3, RealField, Rotation3, UnitQuaternion, Vector3,
+    Vector4, U3,
+};
+use simba::scalar::SupersetOf;
+
+use crate::transform::Transform;
 
 /// A plane which can be intersected by a ray.
 #[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -10,7 +16,7 @@ where
 {
     /// The plane described as x,y,z normal
     normal: Vector3<T>,
-    /// dot product of the point and normal, representing the plane position
+    /// negated dot product of a point on the plane and the normal, representing the plane position
     bias: T,
 }
 impl<T> Plane<T>
@@ -27,7 +33,7 @@ where
         let normalized = normal.normalize();
         Self {
             normal: Vector3::new(normalized.x, normalized.y, normalized.z),
-            bias: point.coords.dot(&normalized),
+            bias: -point.coords.dot(&normalized),
         }
     }
 
@@ -135,8 +141,738 @@ where
     }
 }
 
+/// A geometric primitive which can be moved into another coordinate space.
+pub trait Transformable<T>: Sized
+where
+    T: RealField,
+{
+    /// Returns the primitive transformed by an affine transformation matrix.
+    fn transformed(&self, matrix: &Matrix4<T>) -> Self;
+
+    /// Returns the primitive moved from the local space of an entity into world space, using the
+    /// global matrix of its `Transform`.
+    fn transformed_by(&self, transform: &Transform) -> Self
+    where
+        T: SupersetOf<f32>,
+    {
+        self.transformed(&convert(*transform.global_matrix()))
+    }
+}
+
+/// Returns the largest factor by which the matrix scales a vector.
+fn max_scale<T: RealField>(matrix: &Matrix4<T>) -> T {
+    let linear = matrix.fixed_slice::<U3, U3>(0, 0);
+    linear
+        .column(0)
+        .norm()
+        .max(linear.column(1).norm())
+        .max(linear.column(2).norm())
+}
+
+/// Returns the point of the segment from `a` to `b` which is closest to `point`.
+fn closest_point_on_segment<T: RealField>(
+    a: &Point3<T>,
+    b: &Point3<T>,
+    point: &Point3<T>,
+) -> Point3<T> {
+    let segment = b - a;
+    let length_squared = segment.norm_squared();
+    if length_squared.is_zero() {
+        return *a;
+    }
+    let t = ((point - a).dot(&segment) / length_squared)
+        .max(zero())
+        .min(one());
+    a + segment * t
+}
+
+/// Returns the distance along a ray with a normalized direction at which it enters the sphere,
+/// or zero if it starts inside.
+fn intersect_ray_sphere<T: RealField>(ray: &Ray<T>, center: &Point3<T>, radius: T) -> Option<T> {
+    let offset = ray.origin - center;
+    let b = offset.dot(&ray.direction);
+    let c = offset.norm_squared() - radius * radius;
+    if c > zero() && b > zero() {
+        return None;
+    }
+    let discriminant = b * b - c;
+    if discriminant < zero() {
+        return None;
+    }
+    Some((-b - discriminant.sqrt()).max(zero()))
+}
+
+/// An axis aligned bounding box.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Aabb<T>
+where
+    T: RealField,
+{
+    /// The corner with the smallest coordinates
+    pub min: Point3<T>,
+    /// The corner with the largest coordinates
+    pub max: Point3<T>,
+}
+
+impl<T> Aabb<T>
+where
+    T: RealField,
+{
+    /// Create a new `Aabb` spanning the two given corners, in any order.
+    pub fn new(a: Point3<T>, b: Point3<T>) -> Self {
+        Aabb {
+            min: Point3::from(a.coords.inf(&b.coords)),
+            max: Point3::from(a.coords.sup(&b.coords)),
+        }
+    }
+
+    /// Create a new `Aabb` from its center and half of its size along each axis.
+    pub fn from_center_half_extents(center: Point3<T>, half_extents: Vector3<T>) -> Self {
+        let half_extents = half_extents.abs();
+        Aabb {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /// Create the smallest `Aabb` containing all given points, or `None` if there are none.
+    pub fn from_points<'a, I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Point3<T>>,
+        T: 'a,
+    {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(Aabb::new(first, first), |aabb, point| {
+            aabb.merged(&Aabb::new(*point, *point))
+        }))
+    }
+
+    /// The center of this `Aabb`
+    pub fn center(&self) -> Point3<T> {
+        nalgebra::center(&self.min, &self.max)
+    }
+
+    /// Half of the size of this `Aabb` along each axis
+    pub fn half_extents(&self) -> Vector3<T> {
+        self.size() * convert::<f64, T>(0.5)
+    }
+
+    /// The size of this `Aabb` along each axis
+    pub fn size(&self) -> Vector3<T> {
+        self.max - self.min
+    }
+
+    /// The surface area of this `Aabb`
+    pub fn surface_area(&self) -> T {
+        let size = self.size();
+        (size.x * size.y + size.y * size.z + size.z * size.x) * convert::<f64, T>(2.0)
+    }
+
+    /// Returns the eight corners of this `Aabb`.
+    pub fn corners(&self) -> [Point3<T>; 8] {
+        let (min, max) = (&self.min, &self.max);
+        [
+            Point3::new(min.x, min.y, min.z),
+            Point3::new(max.x, min.y, min.z),
+            Point3::new(min.x, max.y, min.z),
+            Point3::new(max.x, max.y, min.z),
+            Point3::new(min.x, min.y, max.z),
+            Point3::new(max.x, min.y, max.z),
+            Point3::new(min.x, max.y, max.z),
+            Point3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// Returns this `Aabb` grown by `margin` in every direction.
+    pub fn grown(&self, margin: T) -> Self {
+        let margin = Vector3::repeat(margin);
+        Aabb {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    /// Returns the smallest `Aabb` containing both this and the other `Aabb`.
+    pub fn merged(&self, other: &Aabb<T>) -> Self {
+        Aabb {
+            min: Point3::from(self.min.coords.inf(&other.min.coords)),
+            max: Point3::from(self.max.coords.sup(&other.max.coords)),
+        }
+    }
+
+    /// Returns `true` if the point is inside or on the boundary of this `Aabb`.
+    pub fn contains_point(&self, point: &Point3<T>) -> bool {
+        (0..3).all(|axis| self.min[axis] <= point[axis] && point[axis] <= self.max[axis])
+    }
+
+    /// Returns `true` if the other `Aabb` is completely inside this one.
+    pub fn contains_aabb(&self, other: &Aabb<T>) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.min[axis] && other.max[axis] <= self.max[axis])
+    }
+
+    /// Returns `true` if this and the other `Aabb` overlap or touch.
+    pub fn intersects_aabb(&self, other: &Aabb<T>) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    /// Returns `true` if the sphere overlaps or touches this `Aabb`.
+    pub fn intersects_sphere(&self, sphere: &Sphere<T>) -> bool {
+        self.distance_squared(&sphere.center) <= sphere.radius * sphere.radius
+    }
+
+    /// Returns the point of this `Aabb` closest to `point`, which is `point` itself if it is
+    /// inside.
+    pub fn closest_point(&self, point: &Point3<T>) -> Point3<T> {
+        Point3::from(point.coords.sup(&self.min.coords).inf(&self.max.coords))
+    }
+
+    /// Returns the squared distance between `point` and this `Aabb`, zero if it is inside.
+    pub fn distance_squared(&self, point: &Point3<T>) -> T {
+        (self.closest_point(point) - point).norm_squared()
+    }
+
+    /// Returns the distance along the `Ray` at which it enters this `Aabb`, zero if it starts
+    /// inside, or `None` if it misses.
+    ///
+    /// The distance is measured in multiples of the direction of the ray, which doesn't need
+    /// to be normalized.
+    pub fn intersect_ray(&self, ray: &Ray<T>) -> Option<T> {
+        let mut near: T = zero();
+        let mut far: Option<T> = None;
+        for axis in 0..3 {
+            let origin = ray.origin[axis];
+            let direction = ray.direction[axis];
+            if direction.is_zero() {
+                if origin < self.min[axis] || origin > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let mut t0 = (self.min[axis] - origin) / direction;
+            let mut t1 = (self.max[axis] - origin) / direction;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            near = near.max(t0);
+            let limit = far.map_or(t1, |far| far.min(t1));
+            if near > limit {
+                return None;
+            }
+            far = Some(limit);
+        }
+        Some(near)
+    }
+}
+
+impl<T> Transformable<T> for Aabb<T>
+where
+    T: RealField,
+{
+    /// Returns the smallest `Aabb` containing this one after the transformation.
+    fn transformed(&self, matrix: &Matrix4<T>) -> Self {
+        let linear = matrix.fixed_slice::<U3, U3>(0, 0).abs();
+        Aabb::from_center_half_extents(
+            matrix.transform_point(&self.center()),
+            linear * self.half_extents(),
+        )
+    }
+}
+
+/// A box with an arbitrary orientation.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Obb<T>
+where
+    T: RealField,
+{
+    /// The center of the box
+    pub center: Point3<T>,
+    /// Half of the size of the box along each of its axes
+    pub half_extents: Vector3<T>,
+    /// The rotation from the axes of the box to the world axes
+    pub rotation: UnitQuaternion<T>,
+}
+
+impl<T> Obb<T>
+where
+    T: RealField,
+{
+    /// Create a new `Obb`.
+    pub fn new(center: Point3<T>, half_extents: Vector3<T>, rotation: UnitQuaternion<T>) -> Self {
+        Obb {
+            center,
+            half_extents: half_extents.abs(),
+            rotation,
+        }
+    }
+
+    /// Create an `Obb` covering the same space as an `Aabb`.
+    pub fn from_aabb(aabb: &Aabb<T>) -> Self {
+        Obb {
+            center: aabb.center(),
+            half_extents: aabb.half_extents(),
+            rotation: UnitQuaternion::identity(),
+        }
+    }
+
+    /// The normalized axes of this `Obb`
+    pub fn axes(&self) -> [Vector3<T>; 3] {
+        [
+            self.rotation * Vector3::x(),
+            self.rotation * Vector3::y(),
+            self.rotation * Vector3::z(),
+        ]
+    }
+
+    /// Returns the eight corners of this `Obb`.
+    pub fn corners(&self) -> [Point3<T>; 8] {
+        let mut corners = self.local_aabb().corners();
+        for corner in &mut corners {
+            *corner = self.center + self.rotation * corner.coords;
+        }
+        corners
+    }
+
+    /// Returns the smallest `Aabb` containing this `Obb`.
+    pub fn aabb(&self) -> Aabb<T> {
+        let linear = self.rotation.to_rotation_matrix().into_inner().abs();
+        Aabb::from_center_half_extents(self.center, linear * self.half_extents)
+    }
+
+    /// Returns `true` if the point is inside or on the boundary of this `Obb`.
+    pub fn contains_point(&self, point: &Point3<T>) -> bool {
+        self.local_aabb().contains_point(&self.to_local(point))
+    }
+
+    /// Returns the point of this `Obb` closest to `point`, which is `point` itself if it is
+    /// inside.
+    pub fn closest_point(&self, point: &Point3<T>) -> Point3<T> {
+        let local = self.local_aabb().closest_point(&self.to_local(point));
+        self.center + self.rotation * local.coords
+    }
+
+    /// Returns the distance along the `Ray` at which it enters this `Obb`, zero if it starts
+    /// inside, or `None` if it misses.
+    ///
+    /// The distance is measured in multiples of the direction of the ray, which doesn't need
+    /// to be normalized.
+    pub fn intersect_ray(&self, ray: &Ray<T>) -> Option<T> {
+        let local = Ray {
+            origin: self.to_local(&ray.origin),
+            direction: self.rotation.inverse_transform_vector(&ray.direction),
+        };
+        self.local_aabb().intersect_ray(&local)
+    }
+
+    /// The box in its own coordinate space, centered at the origin.
+    fn local_aabb(&self) -> Aabb<T> {
+        Aabb::from_center_half_extents(Point3::origin(), self.half_extents)
+    }
+
+    fn to_local(&self, point: &Point3<T>) -> Point3<T> {
+        Point3::from(
+            self.rotation
+                .inverse_transform_vector(&(point - self.center)),
+        )
+    }
+}
+
+impl<T> Transformable<T> for Obb<T>
+where
+    T: RealField,
+{
+    /// Returns the `Obb` after the transformation. Its axes stay perpendicular, so a matrix
+    /// which shears the box yields an approximation.
+    fn transformed(&self, matrix: &Matrix4<T>) -> Self {
+        let linear = matrix.fixed_slice::<U3, U3>(0, 0);
+        let [x, y, z] = self.axes();
+        let (x, y, z) = (linear * x, linear * y, linear * z);
+        let half_extents = Vector3::new(
+            self.half_extents.x * x.norm(),
+            self.half_extents.y * y.norm(),
+            self.half_extents.z * z.norm(),
+        );
+        let x = x.normalize();
+        let y = (y - x * x.dot(&y)).normalize();
+        let z = x.cross(&y);
+        let rotation = Rotation3::from_matrix_unchecked(Matrix3::from_columns(&[x, y, z]));
+        Obb {
+            center: matrix.transform_point(&self.center),
+            half_extents,
+            rotation: UnitQuaternion::from_rotation_matrix(&rotation),
+        }
+    }
+}
+
+/// A sphere.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Sphere<T>
+where
+    T: RealField,
+{
+    /// The center of the sphere
+    pub center: Point3<T>,
+    /// The radius of the sphere
+    pub radius: T,
+}
+
+impl<T> Sphere<T>
+where
+    T: RealField,
+{
+    /// Create a new `Sphere`.
+    pub fn new(center: Point3<T>, radius: T) -> Self {
+        Sphere {
+            center,
+            radius: radius.abs(),
+        }
+    }
+
+    /// Returns the smallest `Aabb` containing this `Sphere`.
+    pub fn aabb(&self) -> Aabb<T> {
+        Aabb::from_center_half_extents(self.center, Vector3::repeat(self.radius))
+    }
+
+    /// Returns `true` if the point is inside or on the surface of this `Sphere`.
+    pub fn contains_point(&self, point: &Point3<T>) -> bool {
+        (point - self.center).norm_squared() <= self.radius * self.radius
+    }
+
+    /// Returns `true` if this and the other `Sphere` overlap or touch.
+    pub fn intersects_sphere(&self, other: &Sphere<T>) -> bool {
+        let reach = self.radius + other.radius;
+        (other.center - self.center).norm_squared() <= reach * reach
+    }
+
+    /// Returns `true` if the `Aabb` overlaps or touches this `Sphere`.
+    pub fn intersects_aabb(&self, aabb: &Aabb<T>) -> bool {
+        aabb.intersects_sphere(self)
+    }
+
+    /// Returns the point of this `Sphere` closest to `point`, which is `point` itself if it is
+    /// inside.
+    pub fn closest_point(&self, point: &Point3<T>) -> Point3<T> {
+        let offset = point - self.center;
+        let distance = offset.norm();
+        if distance <= self.radius {
+            *point
+        } else {
+            self.center + offset * (self.radius / distance)
+        }
+    }
+
+    /// Returns the distance along the `Ray` at which it enters this `Sphere`, zero if it starts
+    /// inside, or `None` if it misses.
+    ///
+    /// The direction of the ray must be normalized.
+    pub fn intersect_ray(&self, ray: &Ray<T>) -> Option<T> {
+        intersect_ray_sphere(ray, &self.center, self.radius)
+    }
+}
+
+impl<T> Transformable<T> for Sphere<T>
+where
+    T: RealField,
+{
+    /// Returns the `Sphere` after the transformation. A non uniform scale grows the sphere by
+    /// the largest scale factor, so it still contains the transformed sphere.
+    fn transformed(&self, matrix: &Matrix4<T>) -> Self {
+        Sphere {
+            center: matrix.transform_point(&self.center),
+            radius: self.radius * max_scale(matrix),
+        }
+    }
+}
+
+/// A capsule, which is the set of points within `radius` of the segment from `a` to `b`.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Capsule<T>
+where
+    T: RealField,
+{
+    /// The first end of the segment
+    pub a: Point3<T>,
+    /// The second end of the segment
+    pub b: Point3<T>,
+    /// The radius around the segment
+    pub radius: T,
+}
+
+impl<T> Capsule<T>
+where
+    T: RealField,
+{
+    /// Create a new `Capsule`.
+    pub fn new(a: Point3<T>, b: Point3<T>, radius: T) -> Self {
+        Capsule {
+            a,
+            b,
+            radius: radius.abs(),
+        }
+    }
+
+    /// Returns the smallest `Aabb` containing this `Capsule`.
+    pub fn aabb(&self) -> Aabb<T> {
+        Aabb::new(self.a, self.b).grown(self.radius)
+    }
+
+    /// Returns the point of the segment of this `Capsule` closest to `point`.
+    pub fn closest_point_on_segment(&self, point: &Point3<T>) -> Point3<T> {
+        closest_point_on_segment(&self.a, &self.b, point)
+    }
+
+    /// Returns `true` if the point is inside or on the surface of this `Capsule`.
+    pub fn contains_point(&self, point: &Point3<T>) -> bool {
+        (point - self.closest_point_on_segment(point)).norm_squared() <= self.radius * self.radius
+    }
+
+    /// Returns `true` if the `Sphere` overlaps or touches this `Capsule`.
+    pub fn intersects_sphere(&self, sphere: &Sphere<T>) -> bool {
+        Sphere::new(self.closest_point_on_segment(&sphere.center), self.radius)
+            .intersects_sphere(sphere)
+    }
+
+    /// Returns the point of this `Capsule` closest to `point`, which is `point` itself if it
+    /// is inside.
+    pub fn closest_point(&self, point: &Point3<T>) -> Point3<T> {
+        Sphere::new(self.closest_point_on_segment(point), self.radius).closest_point(point)
+    }
+}
+
+impl<T> Transformable<T> for Capsule<T>
+where
+    T: RealField,
+{
+    /// Returns the `Capsule` after the transformation. A non uniform scale grows the radius by
+    /// the largest scale factor, so it still contains the transformed capsule.
+    fn transformed(&self, matrix: &Matrix4<T>) -> Self {
+        Capsule {
+            a: matrix.transform_point(&self.a),
+            b: matrix.transform_point(&self.b),
+            radius: self.radius * max_scale(matrix),
+        }
+    }
+}
+
+/// A triangle.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Triangle<T>
+where
+    T: RealField,
+{
+    /// The first corner
+    pub a: Point3<T>,
+    /// The second corner
+    pub b: Point3<T>,
+    /// The third corner
+    pub c: Point3<T>,
+}
+
+impl<T> Triangle<T>
+where
+    T: RealField,
+{
+    /// Create a new `Triangle`.
+    pub fn new(a: Point3<T>, b: Point3<T>, c: Point3<T>) -> Self {
+        Triangle { a, b, c }
+    }
+
+    /// The normal of the side from which the corners appear counter clockwise, or `None` if
+    /// the triangle is degenerate.
+    pub fn normal(&self) -> Option<Vector3<T>> {
+        (self.b - self.a)
+            .cross(&(self.c - self.a))
+            .try_normalize(zero())
+    }
+
+    /// The area of this `Triangle`
+    pub fn area(&self) -> T {
+        (self.b - self.a).cross(&(self.c - self.a)).norm() * convert::<f64, T>(0.5)
+    }
+
+    /// Returns the smallest `Aabb` containing this `Triangle`.
+    pub fn aabb(&self) -> Aabb<T> {
+        Aabb::new(self.a, self.b).merged(&Aabb::new(self.c, self.c))
+    }
+
+    /// Returns the point of this `Triangle` closest to `point`.
+    pub fn closest_point(&self, point: &Point3<T>) -> Point3<T> {
+        // Finds the Voronoi region of the triangle `point` is in, from "Real-Time Collision
+        // Detection" by Christer Ericson.
+        let (a, b, c) = (&self.a, &self.b, &self.c);
+        let ab = b - a;
+        let ac = c - a;
+        let ap = point - a;
+        let d1 = ab.dot(&ap);
+        let d2 = ac.dot(&ap);
+        if d1 <= zero() && d2 <= zero() {
+            return *a;
+        }
+        let bp = point - b;
+        let d3 = ab.dot(&bp);
+        let d4 = ac.dot(&bp);
+        if d3 >= zero() && d4 <= d3 {
+            return *b;
+        }
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= zero() && d1 >= zero() && d3 <= zero() {
+            return a + ab * (d1 / (d1 - d3));
+        }
+        let cp = point - c;
+        let d5 = ab.dot(&cp);
+        let d6 = ac.dot(&cp);
+        if d6 >= zero() && d5 <= d6 {
+            return *c;
+        }
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= zero() && d2 >= zero() && d6 <= zero() {
+            return a + ac * (d2 / (d2 - d6));
+        }
+        let va = d3 * d6 - d5 * d4;
+        if va <= zero() && d4 >= d3 && d5 >= d6 {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+        let denominator = one() / (va + vb + vc);
+        a + ab * (vb * denominator) + ac * (vc * denominator)
+    }
+
+    /// Returns the distance along the `Ray` at which it hits this `Triangle` from either side,
+    /// or `None` if it misses.
+    ///
+    /// The distance is measured in multiples of the direction of the ray, which doesn't need
+    /// to be normalized.
+    pub fn intersect_ray(&self, ray: &Ray<T>) -> Option<T> {
+        // Möller–Trumbore intersection.
+        let edge1 = self.b - self.a;
+        let edge2 = self.c - self.a;
+        let p = ray.direction.cross(&edge2);
+        let determinant = edge1.dot(&p);
+        let tolerance = T::default_epsilon() * edge1.norm() * edge2.norm() * ray.direction.norm();
+        if determinant.abs() <= tolerance {
+            return None;
+        }
+        let inverse = one() / determinant;
+        let offset = ray.origin - self.a;
+        let u = offset.dot(&p) * inverse;
+        if u < zero() || u > one() {
+            return None;
+        }
+        let q = offset.cross(&edge1);
+        let v = ray.direction.dot(&q) * inverse;
+        if v < zero() || u + v > one() {
+            return None;
+        }
+        let distance = edge2.dot(&q) * inverse;
+        if distance >= zero() {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Transformable<T> for Triangle<T>
+where
+    T: RealField,
+{
+    fn transformed(&self, matrix: &Matrix4<T>) -> Self {
+        Triangle {
+            a: matrix.transform_point(&self.a),
+            b: matrix.transform_point(&self.b),
+            c: matrix.transform_point(&self.c),
+        }
+    }
+}
+
+/// A view frustum, described by six planes facing inwards.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Frustum<T>
+where
+    T: RealField,
+{
+    /// The normalized planes: left, right, bottom, top, near and far
+    planes: [Plane<T>; 6],
+}
+
+impl<T> Frustum<T>
+where
+    T: RealField,
+{
+    /// Create a new `Frustum` from a view projection matrix, which maps world space to clip
+    /// space.
+    ///
+    /// The near plane is placed at a clip space depth of `-w`, so for projections with a depth
+    /// range from `0` to `w` the frustum extends slightly behind the near plane.
+    pub fn new(matrix: &Matrix4<T>) -> Self {
+        let plane = |row: Vector4<T>| Plane::new(row.xyz(), row.w).normalize();
+        let row = |index: usize| matrix.row(index).transpose();
+        Frustum {
+            planes: [
+                plane(row(3) + row(0)),
+                plane(row(3) - row(0)),
+                plane(row(3) + row(1)),
+                plane(row(3) - row(1)),
+                plane(row(3) + row(2)),
+                plane(row(3) - row(2)),
+            ],
+        }
+    }
+
+    /// The normalized planes of this `Frustum`, facing inwards
+    pub fn planes(&self) -> &[Plane<T>; 6] {
+        &self.planes
+    }
+
+    /// Returns `true` if the point is inside this `Frustum`.
+    pub fn contains_point(&self, point: &Point3<T>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.dot_point(point) >= zero())
+    }
+
+    /// Returns `true` if the `Sphere` is at least partially inside this `Frustum`.
+    ///
+    /// This is conservative: spheres close to a corner of the frustum can pass the check
+    /// without intersecting it.
+    pub fn intersects_sphere(&self, sphere: &Sphere<T>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.dot_point(&sphere.center) > -sphere.radius)
+    }
+
+    /// Returns `true` if the `Aabb` is at least partially inside this `Frustum`.
+    ///
+    /// This is conservative: boxes close to a corner of the frustum can pass the check without
+    /// intersecting it.
+    pub fn intersects_aabb(&self, aabb: &Aabb<T>) -> bool {
+        self.planes.iter().all(|plane| {
+            // The corner of the box furthest along the plane normal.
+            let corner = Point3::new(
+                if plane.normal.x >= zero() {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.normal.y >= zero() {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.normal.z >= zero() {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            plane.dot_point(&corner) >= zero()
+        })
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
     use approx::{assert_relative_eq, assert_ulps_eq};
 
     use super::*;
@@ -175,4 +911,174 @@ pub mod tests {
             Point3::new(1., -1.5, 45.5)
         )
     }
+
+    #[test]
+    fn ray_intersect_offset_plane() {
+        let ray = Ray {
+            origin: Point3::new(1.0, 2.0, 10.0),
+            direction: Vector3::new(0.0, 0.0, -1.0),
+        };
+        let distance = Plane::<f32>::with_z(5.0).intersect_ray(&ray).unwrap();
+        assert_relative_eq!(ray.at_distance(distance), Point3::new(1.0, 2.0, 5.0));
+    }
+
+    #[test]
+    fn ray_intersect_aabb() {
+        let aabb = Aabb::new(Point3::new(1.0, 1.0, 1.0), Point3::new(-1.0, -1.0, -1.0));
+        let ray = |origin: [f32; 3], direction: [f32; 3]| {
+            Ray {
+                origin: Point3::from(origin),
+                direction: Vector3::from(direction),
+            }
+        };
+
+        assert_relative_eq!(
+            aabb.intersect_ray(&ray([0.0, 0.0, 5.0], [0.0, 0.0, -1.0]))
+                .unwrap(),
+            4.0
+        );
+        assert_relative_eq!(
+            aabb.intersect_ray(&ray([0.5, 0.5, 0.0], [1.0, 0.0, 0.0]))
+                .unwrap(),
+            0.0
+        );
+        assert!(aabb
+            .intersect_ray(&ray([0.0, 0.0, 5.0], [0.0, 0.0, 1.0]))
+            .is_none());
+        assert!(aabb
+            .intersect_ray(&ray([2.0, 0.0, 5.0], [0.0, 0.0, -1.0]))
+            .is_none());
+    }
+
+    #[test]
+    fn ray_intersect_triangle_from_both_sides() {
+        let triangle = Triangle::new(
+            Point3::new(-1.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        );
+        let front = Ray {
+            origin: Point3::new(0.0, 0.0, 3.0),
+            direction: Vector3::new(0.0, 0.0, -1.0),
+        };
+        let back = Ray {
+            origin: Point3::new(0.0, 0.0, -2.0),
+            direction: Vector3::new(0.0, 0.0, 1.0),
+        };
+        let outside = Ray {
+            origin: Point3::new(1.0, 1.0, 3.0),
+            direction: Vector3::new(0.0, 0.0, -1.0),
+        };
+
+        assert_relative_eq!(triangle.intersect_ray(&front).unwrap(), 3.0);
+        assert_relative_eq!(triangle.intersect_ray(&back).unwrap(), 2.0);
+        assert!(triangle.intersect_ray(&outside).is_none());
+        assert_relative_eq!(triangle.normal().unwrap(), Vector3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn closest_points() {
+        let triangle = Triangle::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        );
+        assert_relative_eq!(
+            triangle.closest_point(&Point3::new(0.5, 0.5, 3.0)),
+            Point3::new(0.5, 0.5, 0.0)
+        );
+        assert_relative_eq!(
+            triangle.closest_point(&Point3::new(-1.0, -1.0, 0.0)),
+            Point3::new(0.0, 0.0, 0.0)
+        );
+        assert_relative_eq!(
+            triangle.closest_point(&Point3::new(2.0, 2.0, 0.0)),
+            Point3::new(1.0, 1.0, 0.0)
+        );
+
+        let aabb = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        assert_relative_eq!(
+            aabb.closest_point(&Point3::new(2.0, 0.5, -1.0)),
+            Point3::new(1.0, 0.5, 0.0)
+        );
+        assert_relative_eq!(aabb.distance_squared(&Point3::new(2.0, 0.5, -1.0)), 2.0);
+
+        let obb = Obb::new(
+            Point3::origin(),
+            Vector3::new(2.0, 1.0, 1.0),
+            UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2),
+        );
+        assert_relative_eq!(
+            obb.closest_point(&Point3::new(0.0, 5.0, 0.0)),
+            Point3::new(0.0, 2.0, 0.0),
+            epsilon = 1e-6
+        );
+        assert!(!obb.contains_point(&Point3::new(1.5, 0.0, 0.0)));
+
+        let capsule = Capsule::new(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 4.0, 0.0), 1.0);
+        assert_relative_eq!(
+            capsule.closest_point(&Point3::new(3.0, 2.0, 0.0)),
+            Point3::new(1.0, 2.0, 0.0)
+        );
+        assert!(capsule.contains_point(&Point3::new(0.0, 4.5, 0.0)));
+        assert!(!capsule.contains_point(&Point3::new(0.0, 5.5, 0.0)));
+    }
+
+    #[test]
+    fn sphere_intersect_aabb() {
+        let aabb = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        assert!(Sphere::new(Point3::new(2.0, 0.5, 0.5), 1.0).intersects_aabb(&aabb));
+        assert!(!Sphere::new(Point3::new(2.0, 2.0, 2.0), 1.0).intersects_aabb(&aabb));
+        assert!(
+            Capsule::new(Point3::new(0.0, 3.0, 0.0), Point3::new(0.0, 4.0, 0.0), 1.0)
+                .intersects_sphere(&Sphere::new(Point3::new(0.0, 1.5, 0.0), 0.5))
+        );
+    }
+
+    #[test]
+    fn frustum_intersect_aabb() {
+        let frustum = Frustum::new(&Matrix4::new_perspective(1.0, FRAC_PI_2, 0.1, 100.0));
+        let around = |x: f32, y: f32, z: f32| {
+            Aabb::from_center_half_extents(Point3::new(x, y, z), Vector3::repeat(0.5))
+        };
+
+        assert!(frustum.intersects_aabb(&around(0.0, 0.0, -5.0)));
+        assert!(frustum.intersects_aabb(&around(5.0, 0.0, -5.0)));
+        assert!(!frustum.intersects_aabb(&around(7.0, 0.0, -5.0)));
+        assert!(!frustum.intersects_aabb(&around(0.0, 0.0, 5.0)));
+        assert!(!frustum.intersects_aabb(&around(0.0, 0.0, -200.0)));
+        assert!(frustum.contains_point(&Point3::new(0.0, 0.0, -1.0)));
+        assert!(!frustum.intersects_sphere(&Sphere::new(Point3::new(0.0, 10.0, -5.0), 1.0)));
+    }
+
+    #[test]
+    fn transform_primitives() {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(10.0, 0.0, 0.0);
+        transform.set_rotation_z_axis(FRAC_PI_2);
+        transform.set_scale(Vector3::new(2.0, 1.0, 1.0));
+        transform.copy_local_to_global();
+
+        let aabb = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0))
+            .transformed_by(&transform);
+        assert_relative_eq!(aabb.min, Point3::new(9.0, 0.0, 0.0), epsilon = 1e-6);
+        assert_relative_eq!(aabb.max, Point3::new(10.0, 2.0, 1.0), epsilon = 1e-6);
+
+        let obb = Obb::from_aabb(&Aabb::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+        ))
+        .transformed_by(&transform);
+        assert_relative_eq!(
+            obb.half_extents,
+            Vector3::new(2.0, 1.0, 1.0),
+            epsilon = 1e-6
+        );
+        assert!(obb.contains_point(&Point3::new(10.0, 1.9, 0.0)));
+        assert!(!obb.contains_point(&Point3::new(11.5, 0.0, 0.0)));
+
+        let sphere = Sphere::new(Point3::new(1.0, 0.0, 0.0), 1.0).transformed_by(&transform);
+        assert_relative_eq!(sphere.center, Point3::new(10.0, 2.0, 0.0), epsilon = 1e-6);
+        assert_relative_eq!(sphere.radius, 2.0);
+    }
 }
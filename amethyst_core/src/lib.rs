@@ -32,12 +32,16 @@ pub use simba as simd;
 
 pub use self::{
     axis::{Axis2, Axis3},
+    clock::{Clock, ClockSource, Clocks, TimeControl},
     event::EventReader,
     hidden::{Hidden, HiddenPropagate},
     logger::{start_logger, LevelFilter as LogLevelFilter, Logger, LoggerConfig, StdoutLog},
     named::Named,
     shrev::EventChannel,
-    timing::Stopwatch,
+    system_order::{stage, SystemOrder},
+    system_timings::{FrameTimings, StepKind, StepTiming, SystemTiming, SystemTimings},
+    system_toggles::{short_type_name, SystemToggles},
+    timing::{FixedUpdateInterpolation, Stopwatch},
     transform::Transform,
 };
 
@@ -49,7 +53,10 @@ pub mod ecs {
         *,
     };
 
-    pub use crate::dispatcher::{Dispatcher, DispatcherBuilder, System, SystemBundle};
+    pub use crate::{
+        dispatcher::{Dispatcher, DispatcherBuilder, ExecutionMode, System, SystemBundle},
+        system_order::{stage, SystemOrder},
+    };
 }
 
 /// Dispatcher module.
@@ -71,8 +78,12 @@ pub mod hide_hierarchy_system;
 pub mod logger;
 
 mod axis;
+mod clock;
 mod event;
 mod hidden;
 mod named;
 pub mod system_ext;
+mod system_order;
+mod system_timings;
+mod system_toggles;
 mod timing;
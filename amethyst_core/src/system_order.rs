@@ -0,0 +1,317 @@
+//! Ordering constraints and stages of the systems of a dispatcher.
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap},
+};
+
+use amethyst_error::{format_err, Error};
+
+use crate::dispatcher::DispatcherItem;
+
+/// Names of the stages every dispatcher starts with, in execution order.
+///
+/// Systems are put into a stage with [SystemOrder::stage]. All systems of a stage run after the
+/// systems of earlier stages and before the systems of later stages. More stages can be added
+/// with `DispatcherBuilder::add_stage` and `DispatcherBuilder::add_stage_after`.
+pub mod stage {
+    /// Systems which read input devices and translate events.
+    pub const INPUT: &str = "input";
+    /// Systems which run the game logic.
+    pub const SIMULATION: &str = "simulation";
+    /// Systems which update the transform hierarchy.
+    pub const TRANSFORM: &str = "transform";
+    /// Systems which prepare the data needed for rendering.
+    pub const RENDER_PREP: &str = "render-prep";
+    /// Systems which render the frame.
+    pub const RENDER: &str = "render";
+
+    /// The default stages, in execution order.
+    pub const DEFAULT: &[&str] = &[INPUT, SIMULATION, TRANSFORM, RENDER_PREP, RENDER];
+}
+
+/// Ordering constraints of a system, used by the `add_*_ordered` methods of `DispatcherBuilder`.
+///
+/// Items without constraints keep the order they were added in. Systems without a stage run in
+/// the `simulation` stage. Constraints refer to other systems by their label, which defaults to
+/// the name of the system, or to bundles by their name. Systems are never moved across a flush of
+/// the command buffers within their stage.
+///
+/// ```
+/// use amethyst_core::{stage, SystemOrder};
+///
+/// let order = SystemOrder::new()
+///     .label("movement")
+///     .stage(stage::SIMULATION)
+///     .after("player_input")
+///     .before("collision");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SystemOrder {
+    pub(crate) label: Option<String>,
+    pub(crate) stage: Option<String>,
+    pub(crate) before: Vec<String>,
+    pub(crate) after: Vec<String>,
+}
+
+impl SystemOrder {
+    /// Creates an order without any constraints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the label other systems use to refer to this one. Several systems can share a label.
+    pub fn label<L: Into<String>>(mut self, label: L) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Puts the system into the given stage.
+    pub fn stage<S: Into<String>>(mut self, stage: S) -> Self {
+        self.stage = Some(stage.into());
+        self
+    }
+
+    /// Runs the system before all systems with the given label, or all systems added by the
+    /// bundle with the given name.
+    pub fn before<L: Into<String>>(mut self, label: L) -> Self {
+        self.before.push(label.into());
+        self
+    }
+
+    /// Runs the system after all systems with the given label, or all systems added by the
+    /// bundle with the given name.
+    pub fn after<L: Into<String>>(mut self, label: L) -> Self {
+        self.after.push(label.into());
+        self
+    }
+}
+
+/// A stage added to a dispatcher, optionally right after an existing stage.
+#[derive(Clone, Debug)]
+pub(crate) struct StageDecl {
+    pub(crate) name: String,
+    pub(crate) after: Option<String>,
+}
+
+/// A dispatcher item together with its ordering constraints.
+pub(crate) struct OrderedItem {
+    pub(crate) item: DispatcherItem,
+    pub(crate) order: SystemOrder,
+    /// Names of the bundles which added the item, outermost first.
+    pub(crate) bundles: Vec<String>,
+}
+
+impl OrderedItem {
+    /// Returns the label of the item, which defaults to the name of the system.
+    pub(crate) fn label(&self) -> Option<String> {
+        self.order.label.clone().or_else(|| {
+            match &self.item {
+                DispatcherItem::System(system) => system.name().map(ToString::to_string),
+                DispatcherItem::ThreadLocalSystem(system) => system.name().map(ToString::to_string),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// Builds the list of stages from the default stages and the added ones.
+pub(crate) fn resolve_stages(decls: &[StageDecl]) -> Result<Vec<String>, Error> {
+    let mut stages: Vec<String> = stage::DEFAULT.iter().map(ToString::to_string).collect();
+    for decl in decls {
+        if stages.contains(&decl.name) {
+            continue;
+        }
+        match &decl.after {
+            Some(after) => {
+                let position = stages.iter().position(|s| s == after).ok_or_else(|| {
+                    format_err!(
+                        "Stage `{}` is added after unknown stage `{}`",
+                        decl.name,
+                        after
+                    )
+                })?;
+                stages.insert(position + 1, decl.name.clone());
+            }
+            None => stages.push(decl.name.clone()),
+        }
+    }
+    Ok(stages)
+}
+
+/// Sorts the items so that all ordering constraints and stages are satisfied.
+///
+/// Items run stage by stage. Items without a stage belong to the `simulation` stage, and a flush
+/// of the command buffers belongs to the stage of the item added right before it. Flushes are
+/// barriers within their stage: items of the stage added before a flush run before it, and items
+/// added after it run after it.
+///
+/// The sort is stable: whenever several items of a stage could run next, the one added first is
+/// picked, so items without constraints keep their insertion order.
+///
+/// The `before` and `after` constraints refer to the labels of items, or to the names of bundles,
+/// in which case they refer to all items the bundle added. Constraints between items of different
+/// stages have to agree with the order of the stages.
+pub(crate) fn sort_items(
+    items: Vec<OrderedItem>,
+    stages: &[String],
+) -> Result<Vec<(OrderedItem, Option<String>)>, Error> {
+    let labels: Vec<Option<String>> = items.iter().map(OrderedItem::label).collect();
+    let is_flush: Vec<bool> = items
+        .iter()
+        .map(|ordered| matches!(ordered.item, DispatcherItem::FlushCmdBuffers))
+        .collect();
+    let describe = |index: usize| {
+        labels[index].clone().unwrap_or_else(|| {
+            if is_flush[index] {
+                format!("<flush #{}>", index)
+            } else {
+                format!("<unnamed #{}>", index)
+            }
+        })
+    };
+
+    let mut by_label: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, (label, ordered)) in labels.iter().zip(&items).enumerate() {
+        let bundles = ordered.bundles.iter().filter(|bundle| !bundle.is_empty());
+        for label in label.iter().chain(bundles) {
+            let indices = by_label.entry(label.as_str()).or_default();
+            if indices.last() != Some(&index) {
+                indices.push(index);
+            }
+        }
+    }
+
+    let default_stage = stages
+        .iter()
+        .position(|s| s == stage::SIMULATION)
+        .expect("The default stages are always present");
+    let mut stage_of: Vec<usize> = Vec::with_capacity(items.len());
+    for (index, ordered) in items.iter().enumerate() {
+        let stage = match &ordered.order.stage {
+            Some(stage) => {
+                stages.iter().position(|s| s == stage).ok_or_else(|| {
+                    format_err!(
+                        "System `{}` is in unknown stage `{}`",
+                        describe(index),
+                        stage
+                    )
+                })?
+            }
+            None if is_flush[index] => stage_of.last().copied().unwrap_or(default_stage),
+            None => default_stage,
+        };
+        stage_of.push(stage);
+    }
+
+    let mut successors = vec![Vec::new(); items.len()];
+    let mut predecessors = vec![0usize; items.len()];
+    let mut add_edge = |from: usize, to: usize| {
+        if from != to {
+            successors[from].push(to);
+            predecessors[to] += 1;
+        }
+    };
+
+    let resolve = |index: usize, relation: &str, target: &str| {
+        by_label.get(target).ok_or_else(|| {
+            format_err!(
+                "System `{}` is ordered {} unknown label `{}`",
+                describe(index),
+                relation,
+                target
+            )
+        })
+    };
+    let contradiction = |index: usize, relation: &str, other: usize| {
+        format_err!(
+            "System `{}` in stage `{}` is ordered {} `{}` in stage `{}`, which runs {}",
+            describe(index),
+            stages[stage_of[index]],
+            relation,
+            describe(other),
+            stages[stage_of[other]],
+            if relation == "before" {
+                "earlier"
+            } else {
+                "later"
+            }
+        )
+    };
+    for (index, ordered) in items.iter().enumerate() {
+        for target in &ordered.order.before {
+            for &other in resolve(index, "before", target)? {
+                match stage_of[index].cmp(&stage_of[other]) {
+                    Ordering::Less => {}
+                    Ordering::Equal => add_edge(index, other),
+                    Ordering::Greater => return Err(contradiction(index, "before", other)),
+                }
+            }
+        }
+        for target in &ordered.order.after {
+            for &other in resolve(index, "after", target)? {
+                match stage_of[other].cmp(&stage_of[index]) {
+                    Ordering::Less => {}
+                    Ordering::Equal => add_edge(other, index),
+                    Ordering::Greater => return Err(contradiction(index, "after", other)),
+                }
+            }
+        }
+    }
+
+    // Each item of a stage only depends on the last flush of the stage added before it, and each
+    // flush on the items and the flush of the stage added since the previous one.
+    let mut last_flush: Vec<Option<usize>> = vec![None; stages.len()];
+    let mut since_flush: Vec<Vec<usize>> = vec![Vec::new(); stages.len()];
+    for (index, &stage) in stage_of.iter().enumerate() {
+        if is_flush[index] {
+            for item in since_flush[stage].drain(..) {
+                add_edge(item, index);
+            }
+            if let Some(flush) = last_flush[stage].replace(index) {
+                add_edge(flush, index);
+            }
+        } else {
+            if let Some(flush) = last_flush[stage] {
+                add_edge(flush, index);
+            }
+            since_flush[stage].push(index);
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<(usize, usize)>> = (0..items.len())
+        .filter(|&index| predecessors[index] == 0)
+        .map(|index| Reverse((stage_of[index], index)))
+        .collect();
+    let mut sorted = Vec::with_capacity(items.len());
+    while let Some(Reverse((_, index))) = ready.pop() {
+        sorted.push(index);
+        for &next in &successors[index] {
+            predecessors[next] -= 1;
+            if predecessors[next] == 0 {
+                ready.push(Reverse((stage_of[next], next)));
+            }
+        }
+    }
+
+    if sorted.len() < items.len() {
+        let cycle: Vec<_> = (0..items.len())
+            .filter(|&index| predecessors[index] > 0)
+            .map(|index| format!("`{}`", describe(index)))
+            .collect();
+        return Err(format_err!(
+            "Cycle in the ordering constraints of systems {}",
+            cycle.join(", ")
+        ));
+    }
+
+    let mut items: Vec<_> = items
+        .into_iter()
+        .zip(labels.into_iter())
+        .map(Some)
+        .collect();
+    Ok(sorted
+        .into_iter()
+        .map(|index| items[index].take().unwrap())
+        .collect())
+}
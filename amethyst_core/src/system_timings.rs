@@ -0,0 +1,535 @@
+//! Per-system and per-step execution timings of the dispatchers.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::Write,
+    ops::RangeBounds,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use amethyst_error::Error;
+use serde::Serialize;
+
+use crate::ecs::{
+    storage::ComponentTypeId,
+    systems::{ResourceTypeId, Runnable, SystemId, UnsafeResources},
+    world::{ArchetypeAccess, WorldId},
+    CommandBuffer, World,
+};
+
+/// Number of frames kept by `SystemTimings::default()`.
+const DEFAULT_WINDOW: usize = 120;
+
+/// Kind of a step of a dispatcher schedule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum StepKind {
+    /// A group of systems executed in parallel.
+    Systems,
+    /// Flush of the command buffers of the systems.
+    FlushCmdBuffers,
+    /// A thread local system or function.
+    ThreadLocal,
+}
+
+/// Execution time of a single system in one frame.
+#[derive(Clone, Debug)]
+pub struct SystemTiming {
+    /// Name of the system.
+    pub name: Arc<str>,
+    /// When the system started running.
+    pub start: Instant,
+    /// How long the system ran.
+    pub duration: Duration,
+    /// Index of the thread the system ran on, stable for the lifetime of the process.
+    pub thread: usize,
+}
+
+/// Execution time of a single step of a dispatcher schedule in one frame.
+#[derive(Clone, Debug)]
+pub struct StepTiming {
+    /// What the step executed.
+    pub kind: StepKind,
+    /// When the step started.
+    pub start: Instant,
+    /// How long the step took.
+    pub duration: Duration,
+    /// Index of the thread the step was driven from.
+    pub thread: usize,
+}
+
+/// All timings recorded in one frame, across every dispatcher that ran in it.
+#[derive(Clone, Debug)]
+pub struct FrameTimings {
+    /// Frame number, as reported by `Time::frame_number`.
+    pub frame: u64,
+    /// Timings of the systems, in the order they finished.
+    pub systems: Vec<SystemTiming>,
+    /// Timings of the schedule steps, in execution order.
+    pub steps: Vec<StepTiming>,
+}
+
+impl FrameTimings {
+    /// Returns the total time spent in schedule steps in this frame.
+    pub fn total(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    /// Returns the total time the named system ran in this frame.
+    pub fn system_time(&self, name: &str) -> Duration {
+        self.systems
+            .iter()
+            .filter(|system| &*system.name == name)
+            .map(|system| system.duration)
+            .sum()
+    }
+}
+
+/// Resource holding the execution times of systems and schedule steps over a rolling window of
+/// frames.
+///
+/// Dispatchers only measure their systems while this resource exists, so insert it to start
+/// profiling and remove it to stop. Timings of every dispatcher that runs in a frame, such as the
+/// fixed update schedule and the dispatchers of states, are collected into the same frame.
+///
+/// # Examples
+///
+/// ```
+/// use amethyst_core::{ecs::Resources, SystemTimings};
+///
+/// let mut resources = Resources::default();
+/// resources.insert(SystemTimings::new(60));
+///
+/// // After a few frames:
+/// let timings = resources.get::<SystemTimings>().unwrap();
+/// for (name, time) in timings.system_breakdown() {
+///     println!("{}: {:?}", name, time);
+/// }
+/// ```
+pub struct SystemTimings {
+    window: usize,
+    epoch: Instant,
+    frames: VecDeque<FrameTimings>,
+}
+
+impl fmt::Debug for SystemTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SystemTimings")
+            .field("window", &self.window)
+            .field("frames", &self.frames.len())
+            .finish()
+    }
+}
+
+impl Default for SystemTimings {
+    fn default() -> Self {
+        SystemTimings::new(DEFAULT_WINDOW)
+    }
+}
+
+impl SystemTimings {
+    /// Creates a resource keeping the timings of the last `window` frames.
+    pub fn new(window: usize) -> Self {
+        SystemTimings {
+            window: window.max(1),
+            epoch: Instant::now(),
+            frames: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Returns the number of frames kept.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the recorded frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &FrameTimings> {
+        self.frames.iter()
+    }
+
+    /// Returns the timings of the given frame, if it is still in the window.
+    pub fn frame(&self, frame: u64) -> Option<&FrameTimings> {
+        self.frames.iter().find(|timings| timings.frame == frame)
+    }
+
+    /// Returns the timings of the most recent frame.
+    pub fn latest(&self) -> Option<&FrameTimings> {
+        self.frames.back()
+    }
+
+    /// Removes all recorded frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Returns the average time per frame the named system ran, over the frames in the window
+    /// it ran in.
+    pub fn average_system_time(&self, name: &str) -> Option<Duration> {
+        self.average(|frame| {
+            let mut systems = frame.systems.iter().filter(|system| &*system.name == name);
+            systems.next().map(|first| {
+                first.duration + systems.map(|system| system.duration).sum::<Duration>()
+            })
+        })
+    }
+
+    /// Returns the average time per frame spent in steps of the given kind.
+    pub fn average_step_time(&self, kind: StepKind) -> Option<Duration> {
+        self.average(|frame| {
+            let mut steps = frame.steps.iter().filter(|step| step.kind == kind);
+            steps
+                .next()
+                .map(|first| first.duration + steps.map(|step| step.duration).sum::<Duration>())
+        })
+    }
+
+    /// Returns the average time per frame spent in all schedule steps.
+    pub fn average_frame_time(&self) -> Option<Duration> {
+        self.average(|frame| Some(frame.total()))
+    }
+
+    /// Returns the average time per frame of every system, slowest first.
+    ///
+    /// This is the breakdown of where frame time goes, e.g. to show next to the `FpsCounter`.
+    pub fn system_breakdown(&self) -> Vec<(Arc<str>, Duration)> {
+        let mut totals: Vec<(Arc<str>, Duration)> = Vec::new();
+        for system in self.frames.iter().flat_map(|frame| frame.systems.iter()) {
+            match totals.iter_mut().find(|(name, _)| *name == system.name) {
+                Some((_, total)) => *total += system.duration,
+                None => totals.push((system.name.clone(), system.duration)),
+            }
+        }
+        let frames = self.frames.len().max(1) as u32;
+        let mut averages: Vec<_> = totals
+            .into_iter()
+            .map(|(name, total)| (name, total / frames))
+            .collect();
+        averages.sort_by(|a, b| b.1.cmp(&a.1));
+        averages
+    }
+
+    /// Averages a value over the frames it is present in.
+    fn average<F>(&self, value: F) -> Option<Duration>
+    where
+        F: Fn(&FrameTimings) -> Option<Duration>,
+    {
+        let (count, total) = self
+            .frames
+            .iter()
+            .filter_map(value)
+            .fold((0, Duration::default()), |(count, total), value| {
+                (count + 1, total + value)
+            });
+        if count == 0 {
+            None
+        } else {
+            Some(total / count)
+        }
+    }
+
+    /// Writes the frames in the given range in the [Chrome trace event format][format], which
+    /// can be opened with `chrome://tracing` or [Perfetto](https://ui.perfetto.dev).
+    ///
+    /// [format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+    pub fn write_chrome_trace<R, W>(&self, frames: R, writer: W) -> Result<(), Error>
+    where
+        R: RangeBounds<u64>,
+        W: Write,
+    {
+        let micros = |instant: Instant| {
+            instant.saturating_duration_since(self.epoch).as_nanos() as f64 / 1000.0
+        };
+        let mut events = Vec::new();
+        for frame in self.frames.iter().filter(|f| frames.contains(&f.frame)) {
+            let start = frame.steps.iter().map(|step| step.start).min();
+            let end = frame
+                .steps
+                .iter()
+                .map(|step| step.start + step.duration)
+                .max();
+            if let (Some(start), Some(end)) = (start, end) {
+                events.push(TraceEvent {
+                    name: format!("frame {}", frame.frame),
+                    cat: "frame",
+                    ph: "X",
+                    ts: micros(start),
+                    dur: micros(end) - micros(start),
+                    pid: 1,
+                    tid: frame.steps[0].thread,
+                });
+            }
+            events.extend(frame.steps.iter().map(|step| {
+                TraceEvent {
+                    name: format!("{:?}", step.kind),
+                    cat: "step",
+                    ph: "X",
+                    ts: micros(step.start),
+                    dur: step.duration.as_nanos() as f64 / 1000.0,
+                    pid: 1,
+                    tid: step.thread,
+                }
+            }));
+            events.extend(frame.systems.iter().map(|system| {
+                TraceEvent {
+                    name: system.name.to_string(),
+                    cat: "system",
+                    ph: "X",
+                    ts: micros(system.start),
+                    dur: system.duration.as_nanos() as f64 / 1000.0,
+                    pid: 1,
+                    tid: system.thread,
+                }
+            }));
+        }
+        serde_json::to_writer(
+            writer,
+            &ChromeTrace {
+                trace_events: events,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Adds the timings of one dispatcher execution to the given frame.
+    pub(crate) fn record(
+        &mut self,
+        frame: u64,
+        systems: Vec<SystemTiming>,
+        steps: Vec<StepTiming>,
+    ) {
+        match self.frames.back_mut() {
+            Some(timings) if timings.frame == frame => {
+                timings.systems.extend(systems);
+                timings.steps.extend(steps);
+            }
+            _ => {
+                if self.frames.len() == self.window {
+                    self.frames.pop_front();
+                }
+                self.frames.push_back(FrameTimings {
+                    frame,
+                    systems,
+                    steps,
+                });
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ChromeTrace {
+    #[serde(rename = "traceEvents")]
+    trace_events: Vec<TraceEvent>,
+}
+
+#[derive(Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: usize,
+}
+
+/// Returns a small index identifying the current thread.
+fn thread_index() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static INDEX: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    INDEX.with(|index| *index)
+}
+
+/// Collects the timings of one dispatcher while it executes.
+#[derive(Default)]
+pub(crate) struct TimingRecorder {
+    enabled: AtomicBool,
+    systems: Mutex<Vec<SystemTiming>>,
+    steps: Mutex<Vec<StepTiming>>,
+}
+
+impl TimingRecorder {
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the start of a step when timings are being recorded.
+    pub(crate) fn step_start(&self) -> Option<Instant> {
+        if self.is_enabled() {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    /// Records a step of the given kind which started at `start`.
+    pub(crate) fn record_step(&self, kind: StepKind, start: Option<Instant>) {
+        if let Some(start) = start {
+            self.steps.lock().unwrap().push(StepTiming {
+                kind,
+                start,
+                duration: start.elapsed(),
+                thread: thread_index(),
+            });
+        }
+    }
+
+    /// Takes the timings recorded since the last call.
+    pub(crate) fn drain(&self) -> (Vec<SystemTiming>, Vec<StepTiming>) {
+        (
+            std::mem::take(&mut *self.systems.lock().unwrap()),
+            std::mem::take(&mut *self.steps.lock().unwrap()),
+        )
+    }
+}
+
+/// Wraps a system to measure how long it runs.
+pub(crate) struct TimedRunnable<R: ?Sized> {
+    name: Arc<str>,
+    recorder: Arc<TimingRecorder>,
+    inner: Box<R>,
+}
+
+impl<R: Runnable + ?Sized> TimedRunnable<R> {
+    pub(crate) fn new(inner: Box<R>, recorder: Arc<TimingRecorder>) -> Self {
+        let name = inner
+            .name()
+            .map_or_else(|| "unnamed system".to_string(), ToString::to_string);
+        TimedRunnable {
+            name: name.into(),
+            recorder,
+            inner,
+        }
+    }
+}
+
+impl<R: Runnable + ?Sized> Runnable for TimedRunnable<R> {
+    fn name(&self) -> Option<&SystemId> {
+        self.inner.name()
+    }
+
+    fn reads(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        self.inner.reads()
+    }
+
+    fn writes(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        self.inner.writes()
+    }
+
+    fn prepare(&mut self, world: &World) {
+        self.inner.prepare(world)
+    }
+
+    fn accesses_archetypes(&self) -> &ArchetypeAccess {
+        self.inner.accesses_archetypes()
+    }
+
+    unsafe fn run_unsafe(&mut self, world: &World, resources: &UnsafeResources) {
+        if !self.recorder.is_enabled() {
+            return self.inner.run_unsafe(world, resources);
+        }
+        let start = Instant::now();
+        self.inner.run_unsafe(world, resources);
+        let duration = start.elapsed();
+        self.recorder.systems.lock().unwrap().push(SystemTiming {
+            name: self.name.clone(),
+            start,
+            duration,
+            thread: thread_index(),
+        });
+    }
+
+    fn command_buffer_mut(&mut self, world: WorldId) -> Option<&mut CommandBuffer> {
+        self.inner.command_buffer_mut(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(name: &str, start: Instant, millis: u64) -> SystemTiming {
+        SystemTiming {
+            name: name.into(),
+            start,
+            duration: Duration::from_millis(millis),
+            thread: 0,
+        }
+    }
+
+    fn step(start: Instant, millis: u64) -> StepTiming {
+        StepTiming {
+            kind: StepKind::Systems,
+            start,
+            duration: Duration::from_millis(millis),
+            thread: 0,
+        }
+    }
+
+    #[test]
+    fn keeps_a_rolling_window_of_frames() {
+        let mut timings = SystemTimings::new(2);
+        let now = Instant::now();
+        timings.record(1, vec![system("a", now, 4)], vec![step(now, 4)]);
+        timings.record(1, vec![system("b", now, 2)], vec![step(now, 2)]);
+        timings.record(2, vec![system("a", now, 2)], vec![step(now, 2)]);
+        timings.record(3, vec![system("a", now, 6)], vec![step(now, 6)]);
+
+        assert!(timings.frame(1).is_none());
+        assert_eq!(2, timings.frames().count());
+        assert_eq!(
+            Some(Duration::from_millis(4)),
+            timings.average_system_time("a")
+        );
+        assert_eq!(None, timings.average_system_time("b"));
+        assert_eq!(
+            Some(Duration::from_millis(4)),
+            timings.average_step_time(StepKind::Systems)
+        );
+    }
+
+    #[test]
+    fn breakdown_is_sorted_by_time() {
+        let mut timings = SystemTimings::default();
+        let now = Instant::now();
+        timings.record(
+            0,
+            vec![system("fast", now, 1), system("slow", now, 3)],
+            vec![step(now, 3)],
+        );
+
+        let breakdown = timings.system_breakdown();
+        assert_eq!("slow", &*breakdown[0].0);
+        assert_eq!("fast", &*breakdown[1].0);
+    }
+
+    #[test]
+    fn chrome_trace_contains_frames_in_range() {
+        let mut timings = SystemTimings::default();
+        let now = Instant::now();
+        timings.record(0, vec![system("zero", now, 1)], vec![step(now, 1)]);
+        timings.record(1, vec![system("one", now, 1)], vec![step(now, 1)]);
+
+        let mut trace = Vec::new();
+        timings.write_chrome_trace(1.., &mut trace).unwrap();
+        let trace: serde_json::Value = serde_json::from_slice(&trace).unwrap();
+        let names: Vec<_> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["name"].as_str().unwrap().to_string())
+            .collect();
+
+        assert_eq!(vec!["frame 1", "Systems", "one"], names);
+    }
+}
@@ -0,0 +1,149 @@
+//! Enabling and disabling systems and bundles of the dispatchers at runtime.
+
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::ecs::{
+    storage::ComponentTypeId,
+    systems::{ResourceTypeId, Runnable, SystemId, UnsafeResources},
+    world::{ArchetypeAccess, WorldId},
+    CommandBuffer, World,
+};
+
+/// Resource which disables systems and bundles of all dispatchers by name.
+///
+/// A name refers either to the label of a system, which defaults to the name of the system, or
+/// to a bundle, which is named after its type without the module path, e.g. `FlyControlBundle`.
+/// Disabling a bundle disables all systems it added, including those of nested bundles.
+///
+/// Disabled systems are skipped by the dispatcher until they are enabled again. Like with
+/// [pausable](crate::system_ext::pausable), systems reading from an `EventChannel` do not
+/// consume their events while they are disabled.
+///
+/// ```
+/// use amethyst_core::SystemToggles;
+///
+/// let mut toggles = SystemToggles::default();
+/// toggles.set_enabled("fly_movement", false);
+/// assert!(!toggles.is_enabled("fly_movement"));
+/// assert!(toggles.is_enabled("transform_system"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SystemToggles {
+    disabled: HashSet<String>,
+}
+
+impl SystemToggles {
+    /// Enables or disables the systems or bundles with the given name.
+    pub fn set_enabled<N: Into<String>>(&mut self, name: N, enabled: bool) {
+        let name = name.into();
+        if enabled {
+            self.disabled.remove(&name);
+        } else {
+            self.disabled.insert(name);
+        }
+    }
+
+    /// Returns `false` if the systems or bundles with the given name are disabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.contains(name)
+    }
+
+    /// Returns the names of all disabled systems and bundles.
+    pub fn disabled(&self) -> impl Iterator<Item = &str> {
+        self.disabled.iter().map(String::as_str)
+    }
+
+    /// Enables all systems and bundles.
+    pub fn enable_all(&mut self) {
+        self.disabled.clear();
+    }
+}
+
+/// Returns the name of a type without the module path and generic parameters, e.g. `Menu` for
+/// `my_game::states::Menu<T>`.
+///
+/// This is the name bundles are toggled by and the default name of states.
+pub fn short_type_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Returns the name of a bundle type, which is its [short_type_name].
+pub(crate) fn bundle_name<T: ?Sized>() -> String {
+    short_type_name::<T>().to_string()
+}
+
+/// Names a single item of a dispatcher can be toggled by, and whether it is enabled.
+pub(crate) struct Toggle {
+    /// The label of the item followed by the names of the bundles it was added by.
+    pub(crate) names: Vec<String>,
+    pub(crate) enabled: Arc<AtomicBool>,
+}
+
+impl Toggle {
+    pub(crate) fn new(names: Vec<String>) -> Self {
+        Toggle {
+            names,
+            enabled: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// Enables the item unless one of its names is disabled.
+    pub(crate) fn update(&self, disabled: &HashSet<String>, toggles: Option<&SystemToggles>) {
+        let enabled = self.names.iter().all(|name| {
+            !disabled.contains(name) && toggles.map_or(true, |toggles| toggles.is_enabled(name))
+        });
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+}
+
+/// Wraps a system which is skipped while it is disabled.
+pub(crate) struct ToggledRunnable<R: ?Sized> {
+    enabled: Arc<AtomicBool>,
+    inner: Box<R>,
+}
+
+impl<R: Runnable + ?Sized> ToggledRunnable<R> {
+    pub(crate) fn new(inner: Box<R>, enabled: Arc<AtomicBool>) -> Self {
+        ToggledRunnable { enabled, inner }
+    }
+}
+
+impl<R: Runnable + ?Sized> Runnable for ToggledRunnable<R> {
+    fn name(&self) -> Option<&SystemId> {
+        self.inner.name()
+    }
+
+    fn reads(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        self.inner.reads()
+    }
+
+    fn writes(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        self.inner.writes()
+    }
+
+    fn prepare(&mut self, world: &World) {
+        self.inner.prepare(world)
+    }
+
+    fn accesses_archetypes(&self) -> &ArchetypeAccess {
+        self.inner.accesses_archetypes()
+    }
+
+    unsafe fn run_unsafe(&mut self, world: &World, resources: &UnsafeResources) {
+        if self.enabled.load(Ordering::Relaxed) {
+            self.inner.run_unsafe(world, resources);
+        }
+    }
+
+    fn command_buffer_mut(&mut self, world: WorldId) -> Option<&mut CommandBuffer> {
+        self.inner.command_buffer_mut(world)
+    }
+}
@@ -1,4 +1,7 @@
-use std::time::{Duration, Instant};
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
 /// A stopwatch which accurately measures elapsed time.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Stopwatch {
@@ -67,13 +70,68 @@ impl Stopwatch {
     }
 }
 
+/// Tracks how far the current frame is between two fixed updates.
+///
+/// Rendering usually runs more often than the fixed update systems. Systems that want smooth
+/// motion can use [alpha](#method.alpha) to interpolate between the previous and the current
+/// simulation state. The application updates this resource once per frame, after running the
+/// fixed update steps.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FixedUpdateInterpolation {
+    accumulator: Duration,
+    alpha: f32,
+}
+
+impl FixedUpdateInterpolation {
+    /// Returns the fraction of a fixed step that has elapsed since the last fixed update,
+    /// in the range `[0.0, 1.0]`.
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Returns the time elapsed since the last fixed update.
+    pub fn time_since_fixed_update(&self) -> Duration {
+        self.accumulator
+    }
+
+    /// Returns how many fixed updates of `fixed_step` length are due after a frame of `delta`
+    /// length.
+    pub fn pending_steps(&self, delta: Duration, fixed_step: Duration) -> u32 {
+        if fixed_step == Duration::default() {
+            0
+        } else {
+            u32::try_from((self.accumulator + delta).as_nanos() / fixed_step.as_nanos())
+                .unwrap_or(u32::MAX)
+        }
+    }
+
+    /// Accounts for a frame of `delta` length, during which `steps` fixed updates of
+    /// `fixed_step` length were executed.
+    ///
+    /// If fewer steps were executed than were due, the remaining whole steps are dropped.
+    pub fn update(&mut self, delta: Duration, steps: u32, fixed_step: Duration) {
+        self.accumulator = (self.accumulator + delta)
+            .checked_sub(fixed_step * steps)
+            .unwrap_or_default();
+        if fixed_step != Duration::default() && self.accumulator >= fixed_step {
+            let remainder = self.accumulator.as_nanos() % fixed_step.as_nanos();
+            self.accumulator = Duration::from_nanos(remainder as u64);
+        }
+        self.alpha = if fixed_step == Duration::default() {
+            0.0
+        } else {
+            (self.accumulator.as_secs_f32() / fixed_step.as_secs_f32()).min(1.0)
+        };
+    }
+}
+
 // Unit tests
 #[cfg(test)]
 mod tests {
 
     use std::{thread, time::Duration};
 
-    use super::Stopwatch;
+    use super::{FixedUpdateInterpolation, Stopwatch};
 
     // Timing varies more on macOS CI
     fn get_uncertainty() -> u32 {
@@ -174,4 +232,31 @@ mod tests {
             elapsed
         );
     }
+
+    #[test]
+    fn fixed_update_interpolation() {
+        let fixed_step = Duration::from_millis(10);
+        let mut interpolation = FixedUpdateInterpolation::default();
+
+        interpolation.update(Duration::from_millis(25), 2, fixed_step);
+        assert_eq!(
+            interpolation.time_since_fixed_update(),
+            Duration::from_millis(5)
+        );
+        assert!((interpolation.alpha() - 0.5).abs() < 1e-6);
+
+        interpolation.update(Duration::from_millis(5), 1, fixed_step);
+        assert_eq!(interpolation.time_since_fixed_update(), Duration::default());
+        assert_eq!(interpolation.alpha(), 0.0);
+
+        assert_eq!(
+            interpolation.pending_steps(Duration::from_millis(1005), fixed_step),
+            100
+        );
+        interpolation.update(Duration::from_millis(1005), 10, fixed_step);
+        assert_eq!(
+            interpolation.time_since_fixed_update(),
+            Duration::from_millis(5)
+        );
+    }
 }
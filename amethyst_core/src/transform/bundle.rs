@@ -5,6 +5,8 @@ use amethyst_error::Error;
 use crate::{ecs::*, transform::*};
 
 /// Transform bundle
+///
+/// Its systems run in the `transform` stage.
 #[derive(Default)]
 #[allow(missing_debug_implementations)]
 pub struct TransformBundle;
@@ -16,10 +18,11 @@ impl SystemBundle for TransformBundle {
         _resources: &mut Resources,
         builder: &mut DispatcherBuilder,
     ) -> Result<(), Error> {
+        let order = SystemOrder::new().stage(stage::TRANSFORM);
         builder
-            .add_system(MissingPreviousParentSystem)
-            .add_system(ParentUpdateSystem)
-            .add_system(TransformSystem);
+            .add_system_ordered(MissingPreviousParentSystem, order.clone())
+            .add_system_ordered(ParentUpdateSystem, order.clone())
+            .add_system_ordered(TransformSystem, order);
 
         Ok(())
     }
@@ -10,9 +10,17 @@ use crate::ecs::*;
 #[uuid = "4f1cb751-d650-4cf0-8418-63cff1e85c84"]
 pub struct Parent(#[serde_diff(opaque)] pub Entity);
 
+/// Creates a parent link to a new entity ID, which does not exist in any world.
+///
+/// Only meant as placeholder until the actual parent is set, e.g. by prefabs which add a default
+/// component before applying its serialized value.
 impl Default for Parent {
     fn default() -> Self {
-        unimplemented!()
+        Parent(
+            world::Allocate::new()
+                .next()
+                .expect("Entity IDs are exhausted"),
+        )
     }
 }
 
@@ -3,8 +3,10 @@ use std::{iter::repeat, ops::Range};
 use amethyst_assets::error::Error;
 use amethyst_core::math::{zero, Vector3};
 use amethyst_rendy::{
+    picking::MeshTriangles,
     rendy::mesh::{Color, MeshBuilder, Normal, Position, Tangent, TexCoord},
     skinning::JointCombined,
+    types::MeshData,
 };
 use gltf::buffer::Data;
 use log::{debug, trace, warn};
@@ -16,7 +18,7 @@ pub fn load_mesh(
     mesh: &gltf::Mesh<'_>,
     buffers: &Vec<Data>,
     options: &GltfSceneOptions,
-) -> Result<Vec<(String, MeshBuilder<'static>, Option<usize>, Range<[f32; 3]>)>, Error> {
+) -> Result<Vec<(String, MeshData, Option<usize>, Range<[f32; 3]>)>, Error> {
     debug!("Loading mesh");
     let mut primitives = vec![];
     for primitive in mesh.primitives() {
@@ -108,6 +110,13 @@ pub fn load_mesh(
             }
         });
 
+        let triangles = if options.load_triangles && primitive.mode() == gltf::mesh::Mode::Triangles
+        {
+            Some(indices.triangles(&positions))
+        } else {
+            None
+        };
+
         let mut builder = MeshBuilder::new();
 
         match indices {
@@ -132,9 +141,13 @@ pub fn load_mesh(
         let bounds = bounds.min..bounds.max;
         let material = primitive.material().index();
 
+        let data = MeshData::from(builder);
         primitives.push((
             mesh.name().expect("Meshes must have a name").to_string(),
-            builder,
+            match triangles {
+                Some(triangles) => data.with_triangles(triangles),
+                None => data,
+            },
             material,
             bounds,
         ));
@@ -181,6 +194,25 @@ impl Indices {
             Indices::U32(vec) => vec[face * 3 + vert] as usize,
         }
     }
+
+    /// Collects the triangles of the primitive for picking.
+    fn triangles(&self, positions: &[Position]) -> MeshTriangles {
+        let positions = positions.iter().map(|p| p.0).collect();
+        match self {
+            Indices::None => MeshTriangles::from_triangle_list(positions),
+            Indices::U16(vec) => {
+                let count = vec.len() / 3 * 3;
+                MeshTriangles::new(
+                    positions,
+                    vec[..count].iter().map(|&i| u32::from(i)).collect(),
+                )
+            }
+            Indices::U32(vec) => {
+                let count = vec.len() / 3 * 3;
+                MeshTriangles::new(positions, vec[..count].to_vec())
+            }
+        }
+    }
 }
 
 fn calculate_normals(positions: &[Position], indices: &Indices) -> Vec<Normal> {
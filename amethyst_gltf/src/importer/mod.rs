@@ -16,7 +16,7 @@ use amethyst_core::{
     math::{convert, Quaternion, Unit, Vector3, Vector4},
     transform::Transform,
 };
-use amethyst_rendy::{light::Light, types::MeshData, Camera, Material};
+use amethyst_rendy::{light::Light, Camera, Material};
 use gltf::{buffer::Data, Document, Node};
 use log::debug;
 use serde::{Deserialize, Serialize};
@@ -70,7 +70,8 @@ pub struct GltfImporter;
 
 impl Importer for GltfImporter {
     fn version_static() -> u32 {
-        1
+        // 2: `MeshData` carries optional triangles.
+        2
     }
 
     fn version(&self) -> u32 {
@@ -270,14 +271,13 @@ fn load_node(
                         .entry(format!("{}_{}", name, 0))
                         .or_insert_with(|| op.new_asset_uuid());
 
-                    let mesh_data: MeshData = mesh.into();
                     imported_assets.push(ImportedAsset {
                         id: mesh_asset_id,
                         search_tags: vec![],
                         build_deps: vec![],
                         load_deps: vec![],
                         build_pipeline: None,
-                        asset_data: Box::new(mesh_data),
+                        asset_data: Box::new(mesh),
                     });
 
                     world
@@ -322,14 +322,13 @@ fn load_node(
                         .entry(format!("{}_{}", name, primitive_index))
                         .or_insert_with(|| op.new_asset_uuid());
 
-                    let mesh_data: MeshData = mesh.into();
                     imported_assets.push(ImportedAsset {
                         id: mesh_asset_id,
                         search_tags: vec![],
                         build_deps: vec![],
                         load_deps: vec![],
                         build_pipeline: None,
-                        asset_data: Box::new(mesh_data),
+                        asset_data: Box::new(mesh),
                     });
 
                     let current_primitive_entity = world.push((
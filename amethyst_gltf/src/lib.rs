@@ -44,6 +44,14 @@ inventory::submit! {
     }
 }
 
+// Binary glTF files are self-contained, so they can also be loaded from a `Source`.
+inventory::submit! {
+    amethyst_assets::SourceImporter {
+        extension: "glb",
+        import: amethyst_assets::import_source_with::<GltfImporter>,
+    }
+}
+
 register_component_type!(Joint);
 register_asset_type!(Animation<Transform> => Animation<Transform>; AssetProcessorSystem<Animation<Transform>>);
 
@@ -72,6 +80,9 @@ pub struct GltfSceneOptions {
     pub load_animations: bool,
     /// Flip the v coordinate for all texture coordinates
     pub flip_v_coord: bool,
+    /// Attach a copy of the triangles of every mesh, so it can be picked precisely if
+    /// `PickingMeshes` is enabled
+    pub load_triangles: bool,
     /// Load the given scene index, if not supplied will either load the default scene (if set),
     /// or the first scene (only if there is only one scene, otherwise an `Error` will be returned).
     pub scene_index: Option<usize>,
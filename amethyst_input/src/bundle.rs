@@ -18,6 +18,7 @@ use crate::{BindingError, Bindings, InputHandler, InputSystem};
 ///
 /// This also adds the Winit EventHandler and the `InputEvent` EventHandler
 /// where `T::Action` is the type for Actions you have assigned here.
+/// Its systems run in the `input` stage.
 ///
 /// ## Type parameters
 ///
@@ -95,7 +96,7 @@ impl SystemBundle for InputBundle {
         #[cfg(feature = "sdl_controller")]
         {
             use super::SdlEventsSystem;
-            builder.add_thread_local(
+            builder.add_thread_local_ordered(
                 // TODO: improve errors when migrating to failure
                 Box::new(
                     SdlEventsSystem::new(
@@ -105,12 +106,16 @@ impl SystemBundle for InputBundle {
                     )
                     .unwrap(),
                 ),
+                SystemOrder::new().stage(stage::INPUT),
             );
         }
 
         resources.insert(handler);
 
-        builder.add_system(InputSystem { reader });
+        builder.add_system_ordered(
+            InputSystem { reader },
+            SystemOrder::new().stage(stage::INPUT),
+        );
 
         Ok(())
     }
@@ -9,7 +9,9 @@ use rendy::init::Rendy;
 
 use crate::{
     camera::ActiveCamera,
+    lod::MeshBounds,
     mtl::{Material, MaterialDefaults},
+    picking::PickingMeshes,
     rendy::{
         command::QueueId,
         factory::Factory,
@@ -20,6 +22,7 @@ use crate::{
         hal,
         wsi::Surface,
     },
+    spatial::SpatialIndex,
     system::{
         create_default_mat, make_graph_aux_data, render, GraphAuxData, GraphCreator, RenderState,
     },
@@ -35,6 +38,8 @@ use crate::{
 /// If you need much more control, or you need to deal directly with the render pipeline,
 /// it's possible to define a `RenderGraphCreator` as show by the
 /// `renderable_custom` example.
+///
+/// The frame is rendered in the `render` stage, after the systems of all earlier stages.
 #[derive(Debug)]
 pub struct RenderingBundle<B: Backend> {
     plugins: Vec<Box<dyn RenderPlugin<B>>>,
@@ -73,6 +78,19 @@ impl<B: Backend> SystemBundle for RenderingBundle<B> {
         builder: &mut DispatcherBuilder,
     ) -> Result<(), Error> {
         resources.insert(ActiveCamera::default());
+        if !resources.contains::<PickingMeshes>() {
+            resources.insert(PickingMeshes::default());
+        }
+        if !resources.contains::<MeshBounds>() {
+            resources.insert(MeshBounds::default());
+        }
+        resources.get_or_insert_with(SpatialIndex::default);
+        if let Some(mut storage) = resources.get_mut::<AssetStorage<Mesh>>() {
+            storage.report_sizes();
+        }
+        if let Some(mut storage) = resources.get_mut::<AssetStorage<Texture>>() {
+            storage.report_sizes();
+        }
 
         for plugin in &mut self.plugins {
             plugin.on_build(world, resources, builder)?;
@@ -100,7 +118,10 @@ impl<B: Backend> SystemBundle for RenderingBundle<B> {
             },
         });
 
-        builder.add_thread_local_fn(render::<B, PluggableRenderGraphCreator<B>>);
+        builder.add_thread_local_fn_ordered(
+            render::<B, PluggableRenderGraphCreator<B>>,
+            SystemOrder::new().label("render").stage(stage::RENDER),
+        );
 
         Ok(())
     }
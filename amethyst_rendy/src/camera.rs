@@ -1,11 +1,7 @@
 //! Camera type with support for perspective and orthographic projections.
 
 use amethyst_assets::{
-    prefab::{
-        register_component_type,
-        serde_diff::{ApplyContext, DiffContext},
-        SerdeDiff,
-    },
+    prefab::{register_component_type, SerdeDiff},
     Asset,
 };
 use amethyst_core::{
@@ -14,7 +10,6 @@ use amethyst_core::{
     math::{Matrix4, Point2, Point3, Vector2},
     transform::Transform,
 };
-use serde::{de, de::SeqAccess, ser::SerializeSeq};
 use type_uuid::TypeUuid;
 
 /// Camera struct.
@@ -40,36 +35,17 @@ use type_uuid::TypeUuid;
 ///
 /// If you change `matrix` you must also change `inverse` so that they stay in sync.
 /// You should probably use from_matrix instead.
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize, TypeUuid, Default)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize, SerdeDiff, TypeUuid, Default)]
 #[uuid = "56946ce2-356e-4038-82ad-e55a69ddfde9"]
 pub struct Camera {
     /// The projection matrix
+    #[serde_diff(opaque)]
     pub matrix: Matrix4<f32>,
     /// Its inverse
+    #[serde_diff(opaque)]
     pub inverse: Matrix4<f32>,
 }
 
-impl SerdeDiff for Camera {
-    fn diff<'a, S: SerializeSeq>(
-        &self,
-        ctx: &mut DiffContext<'a, S>,
-        other: &Self,
-    ) -> Result<bool, <S as SerializeSeq>::Error> {
-        unimplemented!()
-    }
-
-    fn apply<'de, A>(
-        &mut self,
-        seq: &mut A,
-        ctx: &mut ApplyContext,
-    ) -> Result<bool, <A as SeqAccess<'de>>::Error>
-    where
-        A: de::SeqAccess<'de>,
-    {
-        unimplemented!()
-    }
-}
-
 register_component_type!(Camera);
 
 impl Asset for Camera {
@@ -288,6 +264,7 @@ mod tests {
     //! Our view space is +Y Down, +X Right, +Z Away
     //! Current render target is +Y Down, +X Right, +Z Away
 
+    use amethyst_assets::prefab::serde_diff::{Apply, Diff};
     use amethyst_core::{
         math::{convert, Isometry3, Matrix4, Point3, Translation3, UnitQuaternion, Vector3},
         transform::Transform,
@@ -425,6 +402,21 @@ mod tests {
         assert_eq!(test_persp, de);
     }
 
+    #[test]
+    fn test_camera_serde_diff() {
+        let ortho = Camera::standard_2d(1024.0, 768.0);
+        let persp = Camera::standard_3d(1024.0, 768.0);
+        let diff =
+            to_string_pretty(&Diff::serializable(&ortho, &persp), Default::default()).unwrap();
+
+        let mut camera = ortho.clone();
+        let mut de = ron::de::Deserializer::from_str(&diff).unwrap();
+        Apply::apply(&mut de, &mut camera).unwrap();
+
+        assert_eq!(camera, persp);
+        assert_eq!(camera.inverse, persp.inverse);
+    }
+
     // Our world-space is +Y Up, +X Right and -Z Away
     // Current render target is +Y Down, +X Right and +Z Away
     fn setup() -> (Transform, [Point3<f32>; 3], [Point3<f32>; 3]) {
@@ -4,7 +4,7 @@ use amethyst_error::Error;
 use serde::{Deserialize, Serialize};
 use type_uuid::TypeUuid;
 
-use crate::types::MeshData;
+use crate::{picking::MeshTriangles, types::MeshData};
 
 /// 'Obj' mesh format `Format` implementation.
 #[derive(
@@ -21,8 +21,13 @@ use crate::types::MeshData;
     Deserialize,
     TypeUuid,
 )]
+#[serde(default)]
 #[uuid = "7994868a-3ca1-4498-a6e5-4849598a6b22"]
-pub struct ObjFormat;
+pub struct ObjFormat {
+    /// Attach a copy of the triangles of the mesh, so it can be picked precisely if
+    /// `PickingMeshes` is enabled.
+    pub load_triangles: bool,
+}
 
 amethyst_assets::register_importer!(".obj", ObjFormat);
 impl Format<MeshData> for ObjFormat {
@@ -38,8 +43,90 @@ impl Format<MeshData> for ObjFormat {
                 if iter.next().is_some() {
                     log::warn!("OBJ file contains more than one object, only loading the first");
                 }
-                builder.0.into()
+                let data = MeshData::from(builder.0);
+                let triangles = if self.load_triangles {
+                    obj_triangles(&bytes)
+                } else {
+                    None
+                };
+                match triangles {
+                    Some(triangles) => data.with_triangles(triangles),
+                    None => data,
+                }
             })
             .map_err(|e| e.into())
     }
 }
+
+/// Reads the triangles of the first object of an OBJ file, for picking.
+///
+/// Polygons are triangulated as fans. Returns `None` if the file is not valid UTF-8 or a face
+/// refers to a missing vertex.
+fn obj_triangles(bytes: &[u8]) -> Option<MeshTriangles> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    let mut objects = 0;
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("o") => {
+                objects += 1;
+                if objects > 1 && !indices.is_empty() {
+                    break;
+                }
+            }
+            Some("v") => {
+                let mut position = [0.0; 3];
+                for coordinate in &mut position {
+                    *coordinate = tokens.next()?.parse().ok()?;
+                }
+                positions.push(position);
+            }
+            Some("f") => {
+                let face = tokens
+                    .map(|token| {
+                        let index: i64 = token.split('/').next()?.parse().ok()?;
+                        let index = if index < 0 {
+                            positions.len() as i64 + index
+                        } else {
+                            index - 1
+                        };
+                        if index >= 0 && (index as usize) < positions.len() {
+                            Some(index as u32)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                for i in 1..face.len().saturating_sub(1) {
+                    indices.extend_from_slice(&[face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(MeshTriangles::new(positions, indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obj_triangles_triangulate_first_object() {
+        let obj = b"o quad
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+f 1/1/1 2/2/1 3/3/1 -1/4/1
+o other
+v 0 0 5
+f 5 1 2
+";
+        let triangles = obj_triangles(obj).unwrap();
+        assert_eq!(&[0, 1, 2, 0, 2, 3], triangles.indices());
+        assert!(obj_triangles(b"v 0 0 0\nf 1 2 3\n").is_none());
+    }
+}
@@ -20,7 +20,9 @@
 //! ## Systems
 //!
 //! * [`RenderingSystem`](crate::system::RenderingSystem)
+//! * [`SpatialIndexSystem`](crate::spatial::SpatialIndexSystem)
 //! * [`VisibilitySortingSystem`](crate::visibility::VisibilitySortingSystem)
+//! * [`MeshLodSystem`](crate::lod::MeshLodSystem)
 //! * [`SpriteVisibilitySortingSystem`](crate::sprite_visibility::SpriteVisibilitySortingSystem)
 //!
 //! ## Components
@@ -28,6 +30,10 @@
 //! * [`Camera`](camera::Camera)
 //! * [`SpriteVisibility`](sprite_visibility::SpriteVisibility)
 //! * [`Visibility`](visibility::Visibility)
+//! * [`SpatialIndex`](spatial::SpatialIndex)
+//! * [`PickingMeshes`](picking::PickingMeshes)
+//! * [`MeshLod`](lod::MeshLod)
+//! * [`MeshBounds`](lod::MeshBounds)
 //! * [`BoundingSphere`](visibility::BoundingSphere)
 //! * [`DebugLinesComponent`](debug_drawing::DebugLinesComponent)
 //! * [`Light`](light::Light)
@@ -63,13 +69,16 @@ pub mod debug_drawing;
 pub mod error;
 pub mod formats;
 pub mod light;
+pub mod lod;
 pub mod mtl;
+pub mod picking;
 pub mod pipeline;
 pub mod plugins;
 pub mod resources;
 pub mod serde_shim;
 pub mod shape;
 pub mod skinning;
+pub mod spatial;
 pub mod sprite;
 pub mod sprite_visibility;
 pub mod submodules;
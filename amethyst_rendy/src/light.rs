@@ -35,13 +35,19 @@ impl Default for Light {
     }
 }
 
+/// Lights are diffed as a whole, since changing a field of one kind of light can not be applied
+/// to another kind of light.
 impl SerdeDiff for Light {
     fn diff<'a, S: SerializeSeq>(
         &self,
         ctx: &mut DiffContext<'a, S>,
         other: &Self,
     ) -> Result<bool, <S as SerializeSeq>::Error> {
-        unimplemented!()
+        if self == other {
+            return Ok(false);
+        }
+        ctx.save_value(other)?;
+        Ok(true)
     }
 
     fn apply<'de, A>(
@@ -52,7 +58,7 @@ impl SerdeDiff for Light {
     where
         A: de::SeqAccess<'de>,
     {
-        unimplemented!()
+        ctx.read_value(seq, self)
     }
 }
 
@@ -265,3 +271,27 @@ impl From<khr_lights_punctual::Light<'_>> for Light {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use amethyst_assets::prefab::serde_diff::{Apply, Diff};
+
+    use super::*;
+
+    #[test]
+    fn light_diff_replaces_the_light() {
+        let point: Light = PointLight::default().into();
+        let sun: Light = SunLight {
+            intensity: 4.0,
+            ..SunLight::default()
+        }
+        .into();
+        let diff = ron::ser::to_string(&Diff::serializable(&point, &sun)).unwrap();
+
+        let mut light = point.clone();
+        let mut de = ron::de::Deserializer::from_str(&diff).unwrap();
+        Apply::apply(&mut de, &mut light).unwrap();
+
+        assert_eq!(light, sun);
+    }
+}
@@ -0,0 +1,326 @@
+//! Level of detail selection for meshes, based on how much of the screen they cover.
+//!
+//! The [MeshLodSystem] picks one of the meshes of a [MeshLod] for the active camera every frame,
+//! using the screen coverage `VisibilitySortingSystem` computes from the projected
+//! `BoundingSphere` of the entity, and writes it to the `Handle<Mesh>` of the entity.
+use std::collections::{HashMap, HashSet};
+
+use amethyst_assets::{AssetHandle, AssetStorage, Handle, LoadHandle};
+use amethyst_core::ecs::{systems::ParallelRunnable, *};
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use crate::{
+    types::Mesh,
+    visibility::{BoundingSphere, Visibility},
+};
+
+/// Default of [MeshLod::hysteresis].
+const DEFAULT_HYSTERESIS: f32 = 0.1;
+
+/// A mesh of a [MeshLod] and the screen coverage below which it is used.
+#[derive(Debug, Clone)]
+pub struct LodLevel {
+    /// The mesh rendered at this level
+    pub mesh: Handle<Mesh>,
+    /// The largest screen coverage this level is used at, as a fraction of the screen height
+    pub max_coverage: f32,
+}
+
+/// Component selecting the mesh of an entity from several levels of detail.
+///
+/// Screen coverage is the fraction of the screen height covered by the projected bounding sphere
+/// of the entity. The first level is used at any coverage, and each further level once the
+/// coverage drops below its [max_coverage](LodLevel::max_coverage). Below
+/// [cull_below](MeshLod::with_cull_below) the entity isn't rendered at all.
+///
+/// To avoid popping when the coverage stays close to a threshold, a level is only left once the
+/// coverage passes the threshold by the [hysteresis](MeshLod::with_hysteresis) fraction.
+///
+/// Entities without a `BoundingSphere` get the bounds of the mesh of the first level from
+/// [MeshBounds] as soon as it is loaded.
+///
+/// ```ignore
+/// let lod = MeshLod::new(detailed)
+///     .with_level(medium, 0.25)
+///     .with_level(coarse, 0.1)
+///     .with_cull_below(0.01);
+/// world.push((transform, lod, material));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MeshLod {
+    levels: Vec<LodLevel>,
+    hysteresis: f32,
+    cull_below: Option<f32>,
+    /// The camera the selection was made for and the selected level, which is
+    /// `levels.len()` while the entity is culled.
+    selection: Option<(Entity, usize)>,
+}
+
+impl MeshLod {
+    /// Creates a component with the mesh used when the entity covers a large part of the screen.
+    pub fn new(mesh: Handle<Mesh>) -> Self {
+        MeshLod {
+            levels: vec![LodLevel {
+                mesh,
+                max_coverage: f32::INFINITY,
+            }],
+            hysteresis: DEFAULT_HYSTERESIS,
+            cull_below: None,
+            selection: None,
+        }
+    }
+
+    /// Adds a mesh used when the screen coverage is below `max_coverage`.
+    pub fn with_level(mut self, mesh: Handle<Mesh>, max_coverage: f32) -> Self {
+        let index = self
+            .levels
+            .iter()
+            .position(|level| level.max_coverage < max_coverage)
+            .unwrap_or_else(|| self.levels.len());
+        self.levels.insert(index, LodLevel { mesh, max_coverage });
+        self.selection = None;
+        self
+    }
+
+    /// Sets the fraction by which the coverage has to pass a threshold before the level
+    /// changes. Defaults to `0.1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hysteresis` is not in the range `0.0..1.0`.
+    pub fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+        assert!(
+            (0.0..1.0).contains(&hysteresis),
+            "Invalid hysteresis: {}",
+            hysteresis
+        );
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Stops rendering the entity when its screen coverage is below `coverage`.
+    pub fn with_cull_below(mut self, coverage: f32) -> Self {
+        self.cull_below = Some(coverage);
+        self
+    }
+
+    /// Returns the levels, from the most to the least detailed.
+    pub fn levels(&self) -> &[LodLevel] {
+        &self.levels
+    }
+
+    /// Returns the hysteresis fraction.
+    pub fn hysteresis(&self) -> f32 {
+        self.hysteresis
+    }
+
+    /// Returns the coverage below which the entity is culled.
+    pub fn cull_below(&self) -> Option<f32> {
+        self.cull_below
+    }
+
+    /// Returns the index of the selected level, or `None` if no level was selected yet or the
+    /// entity is culled.
+    pub fn active_level(&self) -> Option<usize> {
+        self.selection
+            .map(|(_, level)| level)
+            .filter(|&level| level < self.levels.len())
+    }
+
+    /// Returns `true` if the entity was culled for being too small.
+    pub fn is_culled(&self) -> bool {
+        self.selection
+            .map_or(false, |(_, level)| level == self.levels.len())
+    }
+
+    /// Selects the level for the given screen coverage seen from `camera`, returning `None`
+    /// if the entity is culled.
+    ///
+    /// Hysteresis is applied relative to the previous selection for the same camera.
+    pub(crate) fn select(&mut self, camera: Entity, coverage: f32) -> Option<usize> {
+        let current = self
+            .selection
+            .filter(|(selected_camera, _)| *selected_camera == camera)
+            .map(|(_, level)| level);
+        // Threshold `k` separates level `k - 1` from the coarser level `k`, the last one
+        // separates the least detailed level from culling.
+        let thresholds = self.levels[1..]
+            .iter()
+            .map(|level| level.max_coverage)
+            .chain(self.cull_below);
+        let mut selected = 0;
+        for (index, threshold) in thresholds.enumerate() {
+            let coarser = index + 1;
+            let threshold = match current {
+                Some(current) if current >= coarser => threshold * (1.0 + self.hysteresis),
+                Some(_) => threshold * (1.0 - self.hysteresis),
+                None => threshold,
+            };
+            if coverage >= threshold {
+                break;
+            }
+            selected = coarser;
+        }
+        self.selection = Some((camera, selected));
+        self.active_level()
+    }
+}
+
+/// Bounding spheres of processed meshes, in the local space of the mesh.
+///
+/// The `MeshProcessorSystem` computes the bounds of meshes whose `MeshData` carries triangles,
+/// which is the case for meshes created from a `Shape` and imported from OBJ and glTF files.
+#[derive(Debug, Default)]
+pub struct MeshBounds {
+    spheres: HashMap<LoadHandle, BoundingSphere>,
+}
+
+impl MeshBounds {
+    /// Returns the bounding sphere of the mesh behind the handle.
+    pub fn get(
+        &self,
+        meshes: &AssetStorage<Mesh>,
+        handle: &Handle<Mesh>,
+    ) -> Option<&BoundingSphere> {
+        self.spheres.get(&meshes.resolve(handle.load_handle())?)
+    }
+
+    /// Stores the bounding sphere of a processed mesh.
+    pub(crate) fn insert(&mut self, load_handle: LoadHandle, sphere: BoundingSphere) {
+        self.spheres.insert(load_handle, sphere);
+    }
+
+    /// Drops the bounding spheres of meshes which were freed.
+    pub(crate) fn retain_stored(&mut self, meshes: &AssetStorage<Mesh>) {
+        self.spheres
+            .retain(|load_handle, _| meshes.holds(*load_handle));
+    }
+}
+
+/// Selects the level of detail of every visible entity with a [MeshLod], writing its mesh to
+/// the `Handle<Mesh>` of the entity and removing culled entities from the [Visibility].
+///
+/// This has to run after `VisibilitySortingSystem` and before rendering.
+#[derive(Debug, Default)]
+pub struct MeshLodSystem;
+
+impl System for MeshLodSystem {
+    fn build(self) -> Box<dyn ParallelRunnable> {
+        let mut culled: HashSet<Entity> = HashSet::new();
+        Box::new(
+            SystemBuilder::new("MeshLodSystem")
+                .read_resource::<AssetStorage<Mesh>>()
+                .read_resource::<MeshBounds>()
+                .write_resource::<Visibility>()
+                .with_query(<(Entity, &MeshLod)>::query().filter(!component::<BoundingSphere>()))
+                .with_query(<(Entity, &mut MeshLod, Option<&mut Handle<Mesh>>)>::query())
+                .build(
+                    move |commands,
+                          world,
+                          (meshes, bounds, visibility),
+                          (unbounded_query, lod_query)| {
+                        #[cfg(feature = "profiler")]
+                        profile_scope!("mesh_lod_system");
+
+                        for (entity, lod) in unbounded_query.iter(world) {
+                            if let Some(sphere) = bounds.get(meshes, &lod.levels()[0].mesh) {
+                                commands.add_component(*entity, sphere.clone());
+                            }
+                        }
+
+                        let camera = match visibility.camera {
+                            Some(camera) => camera,
+                            None => return,
+                        };
+                        culled.clear();
+                        for (entity, lod, mesh) in lod_query.iter_mut(world) {
+                            // Entities outside of the view keep their level until they are seen.
+                            let coverage = match visibility.screen_coverage.get(entity) {
+                                Some(coverage) => *coverage,
+                                None => continue,
+                            };
+                            let selected = match lod.select(camera, coverage) {
+                                Some(level) => &lod.levels()[level].mesh,
+                                None => {
+                                    culled.insert(*entity);
+                                    continue;
+                                }
+                            };
+                            match mesh {
+                                Some(mesh) if mesh.load_handle() == selected.load_handle() => {}
+                                Some(mesh) => *mesh = selected.clone(),
+                                None => commands.add_component(*entity, selected.clone()),
+                            }
+                        }
+
+                        if !culled.is_empty() {
+                            visibility
+                                .visible_unordered
+                                .retain(|entity| !culled.contains(entity));
+                            visibility
+                                .visible_ordered
+                                .retain(|entity| !culled.contains(entity));
+                        }
+                    },
+                ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_assets::detached_handle;
+
+    use super::*;
+
+    fn lod() -> MeshLod {
+        MeshLod::new(detached_handle())
+            .with_level(detached_handle(), 0.1)
+            .with_level(detached_handle(), 0.5)
+            .with_cull_below(0.02)
+    }
+
+    #[test]
+    fn levels_are_sorted_by_coverage() {
+        let lod = lod();
+        let coverages: Vec<_> = lod.levels().iter().map(|l| l.max_coverage).collect();
+        assert_eq!(vec![f32::INFINITY, 0.5, 0.1], coverages);
+    }
+
+    #[test]
+    fn selects_level_by_coverage_and_culls_small_entities() {
+        let mut world = World::default();
+        let camera = world.push(());
+        let other_camera = world.push(());
+
+        assert_eq!(Some(0), lod().select(camera, 2.0));
+        assert_eq!(Some(1), lod().select(camera, 0.3));
+        assert_eq!(Some(2), lod().select(camera, 0.05));
+        let mut lod = lod();
+        assert_eq!(None, lod.select(camera, 0.01));
+        assert!(lod.is_culled());
+        assert_eq!(None, lod.active_level());
+        assert_eq!(Some(2), lod.select(other_camera, 0.03));
+    }
+
+    #[test]
+    fn hysteresis_delays_level_changes() {
+        let mut world = World::default();
+        let camera = world.push(());
+        let mut lod = lod().with_hysteresis(0.2);
+
+        assert_eq!(Some(1), lod.select(camera, 0.45));
+        // Becoming more detailed requires passing 0.5 by 20%.
+        assert_eq!(Some(1), lod.select(camera, 0.55));
+        assert_eq!(Some(0), lod.select(camera, 0.65));
+        // Becoming less detailed requires dropping 20% below 0.5.
+        assert_eq!(Some(0), lod.select(camera, 0.45));
+        assert_eq!(Some(1), lod.select(camera, 0.35));
+        assert_eq!(Some(2), lod.select(camera, 0.07));
+        assert_eq!(Some(2), lod.select(camera, 0.018));
+        assert_eq!(None, lod.select(camera, 0.015));
+        assert_eq!(None, lod.select(camera, 0.022));
+        assert_eq!(Some(2), lod.select(camera, 0.025));
+    }
+}
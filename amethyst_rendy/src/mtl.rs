@@ -1,12 +1,8 @@
 //! Physically-based material.
 
 use amethyst_assets::{
-    erased_serde::private::serde::{de, de::SeqAccess, ser::SerializeSeq},
-    prefab::{
-        register_component_type,
-        serde_diff::{ApplyContext, DiffContext},
-        SerdeDiff,
-    },
+    detached_handle,
+    prefab::{register_component_type, SerdeDiff},
     Asset, Handle,
 };
 use serde::{Deserialize, Serialize};
@@ -33,24 +29,43 @@ impl Default for TextureOffset {
 }
 
 /// A physically based Material with metallic workflow, fully utilized in PBR render pass.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TypeUuid)]
+///
+/// In prefabs and other serialized materials, textures are referenced by the UUID or the path of
+/// the texture asset. Textures which are left out, i.e. which are [detached], are serialized as
+/// `None` and replaced with the textures of [MaterialDefaults] by the renderer.
+///
+/// [detached]: amethyst_assets::detached_handle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SerdeDiff, TypeUuid)]
 #[uuid = "e238c036-42e9-4d0e-9aa9-c6511c906820"]
 pub struct Material {
     /// Alpha cutoff: the value at which we do not draw the pixel
     pub alpha_cutoff: f32,
     /// Diffuse map.
+    #[serde_diff(opaque)]
+    #[serde(with = "amethyst_assets::serde_detached")]
     pub albedo: Handle<Texture>,
     /// Emission map.
+    #[serde_diff(opaque)]
+    #[serde(with = "amethyst_assets::serde_detached")]
     pub emission: Handle<Texture>,
     /// Normal map.
+    #[serde_diff(opaque)]
+    #[serde(with = "amethyst_assets::serde_detached")]
     pub normal: Handle<Texture>,
     /// Metallic-roughness map. (B channel metallic, G channel roughness)
+    #[serde_diff(opaque)]
+    #[serde(with = "amethyst_assets::serde_detached")]
     pub metallic_roughness: Handle<Texture>,
     /// Ambient occlusion map.
+    #[serde_diff(opaque)]
+    #[serde(with = "amethyst_assets::serde_detached")]
     pub ambient_occlusion: Handle<Texture>,
     /// Cavity map.
+    #[serde_diff(opaque)]
+    #[serde(with = "amethyst_assets::serde_detached")]
     pub cavity: Handle<Texture>,
     /// Texture offset
+    #[serde_diff(opaque)]
     pub uv_offset: TextureOffset,
 }
 
@@ -61,30 +76,20 @@ impl Asset for Material {
     type Data = Self;
 }
 
+/// Creates a material without textures, which are all taken from [MaterialDefaults] when the
+/// material is rendered.
 impl Default for Material {
     fn default() -> Self {
-        unimplemented!()
-    }
-}
-
-impl SerdeDiff for Material {
-    fn diff<'a, S: SerializeSeq>(
-        &self,
-        ctx: &mut DiffContext<'a, S>,
-        other: &Self,
-    ) -> Result<bool, <S as SerializeSeq>::Error> {
-        unimplemented!()
-    }
-
-    fn apply<'de, A>(
-        &mut self,
-        seq: &mut A,
-        ctx: &mut ApplyContext,
-    ) -> Result<bool, <A as SeqAccess<'de>>::Error>
-    where
-        A: de::SeqAccess<'de>,
-    {
-        unimplemented!()
+        Material {
+            alpha_cutoff: 0.01,
+            albedo: detached_handle(),
+            emission: detached_handle(),
+            normal: detached_handle(),
+            metallic_roughness: detached_handle(),
+            ambient_occlusion: detached_handle(),
+            cavity: detached_handle(),
+            uv_offset: TextureOffset::default(),
+        }
     }
 }
 
@@ -183,3 +188,115 @@ impl_texture_set_tuple!(A, B, C);
 impl_texture_set_tuple!(A, B, C, D);
 impl_texture_set_tuple!(A, B, C, D, E);
 impl_texture_set_tuple!(A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use amethyst_assets::{
+        is_detached,
+        prefab::serde_diff::{Apply, Diff},
+        AssetHandle, LoadHandle,
+    };
+    use distill::loader::{
+        crossbeam_channel::{unbounded, Sender},
+        handle::{RefOp, SerdeContext},
+        storage::LoaderInfoProvider,
+        AssetRef, AssetUuid,
+    };
+
+    use super::*;
+
+    const TEXTURE: LoadHandle = LoadHandle(7);
+    const TEXTURE_UUID: AssetUuid = AssetUuid([7; 16]);
+    const TEXTURE_PATH: &str = "textures/albedo.png";
+
+    /// Resolves a single loaded texture by UUID or path, like the loader does while prefabs are
+    /// imported and saved. Nothing else resolves, so detached handles must never be looked up.
+    struct TextureInfo;
+
+    impl LoaderInfoProvider for TextureInfo {
+        fn get_load_handle(&self, id: &AssetRef) -> Option<LoadHandle> {
+            match id {
+                AssetRef::Uuid(uuid) if *uuid == TEXTURE_UUID => Some(TEXTURE),
+                AssetRef::Path(path) if path == Path::new(TEXTURE_PATH) => Some(TEXTURE),
+                _ => None,
+            }
+        }
+
+        fn get_asset_id(&self, load: LoadHandle) -> Option<AssetUuid> {
+            if load == TEXTURE {
+                Some(TEXTURE_UUID)
+            } else {
+                None
+            }
+        }
+    }
+
+    fn with_texture_info<R>(sender: &Sender<RefOp>, f: impl FnOnce() -> R) -> R {
+        futures_executor::block_on(SerdeContext::with(&TextureInfo, sender.clone(), async {
+            f()
+        }))
+    }
+
+    #[test]
+    fn default_material_has_detached_textures() {
+        let material = Material::default();
+        assert!(FullTextureSet::textures(&material).all(is_detached));
+    }
+
+    #[test]
+    fn material_textures_are_serialized_by_reference() {
+        let (sender, _receiver) = unbounded();
+        let material = Material {
+            albedo: Handle::new(sender.clone(), TEXTURE),
+            ..Material::default()
+        };
+
+        let serialized = with_texture_info(&sender, || ron::ser::to_string(&material).unwrap());
+        assert!(serialized.contains("emission:None"));
+        let loaded: Material =
+            with_texture_info(&sender, || ron::de::from_str(&serialized).unwrap());
+        assert_eq!(loaded.albedo.load_handle(), TEXTURE);
+        assert!(is_detached(&loaded.emission));
+        assert_eq!(loaded, material);
+
+        let by_path: Handle<Texture> = with_texture_info(&sender, || {
+            ron::de::from_str(&format!("{:?}", TEXTURE_PATH)).unwrap()
+        });
+        assert_eq!(by_path.load_handle(), TEXTURE);
+    }
+
+    #[test]
+    fn default_material_round_trips_without_loader_lookups() {
+        let (sender, _receiver) = unbounded();
+        let material = Material::default();
+
+        let loaded: Material = with_texture_info(&sender, || {
+            let serialized = ron::ser::to_string(&material).unwrap();
+            ron::de::from_str(&serialized).unwrap()
+        });
+        assert!(FullTextureSet::textures(&loaded).all(is_detached));
+        assert_eq!(loaded, material);
+    }
+
+    #[test]
+    fn material_diff_patches_changed_fields() {
+        let material = Material::default();
+        let cutout = Material {
+            alpha_cutoff: 0.5,
+            uv_offset: TextureOffset {
+                u: (0.0, 0.5),
+                v: (0.5, 1.0),
+            },
+            ..material.clone()
+        };
+        let diff = ron::ser::to_string(&Diff::serializable(&material, &cutout)).unwrap();
+
+        let mut patched = material.clone();
+        let mut de = ron::de::Deserializer::from_str(&diff).unwrap();
+        Apply::apply(&mut de, &mut patched).unwrap();
+
+        assert_eq!(patched, cutout);
+    }
+}
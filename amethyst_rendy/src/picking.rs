@@ -0,0 +1,545 @@
+//! Picking of rendered entities with rays, e.g. to find the entity under the mouse cursor.
+//!
+//! Meshes are found through the bounding spheres of the [SpatialIndex], and can optionally be
+//! tested against their triangles, which the [PickingMeshes] resource keeps a copy of. Sprites
+//! are tested against the quad they are drawn with.
+use std::{cmp::Ordering, collections::HashMap};
+
+use amethyst_assets::{AssetHandle, AssetStorage, Handle, LoadHandle};
+use amethyst_core::{
+    ecs::*,
+    geometry::{Aabb, Ray, Triangle},
+    math::{Point2, Point3, Vector2, Vector3},
+    transform::Transform,
+    Hidden, HiddenPropagate,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::{ActiveCamera, Camera},
+    spatial::SpatialIndex,
+    sprite::{Sprite, SpriteRender, SpriteSheet, Sprites},
+    types::Mesh,
+    visibility::BoundingSphere,
+};
+
+/// A copy of the triangles of a mesh, kept on the CPU for ray picking.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeshTriangles {
+    positions: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+}
+
+impl MeshTriangles {
+    /// Creates the triangles from vertex positions and three indices per triangle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of indices is not a multiple of three.
+    pub fn new(positions: Vec<[f32; 3]>, indices: Vec<u32>) -> Self {
+        assert_eq!(
+            indices.len() % 3,
+            0,
+            "The number of indices of a triangle mesh must be a multiple of three"
+        );
+        MeshTriangles { positions, indices }
+    }
+
+    /// Creates the triangles from a list of positions where every three positions are a
+    /// triangle. Trailing positions which don't make up a triangle are ignored.
+    pub fn from_triangle_list(positions: Vec<[f32; 3]>) -> Self {
+        let count = positions.len() / 3 * 3;
+        MeshTriangles {
+            indices: (0..count as u32).collect(),
+            positions,
+        }
+    }
+
+    /// Returns the vertex positions.
+    pub fn positions(&self) -> &[[f32; 3]] {
+        &self.positions
+    }
+
+    /// Returns the indices, three per triangle.
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// Returns the number of triangles.
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Returns a sphere around all vertex positions, centered on their bounding box, or `None`
+    /// if there are no positions.
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        let points: Vec<_> = self.positions.iter().map(|p| Point3::from(*p)).collect();
+        let center = Aabb::from_points(&points)?.center();
+        let radius = points
+            .iter()
+            .map(|p| (p - center).norm())
+            .fold(0.0, f32::max);
+        Some(BoundingSphere::new(center, radius))
+    }
+
+    /// Returns the distance along the ray to the closest triangle it hits, from either side.
+    ///
+    /// The direction of the ray doesn't need to be normalized, the distance is measured in
+    /// multiples of it. Triangles with indices out of bounds are skipped.
+    pub fn cast_ray(&self, ray: &Ray<f32>) -> Option<f32> {
+        let position = |index: &u32| {
+            self.positions
+                .get(*index as usize)
+                .map(|p| Point3::from(*p))
+        };
+        self.indices
+            .chunks_exact(3)
+            .filter_map(|triangle| {
+                let a = position(&triangle[0])?;
+                let b = position(&triangle[1])?;
+                let c = position(&triangle[2])?;
+                Triangle::new(a, b, c).intersect_ray(ray)
+            })
+            .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    }
+}
+
+/// CPU copies of the triangles of loaded meshes, used by the [Picker] to test meshes precisely.
+///
+/// The `MeshProcessorSystem` only keeps the triangles of meshes while this is enabled, so enable
+/// it before the meshes which should be picked are loaded. Triangles are available for meshes
+/// created with `Shape::upload_with_triangles`, and for meshes imported from OBJ and glTF files
+/// with the `load_triangles` option of `ObjFormat` or `GltfSceneOptions`.
+#[derive(Debug, Default)]
+pub struct PickingMeshes {
+    enabled: bool,
+    meshes: HashMap<LoadHandle, MeshTriangles>,
+}
+
+impl PickingMeshes {
+    /// Returns `true` if the triangles of newly processed meshes are kept.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables keeping the triangles of newly processed meshes. Disabling drops the
+    /// triangles kept so far.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.meshes.clear();
+        }
+    }
+
+    /// Returns the triangles of the mesh behind the handle.
+    pub fn get(
+        &self,
+        meshes: &AssetStorage<Mesh>,
+        handle: &Handle<Mesh>,
+    ) -> Option<&MeshTriangles> {
+        self.meshes.get(&meshes.resolve(handle.load_handle())?)
+    }
+
+    /// Keeps the triangles of a processed mesh, if enabled.
+    pub(crate) fn insert(&mut self, load_handle: LoadHandle, triangles: MeshTriangles) {
+        if self.enabled {
+            self.meshes.insert(load_handle, triangles);
+        }
+    }
+
+    /// Drops the triangles of meshes which were freed.
+    pub(crate) fn retain_stored(&mut self, meshes: &AssetStorage<Mesh>) {
+        self.meshes
+            .retain(|load_handle, _| meshes.holds(*load_handle));
+    }
+}
+
+/// An entity hit by a ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PickHit {
+    /// The entity which was hit.
+    pub entity: Entity,
+    /// The distance along the ray to the hit point.
+    pub distance: f32,
+    /// The point where the ray hit the entity, in world space.
+    pub point: Point3<f32>,
+}
+
+/// Casts rays against rendered entities.
+///
+/// Entities with `Hidden` or `HiddenPropagate` are skipped. Meshes are entities with a
+/// `Handle<Mesh>` in the [SpatialIndex], which are hit where the ray enters their bounding sphere,
+/// or where it hits their closest triangle if [with_triangles](Picker::with_triangles) is used and
+/// the triangles of the mesh are known. Sprites are entities with a `SpriteRender`, hit where the
+/// ray crosses the quad they are drawn with.
+///
+/// When used from a system, it needs read access to the `Transform`, `Handle<Mesh>`,
+/// `SpriteRender`, `Hidden` and `HiddenPropagate` components, and to the resources passed to
+/// the picker.
+///
+/// ```ignore
+/// let ray = picking::screen_ray(world, &active_camera, mouse, screen_diagonal)?;
+/// let hits = Picker::new()
+///     .with_meshes(&spatial_index)
+///     .with_triangles(&picking_meshes, &mesh_storage)
+///     .pick(world, &ray);
+/// let closest = hits.first().map(|hit| hit.entity);
+/// ```
+#[allow(missing_debug_implementations)]
+#[derive(Clone, Copy)]
+pub struct Picker<'a> {
+    index: Option<&'a SpatialIndex>,
+    triangles: Option<(&'a PickingMeshes, &'a AssetStorage<Mesh>)>,
+    sprites: Option<(&'a AssetStorage<SpriteSheet>, &'a AssetStorage<Sprites>)>,
+    max_distance: f32,
+}
+
+impl<'a> Default for Picker<'a> {
+    fn default() -> Self {
+        Picker {
+            index: None,
+            triangles: None,
+            sprites: None,
+            max_distance: f32::INFINITY,
+        }
+    }
+}
+
+impl<'a> Picker<'a> {
+    /// Creates a picker which doesn't pick anything until meshes or sprites are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Picks meshes through their bounding spheres in the index.
+    pub fn with_meshes(mut self, index: &'a SpatialIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Tests the triangles of picked meshes, for those meshes whose triangles are known.
+    pub fn with_triangles(
+        mut self,
+        triangles: &'a PickingMeshes,
+        meshes: &'a AssetStorage<Mesh>,
+    ) -> Self {
+        self.triangles = Some((triangles, meshes));
+        self
+    }
+
+    /// Picks sprites.
+    pub fn with_sprites(
+        mut self,
+        sheets: &'a AssetStorage<SpriteSheet>,
+        sprites: &'a AssetStorage<Sprites>,
+    ) -> Self {
+        self.sprites = Some((sheets, sprites));
+        self
+    }
+
+    /// Ignores hits further along the ray than `max_distance`.
+    pub fn with_max_distance(mut self, max_distance: f32) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Returns all entities hit by the ray, sorted from near to far.
+    ///
+    /// The direction of the ray must be normalized, like the rays of `Camera::screen_ray`.
+    pub fn pick<W: EntityStore>(&self, world: &W, ray: &Ray<f32>) -> Vec<PickHit> {
+        let mut hits = Vec::new();
+        if let Some(index) = self.index {
+            self.pick_meshes(world, index, ray, &mut hits);
+        }
+        if let Some((sheets, sprites)) = self.sprites {
+            self.pick_sprites(world, sheets, sprites, ray, &mut hits);
+        }
+        hits.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap_or(Ordering::Equal)
+        });
+        hits
+    }
+
+    fn pick_meshes<W: EntityStore>(
+        &self,
+        world: &W,
+        index: &SpatialIndex,
+        ray: &Ray<f32>,
+        hits: &mut Vec<PickHit>,
+    ) {
+        for (entity, sphere_distance) in index.cast_ray(ray, self.max_distance) {
+            // The index can still contain entities deleted this frame.
+            let entry = match world.entry_ref(entity) {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            if is_hidden(&entry) {
+                continue;
+            }
+            let mesh = match entry.get_component::<Handle<Mesh>>() {
+                Ok(mesh) => mesh,
+                Err(_) => continue,
+            };
+
+            let triangles = self.triangles.and_then(|(triangles, meshes)| {
+                let transform = entry.get_component::<Transform>().ok()?;
+                Some((triangles.get(meshes, mesh)?, transform))
+            });
+            let distance = match triangles {
+                Some((triangles, transform)) => {
+                    match cast_ray_local(ray, transform, |local| triangles.cast_ray(local)) {
+                        Some(distance) if distance <= self.max_distance => distance,
+                        _ => continue,
+                    }
+                }
+                None => sphere_distance,
+            };
+            hits.push(PickHit {
+                entity,
+                distance,
+                point: ray.at_distance(distance),
+            });
+        }
+    }
+
+    fn pick_sprites<W: EntityStore>(
+        &self,
+        world: &W,
+        sheets: &AssetStorage<SpriteSheet>,
+        sprites: &AssetStorage<Sprites>,
+        ray: &Ray<f32>,
+        hits: &mut Vec<PickHit>,
+    ) {
+        let mut built: HashMap<LoadHandle, Vec<Sprite>> = HashMap::new();
+        let mut query = <(Entity, &SpriteRender, &Transform)>::query()
+            .filter(!component::<Hidden>() & !component::<HiddenPropagate>());
+        for (entity, sprite_render, transform) in query.iter(world) {
+            let sprites_handle = match sheets.get(&sprite_render.sprite_sheet) {
+                Some(sheet) => &sheet.sprites,
+                None => continue,
+            };
+            let key = sprites_handle.load_handle();
+            if !built.contains_key(&key) {
+                match sprites.get(sprites_handle) {
+                    Some(sheet_sprites) => {
+                        built.insert(key, sheet_sprites.build_sprites());
+                    }
+                    None => continue,
+                }
+            }
+            let sprite = match built[&key].get(sprite_render.sprite_number) {
+                Some(sprite) => sprite,
+                None => continue,
+            };
+            if let Some(distance) = cast_ray_sprite(ray, sprite, transform) {
+                if distance <= self.max_distance {
+                    hits.push(PickHit {
+                        entity: *entity,
+                        distance,
+                        point: ray.at_distance(distance),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn is_hidden(entry: &EntryRef<'_>) -> bool {
+    entry.get_component::<Hidden>().is_ok() || entry.get_component::<HiddenPropagate>().is_ok()
+}
+
+/// Casts the ray in the local space of the transform. The local direction is not normalized, so
+/// distances along it are the same as along the world space ray.
+fn cast_ray_local<F>(ray: &Ray<f32>, transform: &Transform, cast: F) -> Option<f32>
+where
+    F: FnOnce(&Ray<f32>) -> Option<f32>,
+{
+    let inverse = transform.global_matrix().try_inverse()?;
+    cast(&Ray {
+        origin: inverse.transform_point(&ray.origin),
+        direction: inverse.transform_vector(&ray.direction),
+    })
+}
+
+/// Returns the distance along the ray to the point where it crosses the quad of the sprite, as
+/// drawn by the flat 2D passes.
+fn cast_ray_sprite(ray: &Ray<f32>, sprite: &Sprite, transform: &Transform) -> Option<f32> {
+    let matrix = transform.global_matrix();
+    let center = matrix.transform_point(&Point3::new(-sprite.offsets[0], -sprite.offsets[1], 0.0));
+    let axis_x = matrix.transform_vector(&Vector3::x()) * sprite.width;
+    let axis_y = matrix.transform_vector(&Vector3::y()) * sprite.height;
+
+    let normal = axis_x.cross(&axis_y);
+    let facing = ray.direction.dot(&normal);
+    if facing.abs() < 1e-12 {
+        return None;
+    }
+    let distance = (center - ray.origin).dot(&normal) / facing;
+    if distance < 0.0 {
+        return None;
+    }
+
+    // Express the hit point in the axes of the quad, which spans -0.5 to 0.5 along both.
+    let offset = ray.at_distance(distance) - center;
+    let (xx, xy, yy) = (
+        axis_x.dot(&axis_x),
+        axis_x.dot(&axis_y),
+        axis_y.dot(&axis_y),
+    );
+    let (ox, oy) = (offset.dot(&axis_x), offset.dot(&axis_y));
+    let determinant = xx * yy - xy * xy;
+    let u = (ox * yy - oy * xy) / determinant;
+    let v = (oy * xx - ox * xy) / determinant;
+    if u.abs() <= 0.5 && v.abs() <= 0.5 {
+        Some(distance)
+    } else {
+        None
+    }
+}
+
+/// Returns the ray through a screen position of the active camera, or of the first camera if
+/// there is no active camera.
+///
+/// The screen coordinate (0, 0) is the top-left corner of the top-left pixel.
+/// `screen_diagonal` is the bottom-right corner of the bottom-right pixel.
+pub fn screen_ray<W: EntityStore>(
+    world: &W,
+    active_camera: &ActiveCamera,
+    screen_position: Point2<f32>,
+    screen_diagonal: Vector2<f32>,
+) -> Option<Ray<f32>> {
+    let mut query = <(Entity, &Camera, &Transform)>::query();
+    let mut cameras = query.iter(world);
+    let (_, camera, transform) = match active_camera.entity {
+        Some(active) => cameras.find(|(entity, _, _)| **entity == active)?,
+        None => cameras.next()?,
+    };
+    Some(camera.screen_ray(screen_position, screen_diagonal, transform))
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_assets::detached_handle;
+    use amethyst_core::math::UnitQuaternion;
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::sprite::TextureCoordinates;
+
+    fn ray(origin: [f32; 3], direction: [f32; 3]) -> Ray<f32> {
+        Ray {
+            origin: Point3::from(origin),
+            direction: Vector3::from(direction).normalize(),
+        }
+    }
+
+    #[test]
+    fn triangles_return_closest_hit_from_both_sides() {
+        let triangles = MeshTriangles::from_triangle_list(vec![
+            [-1.0, -1.0, 0.0],
+            [1.0, -1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [-1.0, -1.0, -2.0],
+            [0.0, 1.0, -2.0],
+            [1.0, -1.0, -2.0],
+        ]);
+        assert_eq!(2, triangles.triangle_count());
+
+        let hit = triangles.cast_ray(&ray([0.0, 0.0, 5.0], [0.0, 0.0, -1.0]));
+        assert_relative_eq!(5.0, hit.unwrap());
+        let hit = triangles.cast_ray(&ray([0.0, 0.0, -5.0], [0.0, 0.0, 1.0]));
+        assert_relative_eq!(3.0, hit.unwrap());
+        assert_eq!(
+            None,
+            triangles.cast_ray(&ray([0.9, 0.9, 5.0], [0.0, 0.0, -1.0]))
+        );
+        assert_eq!(
+            None,
+            triangles.cast_ray(&ray([0.0, 0.0, 5.0], [0.0, 0.0, 1.0]))
+        );
+    }
+
+    #[test]
+    fn local_cast_keeps_world_distances() {
+        let triangles = MeshTriangles::new(
+            vec![[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [0.0, 1.0, 0.0]],
+            vec![0, 1, 2],
+        );
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(0.0, 0.0, -10.0);
+        transform.set_scale(Vector3::new(4.0, 4.0, 4.0));
+        transform.copy_local_to_global();
+
+        let world_ray = ray([1.0, 0.0, 0.0], [0.0, 0.0, -1.0]);
+        let distance = cast_ray_local(&world_ray, &transform, |local| triangles.cast_ray(local));
+        assert_relative_eq!(10.0, distance.unwrap());
+    }
+
+    #[test]
+    fn sprite_quad_follows_transform_and_offsets() {
+        let sprite = Sprite {
+            width: 20.0,
+            height: 10.0,
+            offsets: [5.0, 0.0],
+            tex_coords: TextureCoordinates {
+                left: 0.0,
+                right: 1.0,
+                bottom: 0.0,
+                top: 1.0,
+            },
+        };
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(100.0, 0.0, 0.0);
+        transform.set_rotation(UnitQuaternion::from_axis_angle(
+            &Vector3::z_axis(),
+            std::f32::consts::FRAC_PI_2,
+        ));
+        transform.copy_local_to_global();
+
+        // The quad is centered at (100, -5) and spans 10 units along x and 20 along y.
+        let down = [0.0, 0.0, -1.0];
+        let hit = cast_ray_sprite(&ray([100.0, -5.0, 10.0], down), &sprite, &transform);
+        assert_relative_eq!(10.0, hit.unwrap());
+        assert!(cast_ray_sprite(&ray([104.0, 4.0, 10.0], down), &sprite, &transform).is_some());
+        assert!(cast_ray_sprite(&ray([106.0, -5.0, 10.0], down), &sprite, &transform).is_none());
+        assert!(cast_ray_sprite(&ray([100.0, 6.0, 10.0], down), &sprite, &transform).is_none());
+    }
+
+    #[test]
+    fn picks_visible_meshes_sorted_by_distance() {
+        let mut world = World::default();
+        let mut index = SpatialIndex::new();
+        let mut spawn = |world: &mut World, z: f32| {
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(0.0, 0.0, z);
+            let entity = world.push((transform, detached_handle::<Mesh>()));
+            index.insert(entity, BoundingSphere::new(Point3::new(0.0, 0.0, z), 1.0));
+            entity
+        };
+        let far = spawn(&mut world, -20.0);
+        let near = spawn(&mut world, -10.0);
+        let hidden = spawn(&mut world, -5.0);
+        world.entry(hidden).unwrap().add_component(Hidden);
+        let no_mesh = world.push((Transform::default(),));
+        index.insert(
+            no_mesh,
+            BoundingSphere::new(Point3::new(0.0, 0.0, -2.0), 1.0),
+        );
+
+        let picker = Picker::new().with_meshes(&index);
+        let hits = picker.pick(&world, &ray([0.0, 0.0, 0.0], [0.0, 0.0, -1.0]));
+        assert_eq!(
+            vec![near, far],
+            hits.iter().map(|hit| hit.entity).collect::<Vec<_>>()
+        );
+        assert_relative_eq!(9.0, hits[0].distance);
+        assert_relative_eq!(Point3::new(0.0, 0.0, -9.0), hits[0].point);
+
+        let hits = picker
+            .with_max_distance(15.0)
+            .pick(&world, &ray([0.0, 0.0, 0.0], [0.0, 0.0, -1.0]));
+        assert_eq!(1, hits.len());
+    }
+}
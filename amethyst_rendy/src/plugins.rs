@@ -9,7 +9,9 @@ pub use window::RenderToWindow;
 
 use crate::{
     bundle::{RenderOrder, RenderPlan, RenderPlugin, Target},
+    lod::MeshLodSystem,
     pass::*,
+    spatial::{SpatialIndex, SpatialIndexSystem},
     sprite_visibility::{SpriteVisibility, SpriteVisibilitySortingSystem},
     visibility::{Visibility, VisibilitySortingSystem},
     Backend, Factory,
@@ -205,7 +207,14 @@ impl<B: Backend, D: Base3DPassDef> RenderPlugin<B> for RenderBase3D<D> {
         builder: &mut DispatcherBuilder,
     ) -> Result<(), Error> {
         resources.insert(Visibility::default());
-        builder.add_system(VisibilitySortingSystem::default());
+        resources.get_or_insert_with(SpatialIndex::default);
+        let order = SystemOrder::new().stage(stage::RENDER_PREP);
+        builder.add_system_ordered(SpatialIndexSystem, order.clone());
+        builder.add_system_ordered(
+            VisibilitySortingSystem::default(),
+            order.clone().after("SpatialIndexSystem"),
+        );
+        builder.add_system_ordered(MeshLodSystem, order.after("VisibilitySortingSystem"));
         Ok(())
     }
 
@@ -259,7 +268,10 @@ impl<B: Backend> RenderPlugin<B> for RenderFlat2D {
         builder: &mut DispatcherBuilder,
     ) -> Result<(), Error> {
         resources.insert(SpriteVisibility::default());
-        builder.add_system(SpriteVisibilitySortingSystem);
+        builder.add_system_ordered(
+            SpriteVisibilitySortingSystem,
+            SystemOrder::new().stage(stage::RENDER_PREP),
+        );
         Ok(())
     }
 
@@ -1,6 +1,9 @@
 //! 3D Shape Primitives
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
 use amethyst_assets::{DefaultLoader, Handle, Loader, ProcessingQueue, Progress};
 use amethyst_core::math::Vector3;
+use amethyst_error::{format_err, Error};
 use genmesh::{
     generators::{
         Circle, Cone, Cube, Cylinder, IcoSphere, IndexedPolygon, Plane, SharedVertex, SphereUv,
@@ -12,7 +15,10 @@ use rendy::mesh::{
     MeshBuilder, Normal, PosNormTangTex, PosNormTex, PosTex, Position, Tangent, TexCoord,
 };
 
-use crate::types::{Mesh, MeshData};
+use crate::{
+    picking::MeshTriangles,
+    types::{Mesh, MeshData},
+};
 fn option_none<T>() -> Option<T> {
     None
 }
@@ -38,6 +44,157 @@ pub enum Shape {
     Plane(Option<(usize, usize)>),
     /// Circle, located in the XY plane, number of points around the circle
     Circle(usize),
+    /// Capsule along the Y axis, radius, length of the cylinder between the two hemispheres,
+    /// number of points around the axis >= 3, number of rings of each hemisphere > 0
+    Capsule(f32, f32, usize, usize),
+    /// Cube with vertices in [-1, +1] range and rounded edges, radius of the rounding in
+    /// (0, 1], number of segments of each rounded edge on each side of the edge > 0
+    RoundedBox(f32, usize),
+    /// Terrain in the XZ plane with vertices in [-1, +1] range, raised along the Y axis by the
+    /// heights of the heightmap
+    Heightmap(Heightmap),
+}
+
+/// Grid of heights for generating a `Shape::Heightmap` terrain.
+///
+/// Heights are usually in the `0.0..=1.0` range, the scale passed when generating the shape
+/// sets the size and the height of the terrain. The columns of the grid are laid out along the
+/// X axis and the rows from +Z to -Z, so a texture drawn on the terrain with its texture
+/// coordinates lines up with the image the heights were read from.
+///
+/// Heightmaps can't be built from a `TextureData`, as it doesn't give access to its pixels. To
+/// build the terrain from a grayscale image, pass the bytes of the image file to
+/// `from_image_data`, or decode the image and pass its pixels to `from_luma8` or `from_luma16`.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "RawHeightmap")]
+pub struct Heightmap {
+    columns: usize,
+    rows: usize,
+    heights: Vec<f32>,
+}
+
+/// Unchecked heightmap, deserialized before it is validated by `Heightmap::try_new`.
+#[derive(serde::Deserialize)]
+struct RawHeightmap {
+    columns: usize,
+    rows: usize,
+    heights: Vec<f32>,
+}
+
+impl std::convert::TryFrom<RawHeightmap> for Heightmap {
+    type Error = Error;
+
+    fn try_from(raw: RawHeightmap) -> Result<Self, Error> {
+        Heightmap::try_new(raw.columns, raw.rows, raw.heights)
+    }
+}
+
+impl Heightmap {
+    /// Creates a heightmap from heights stored row by row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are less than two columns or rows, or if the number of heights doesn't
+    /// match the size of the grid.
+    pub fn new(columns: usize, rows: usize, heights: Vec<f32>) -> Self {
+        Heightmap::try_new(columns, rows, heights).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a heightmap from heights stored row by row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are less than two columns or rows, or if the number of heights
+    /// doesn't match the size of the grid.
+    pub fn try_new(columns: usize, rows: usize, heights: Vec<f32>) -> Result<Self, Error> {
+        if columns < 2 || rows < 2 {
+            return Err(format_err!(
+                "Heightmap needs at least 2x2 heights, got {}x{}",
+                columns,
+                rows
+            ));
+        }
+        if columns.checked_mul(rows) != Some(heights.len()) {
+            return Err(format_err!(
+                "Heightmap of {}x{} heights needs {} heights, got {}",
+                columns,
+                rows,
+                columns.saturating_mul(rows),
+                heights.len()
+            ));
+        }
+        Ok(Heightmap {
+            columns,
+            rows,
+            heights,
+        })
+    }
+
+    /// Creates a heightmap from 8 bit grayscale pixels, mapping black to `0.0` and white to
+    /// `1.0`.
+    pub fn from_luma8(width: usize, height: usize, pixels: &[u8]) -> Self {
+        let heights = pixels.iter().map(|&p| f32::from(p) / 255.0).collect();
+        Heightmap::new(width, height, heights)
+    }
+
+    /// Creates a heightmap from 16 bit grayscale pixels, mapping black to `0.0` and white to
+    /// `1.0`.
+    pub fn from_luma16(width: usize, height: usize, pixels: &[u16]) -> Self {
+        let heights = pixels.iter().map(|&p| f32::from(p) / 65535.0).collect();
+        Heightmap::new(width, height, heights)
+    }
+
+    /// Creates a heightmap from the bytes of an image file in any of the formats the
+    /// `ImageFormat` importer reads, mapping black to `0.0` and white to `1.0`.
+    ///
+    /// 8 and 16 bit grayscale images keep their precision, other images are converted to 8 bit
+    /// grayscale.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image can't be decoded.
+    pub fn from_image_data(bytes: &[u8]) -> Result<Self, Error> {
+        use image::{DynamicImage, GenericImageView, Pixel};
+
+        let image = image::load_from_memory(bytes)?;
+        let (width, height) = image.dimensions();
+        let (width, height) = (width as usize, height as usize);
+        Ok(match image {
+            DynamicImage::ImageLuma8(buffer) => {
+                Heightmap::from_luma8(width, height, &buffer.into_raw())
+            }
+            DynamicImage::ImageLuma16(buffer) => {
+                Heightmap::from_luma16(width, height, &buffer.into_raw())
+            }
+            image => {
+                let pixels = image
+                    .pixels()
+                    .map(|(_, _, pixel)| pixel.to_luma().0[0])
+                    .collect::<Vec<_>>();
+                Heightmap::from_luma8(width, height, &pixels)
+            }
+        })
+    }
+
+    /// Returns the number of heights along the X axis.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of heights along the Z axis.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns all heights, row by row.
+    pub fn heights(&self) -> &[f32] {
+        &self.heights
+    }
+
+    /// Returns the height at the given column and row.
+    pub fn height(&self, column: usize, row: usize) -> f32 {
+        self.heights[row * self.columns + column]
+    }
 }
 
 /// Required resource access to upload shape
@@ -111,6 +268,26 @@ impl Shape {
             .load_from_data(self.generate::<V>(scale).into(), progress, upload.storage)
     }
 
+    /// Generate `Mesh` for the `Shape` with a copy of its triangles attached, so the mesh can be
+    /// picked precisely if `PickingMeshes` is enabled, and convert it into a `Handle<Mesh>`.
+    ///
+    /// See [upload](#method.upload) for the parameters.
+    pub fn upload_with_triangles<V, P>(
+        &self,
+        scale: Option<(f32, f32, f32)>,
+        upload: &ShapeUpload<'_>,
+        progress: P,
+    ) -> Handle<Mesh>
+    where
+        V: FromShape + Into<MeshBuilder<'static>>,
+        P: Progress,
+    {
+        let internal = self.generate_internal(scale);
+        let triangles = MeshTriangles::from_triangle_list(internal.map_into(|v| v.0));
+        let data = MeshData::from(V::from(&internal).into()).with_triangles(triangles);
+        upload.loader.load_from_data(data, progress, upload.storage)
+    }
+
     /// Generate `MeshBuilder` for the `Shape`
     ///
     /// ### Parameters:
@@ -188,6 +365,13 @@ impl Shape {
                 )
             }
             Shape::Circle(u) => generate_vertices(Circle::new(u), scale),
+            Shape::Capsule(radius, length, segments, rings) => {
+                scale_vertices(generate_capsule(radius, length, segments, rings), scale)
+            }
+            Shape::RoundedBox(radius, segments) => {
+                scale_vertices(generate_rounded_box(radius, segments), scale)
+            }
+            Shape::Heightmap(ref heightmap) => scale_vertices(generate_heightmap(heightmap), scale),
         };
         InternalShape(vertices)
     }
@@ -239,6 +423,227 @@ where
         .collect::<Vec<_>>()
 }
 
+/// Scales vertices generated in the unit space of a shape.
+///
+/// Normals are transformed by the inverse scale so they stay perpendicular to the surface, while
+/// tangents follow the surface.
+///
+/// # Panics
+///
+/// Panics if a component of the scale is zero, as the surface would collapse and its normals
+/// couldn't be computed.
+fn scale_vertices(
+    vertices: Vec<InternalVertexData>,
+    scale: Option<(f32, f32, f32)>,
+) -> Vec<InternalVertexData> {
+    let (x, y, z) = match scale {
+        Some(scale) => scale,
+        None => return vertices,
+    };
+    assert!(
+        x != 0.0 && y != 0.0 && z != 0.0,
+        "Invalid shape scale, components must not be zero: {:?}",
+        (x, y, z)
+    );
+    let scale = Vector3::new(x, y, z);
+    vertices
+        .into_iter()
+        .map(|(pos, normal, uv, tangent)| {
+            let pos = Vector3::from(pos).component_mul(&scale);
+            let normal = Vector3::from(normal).component_div(&scale).normalize();
+            let tangent = Vector3::from(tangent).component_mul(&scale).normalize();
+            (pos.into(), normal.into(), uv, tangent.into())
+        })
+        .collect()
+}
+
+/// Triangulates a grid of vertices stored row by row, with texture coordinates increasing along
+/// the columns and rows.
+///
+/// The tangents of the vertices point along the columns and `normal x tangent` along the rows,
+/// which makes the triangles counter clockwise seen from the side the normals point to.
+/// Degenerate triangles, e.g. at the poles of a capsule, are left out.
+fn grid_triangles(
+    columns: usize,
+    rows: usize,
+    grid: &[InternalVertexData],
+    triangles: &mut Vec<InternalVertexData>,
+) {
+    for row in 0..rows - 1 {
+        for column in 0..columns - 1 {
+            let a = grid[row * columns + column];
+            let b = grid[row * columns + column + 1];
+            let c = grid[(row + 1) * columns + column + 1];
+            let d = grid[(row + 1) * columns + column];
+            for &(a, b, c) in &[(a, b, c), (a, c, d)] {
+                let ab = Vector3::from(b.0) - Vector3::from(a.0);
+                let ac = Vector3::from(c.0) - Vector3::from(a.0);
+                if ab.cross(&ac).norm_squared() > 0.0 {
+                    triangles.extend_from_slice(&[a, b, c]);
+                }
+            }
+        }
+    }
+}
+
+fn generate_capsule(
+    radius: f32,
+    length: f32,
+    segments: usize,
+    rings: usize,
+) -> Vec<InternalVertexData> {
+    assert!(
+        segments >= 3,
+        "Invalid number of capsule segments: {}",
+        segments
+    );
+    assert!(rings > 0, "Invalid number of capsule rings: {}", rings);
+    let half_length = length / 2.0;
+    let arc = radius * FRAC_PI_2;
+    let total = 2.0 * arc + length;
+    // Latitude, sine and cosine of it and the center of the hemisphere of each row, from the
+    // bottom to the top pole. The poles use exact values so their triangles are degenerate.
+    let profile = (0..=rings)
+        .map(|ring| (ring, -half_length, -1.0))
+        .chain((0..=rings).map(|ring| (ring, half_length, 1.0)))
+        .map(|(ring, center, side)| {
+            let latitude = FRAC_PI_2 * ring as f32 / rings as f32;
+            let latitude = if side < 0.0 {
+                latitude - FRAC_PI_2
+            } else {
+                latitude
+            };
+            let (sin, cos) = if ring == rings && side < 0.0 || ring == 0 && side > 0.0 {
+                (0.0, 1.0)
+            } else if ring == 0 {
+                (-1.0, 0.0)
+            } else if ring == rings {
+                (1.0, 0.0)
+            } else {
+                latitude.sin_cos()
+            };
+            (latitude, sin, cos, center)
+        })
+        .collect::<Vec<_>>();
+
+    let columns = segments + 1;
+    let mut grid = Vec::with_capacity(columns * profile.len());
+    for &(latitude, sin, cos, center) in &profile {
+        let distance = if center < 0.0 {
+            (latitude + FRAC_PI_2) * radius
+        } else {
+            arc + length + latitude * radius
+        };
+        let v = if total > 0.0 { distance / total } else { 0.0 };
+        for segment in 0..columns {
+            let u = segment as f32 / segments as f32;
+            let (around_sin, around_cos) = (2.0 * PI * u).sin_cos();
+            let normal = [cos * around_cos, sin, -cos * around_sin];
+            grid.push((
+                [
+                    radius * normal[0],
+                    center + radius * normal[1],
+                    radius * normal[2],
+                ],
+                normal,
+                [u, v],
+                [-around_sin, 0.0, -around_cos],
+            ));
+        }
+    }
+
+    let mut vertices = Vec::new();
+    grid_triangles(columns, profile.len(), &grid, &mut vertices);
+    vertices
+}
+
+fn generate_rounded_box(radius: f32, segments: usize) -> Vec<InternalVertexData> {
+    assert!(
+        radius > 0.0 && radius <= 1.0,
+        "Invalid rounded box radius: {}",
+        radius
+    );
+    assert!(
+        segments > 0,
+        "Invalid number of rounded box segments: {}",
+        segments
+    );
+    let inner = 1.0 - radius;
+    // Coordinates along each axis of a face. The rounded parts are sampled at equal angles.
+    let coordinates = (0..=segments)
+        .map(|i| -inner - radius * (FRAC_PI_4 * (segments - i) as f32 / segments as f32).tan())
+        .chain(
+            (0..=segments).map(|i| inner + radius * (FRAC_PI_4 * i as f32 / segments as f32).tan()),
+        )
+        .collect::<Vec<_>>();
+    // Normal, direction of the columns and direction of the rows of each face.
+    let faces = [
+        (Vector3::x(), -Vector3::z(), Vector3::y()),
+        (-Vector3::x(), Vector3::z(), Vector3::y()),
+        (Vector3::y(), Vector3::x(), -Vector3::z()),
+        (-Vector3::y(), Vector3::x(), Vector3::z()),
+        (Vector3::z(), Vector3::x(), Vector3::y()),
+        (-Vector3::z(), -Vector3::x(), Vector3::y()),
+    ];
+
+    let size = coordinates.len();
+    let mut vertices = Vec::new();
+    let mut grid = Vec::with_capacity(size * size);
+    for &(face, u_axis, v_axis) in &faces {
+        grid.clear();
+        for &v in &coordinates {
+            for &u in &coordinates {
+                let point: Vector3<f32> = face + u_axis * u + v_axis * v;
+                let center = point.map(|c| c.max(-inner).min(inner));
+                let normal = (point - center).normalize();
+                let tangent = (u_axis - normal * normal.dot(&u_axis)).normalize();
+                grid.push((
+                    (center + normal * radius).into(),
+                    normal.into(),
+                    [(u + 1.0) / 2.0, (v + 1.0) / 2.0],
+                    tangent.into(),
+                ));
+            }
+        }
+        grid_triangles(size, size, &grid, &mut vertices);
+    }
+    vertices
+}
+
+fn generate_heightmap(heightmap: &Heightmap) -> Vec<InternalVertexData> {
+    let (columns, rows) = (heightmap.columns(), heightmap.rows());
+    let x = |column: usize| column as f32 / (columns - 1) as f32 * 2.0 - 1.0;
+    let z = |row: usize| 1.0 - row as f32 / (rows - 1) as f32 * 2.0;
+
+    let mut grid = Vec::with_capacity(columns * rows);
+    for row in 0..rows {
+        for column in 0..columns {
+            // Slopes from central differences, one sided at the borders.
+            let (left, right) = (column.saturating_sub(1), (column + 1).min(columns - 1));
+            let (near, far) = (row.saturating_sub(1), (row + 1).min(rows - 1));
+            let slope_x =
+                (heightmap.height(right, row) - heightmap.height(left, row)) / (x(right) - x(left));
+            let slope_z = (heightmap.height(column, far) - heightmap.height(column, near))
+                / (z(far) - z(near));
+            let normal = Vector3::new(-slope_x, 1.0, -slope_z).normalize();
+            let tangent = Vector3::new(1.0, slope_x, 0.0).normalize();
+            grid.push((
+                [x(column), heightmap.height(column, row), z(row)],
+                normal.into(),
+                [
+                    column as f32 / (columns - 1) as f32,
+                    row as f32 / (rows - 1) as f32,
+                ],
+                tangent.into(),
+            ));
+        }
+    }
+
+    let mut vertices = Vec::with_capacity((columns - 1) * (rows - 1) * 6);
+    grid_triangles(columns, rows, &grid, &mut vertices);
+    vertices
+}
+
 impl FromInternalVertex for Position {
     fn from_internal(v: &InternalVertexData) -> Self {
         Position([v.0[0], v.0[1], v.0[2]])
@@ -322,4 +727,117 @@ mod tests {
             Shape::Plane(None).generate::<Vec<PosNormTangTex>>(None)
         );
     }
+
+    /// Checks that every triangle is counter clockwise seen from the side its normals point to,
+    /// and that the tangents are unit length and perpendicular to the normals.
+    fn assert_consistent(shape: &Shape, scale: Option<(f32, f32, f32)>) {
+        let vertices = shape.generate_internal(scale).0;
+        assert!(!vertices.is_empty());
+        assert_eq!(0, vertices.len() % 3);
+        for triangle in vertices.chunks(3) {
+            let [a, b, c] = [triangle[0].0, triangle[1].0, triangle[2].0];
+            let face =
+                (Vector3::from(b) - Vector3::from(a)).cross(&(Vector3::from(c) - Vector3::from(a)));
+            for vertex in triangle {
+                let normal = Vector3::from(vertex.1);
+                let tangent = Vector3::from(vertex.3);
+                assert!(face.dot(&normal) > 0.0, "{:?} faces inwards", triangle);
+                assert!((normal.norm() - 1.0).abs() < 1e-5);
+                assert!((tangent.norm() - 1.0).abs() < 1e-5);
+                assert!(normal.dot(&tangent).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn capsule_is_closed_and_outward_facing() {
+        let shape = Shape::Capsule(0.5, 2.0, 16, 4);
+        assert_consistent(&shape, None);
+        assert_consistent(&shape, Some((2.0, 1.0, 3.0)));
+        for (pos, normal, _, _) in shape.generate_internal(None).0 {
+            let axis = Vector3::new(0.0, pos[1].max(-1.0).min(1.0), 0.0);
+            let offset = Vector3::from(pos) - axis;
+            assert!((offset.norm() - 0.5).abs() < 1e-5);
+            assert!((offset.normalize() - Vector3::from(normal)).norm() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn rounded_box_stays_within_cube() {
+        let shape = Shape::RoundedBox(0.25, 3);
+        assert_consistent(&shape, None);
+        assert_consistent(&shape, Some((1.0, 2.0, 0.5)));
+        for (pos, _, uv, _) in shape.generate_internal(None).0 {
+            assert!(pos.iter().all(|c| c.abs() <= 1.0 + 1e-5));
+            assert!(uv.iter().all(|c| (0.0..=1.0).contains(c)));
+        }
+    }
+
+    #[test]
+    fn heightmap_normals_follow_slope() {
+        // Rising by 1.0 over each step of 1.0 along X.
+        let heights = (0..3).flat_map(|_| vec![0.0, 1.0, 2.0]).collect();
+        let shape = Shape::Heightmap(Heightmap::new(3, 3, heights));
+        assert_consistent(&shape, None);
+        assert_consistent(&shape, Some((10.0, 2.0, 10.0)));
+
+        let vertices = shape.generate_internal(None).0;
+        assert_eq!(2 * 2 * 6, vertices.len());
+        let expected_normal = Vector3::new(-1.0, 1.0, 0.0).normalize();
+        for (pos, normal, uv, tangent) in vertices {
+            assert!((Vector3::from(normal) - expected_normal).norm() < 1e-5);
+            assert!(
+                (Vector3::from(tangent) - Vector3::new(1.0, 1.0, 0.0).normalize()).norm() < 1e-5
+            );
+            assert_eq!([(pos[0] + 1.0) / 2.0, (1.0 - pos[2]) / 2.0], uv);
+        }
+
+        let scaled = shape.generate_internal(Some((2.0, 1.0, 1.0))).0;
+        let expected_normal = Vector3::new(-0.5, 1.0, 0.0).normalize();
+        assert!((Vector3::from(scaled[0].1) - expected_normal).norm() < 1e-5);
+    }
+
+    #[test]
+    fn heightmap_from_luma8() {
+        let heightmap = Heightmap::from_luma8(2, 2, &[0, 255, 51, 102]);
+        assert_eq!(&[0.0, 1.0, 0.2, 0.4], heightmap.heights());
+        assert_eq!(0.4, heightmap.height(1, 1));
+    }
+
+    #[test]
+    fn heightmap_from_image_data() {
+        let pixels = image::GrayImage::from_raw(2, 2, vec![0, 255, 51, 102]).unwrap();
+        let mut bytes = Vec::new();
+        image::DynamicImage::ImageLuma8(pixels)
+            .write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .unwrap();
+
+        let heightmap = Heightmap::from_image_data(&bytes).unwrap();
+        assert_eq!(&[0.0, 1.0, 0.2, 0.4], heightmap.heights());
+        assert!(Heightmap::from_image_data(b"not an image").is_err());
+    }
+
+    #[test]
+    fn heightmap_deserialization_is_validated() {
+        let heightmap: Heightmap =
+            ron::de::from_str("(columns: 2, rows: 2, heights: [0.0, 1.0, 0.5, 0.25])").unwrap();
+        assert_eq!(Heightmap::new(2, 2, vec![0.0, 1.0, 0.5, 0.25]), heightmap);
+
+        let too_small = "(columns: 1, rows: 2, heights: [0.0, 1.0])";
+        assert!(ron::de::from_str::<Heightmap>(too_small).is_err());
+        let mismatch = "(columns: 2, rows: 2, heights: [0.0, 1.0, 0.5])";
+        assert!(ron::de::from_str::<Heightmap>(mismatch).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid shape scale")]
+    fn shape_scale_must_not_be_zero() {
+        Shape::Cube.generate_internal(Some((1.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid number of capsule segments: 2")]
+    fn capsule_needs_three_segments() {
+        Shape::Capsule(0.5, 1.0, 2, 4).generate_internal(None);
+    }
 }
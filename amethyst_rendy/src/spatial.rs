@@ -0,0 +1,663 @@
+//! Bounding volume hierarchy over the bounding spheres of all entities with a `Transform`.
+//!
+//! The [SpatialIndex] resource is kept up to date by the [SpatialIndexSystem], which only
+//! touches entities whose `Transform` or `BoundingSphere` changed. It is used for frustum
+//! culling by `VisibilitySortingSystem` and answers overlap queries and ray casts for gameplay
+//! code.
+use std::{cmp::Ordering, collections::HashMap};
+
+use amethyst_core::{
+    ecs::{systems::ParallelRunnable, *},
+    geometry::{Aabb, Ray, Sphere, Transformable},
+    math::Point3,
+    transform::Transform,
+};
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use crate::visibility::BoundingSphere;
+
+/// Marks the absence of a node.
+const NULL: usize = usize::MAX;
+
+/// Margin added around the bounds of a leaf, relative to its radius, so that small movements
+/// don't restructure the tree.
+const FAT_MARGIN: f32 = 0.1;
+
+/// Number of entries checked for deleted entities every frame by [SpatialIndexSystem].
+const SWEEP_BUDGET: usize = 512;
+
+#[derive(Debug, Clone)]
+struct Node {
+    bounds: Aabb<f32>,
+    /// The parent of the node, or the next free node for nodes in the free list.
+    parent: usize,
+    left: usize,
+    right: usize,
+    /// Height of the subtree, `0` for leaves and `-1` for free nodes.
+    height: i32,
+    leaf: Option<(Entity, BoundingSphere)>,
+}
+
+impl Node {
+    fn is_leaf(&self) -> bool {
+        self.left == NULL
+    }
+}
+
+/// Dynamic bounding volume hierarchy of the world space bounding spheres of entities.
+///
+/// Every entity is stored with its exact sphere in a leaf whose bounds are enlarged by a small
+/// margin, so entities which move a little don't change the structure of the tree. Insertions
+/// pick the sibling which grows the surface area of the tree the least, and the tree is kept
+/// balanced with rotations.
+///
+/// The index is maintained by [SpatialIndexSystem], which removes deleted entities lazily over a
+/// few frames. Queries can therefore return entities which have been deleted very recently, so
+/// their components should be accessed fallibly.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    nodes: Vec<Node>,
+    root: usize,
+    free: usize,
+    leaves: HashMap<Entity, usize>,
+    sweep_cursor: usize,
+    /// Whether a [SpatialIndexSystem] keeps the index up to date.
+    pub(crate) maintained: bool,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        SpatialIndex {
+            nodes: Vec::new(),
+            root: NULL,
+            free: NULL,
+            leaves: HashMap::new(),
+            sweep_cursor: 0,
+            maintained: false,
+        }
+    }
+}
+
+impl SpatialIndex {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of entities in the index.
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns `true` if the index contains no entities.
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Returns `true` if the entity is in the index.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.leaves.contains_key(&entity)
+    }
+
+    /// Returns the world space bounding sphere the entity is indexed with.
+    pub fn bounds(&self, entity: Entity) -> Option<&BoundingSphere> {
+        self.leaves
+            .get(&entity)
+            .and_then(|&leaf| self.nodes[leaf].leaf.as_ref())
+            .map(|(_, sphere)| sphere)
+    }
+
+    /// Inserts the entity with the given world space bounding sphere, or moves it if it is
+    /// already in the index.
+    pub fn insert(&mut self, entity: Entity, sphere: BoundingSphere) {
+        let tight = sphere.to_sphere().aabb();
+        let margin = sphere.radius.abs() * FAT_MARGIN;
+
+        if let Some(&leaf) = self.leaves.get(&entity) {
+            let fat = self.nodes[leaf].bounds;
+            self.nodes[leaf].leaf = Some((entity, sphere));
+            // Keep the leaf in place unless it left its bounds or shrank a lot.
+            if fat.contains_aabb(&tight) && tight.grown(4.0 * margin).contains_aabb(&fat) {
+                return;
+            }
+            self.remove_leaf(leaf);
+            self.nodes[leaf].bounds = tight.grown(margin);
+            self.insert_leaf(leaf);
+            return;
+        }
+
+        let leaf = self.allocate();
+        let node = &mut self.nodes[leaf];
+        node.bounds = tight.grown(margin);
+        node.height = 0;
+        node.leaf = Some((entity, sphere));
+        self.leaves.insert(entity, leaf);
+        self.insert_leaf(leaf);
+    }
+
+    /// Removes the entity from the index, returning `false` if it wasn't in the index.
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        match self.leaves.remove(&entity) {
+            Some(leaf) => {
+                self.remove_leaf(leaf);
+                self.free_node(leaf);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes all entities from the index.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Walks the tree and calls `visit` for every entity in a leaf whose bounds pass `overlaps`.
+    ///
+    /// `overlaps` receives the bounds of a node, and decides whether the subtree below it is
+    /// visited. The bounds of a node contain the spheres of all entities below it, so `visit`
+    /// should still test the sphere it receives.
+    pub fn query<F, V>(&self, mut overlaps: F, mut visit: V)
+    where
+        F: FnMut(&Aabb<f32>) -> bool,
+        V: FnMut(Entity, &BoundingSphere),
+    {
+        if self.root == NULL {
+            return;
+        }
+        let mut stack = Vec::with_capacity(64);
+        stack.push(self.root);
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !overlaps(&node.bounds) {
+                continue;
+            }
+            match &node.leaf {
+                Some((entity, sphere)) => visit(*entity, sphere),
+                None => {
+                    stack.push(node.left);
+                    stack.push(node.right);
+                }
+            }
+        }
+    }
+
+    /// Returns all entities whose bounding sphere overlaps the axis aligned box.
+    pub fn query_aabb(&self, aabb: &Aabb<f32>) -> Vec<Entity> {
+        let mut result = Vec::new();
+        self.query(
+            |bounds| aabb.intersects_aabb(bounds),
+            |entity, sphere| {
+                if aabb.intersects_sphere(&sphere.to_sphere()) {
+                    result.push(entity);
+                }
+            },
+        );
+        result
+    }
+
+    /// Returns all entities whose bounding sphere overlaps the given sphere.
+    pub fn query_sphere(&self, center: &Point3<f32>, radius: f32) -> Vec<Entity> {
+        let query = Sphere::new(*center, radius);
+        let mut result = Vec::new();
+        self.query(
+            |bounds| bounds.intersects_sphere(&query),
+            |entity, sphere| {
+                if query.intersects_sphere(&sphere.to_sphere()) {
+                    result.push(entity);
+                }
+            },
+        );
+        result
+    }
+
+    /// Returns all entities whose bounding sphere is hit by the ray within `max_distance`,
+    /// together with the distance at which the ray enters the sphere, sorted from near to far.
+    ///
+    /// The direction of the ray must be normalized.
+    pub fn cast_ray(&self, ray: &Ray<f32>, max_distance: f32) -> Vec<(Entity, f32)> {
+        let mut hits = Vec::new();
+        self.query(
+            |bounds| {
+                bounds
+                    .intersect_ray(ray)
+                    .map_or(false, |distance| distance <= max_distance)
+            },
+            |entity, sphere| {
+                if let Some(distance) = sphere.to_sphere().intersect_ray(ray) {
+                    if distance <= max_distance {
+                        hits.push((entity, distance));
+                    }
+                }
+            },
+        );
+        hits.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+        hits
+    }
+
+    /// Checks up to `budget` entries, continuing where the last call stopped, and removes the
+    /// entities for which `keep` returns `false`.
+    pub(crate) fn sweep<F: FnMut(Entity) -> bool>(&mut self, budget: usize, mut keep: F) {
+        let len = self.nodes.len();
+        let mut stale = Vec::new();
+        for _ in 0..budget.min(len) {
+            if self.sweep_cursor >= len {
+                self.sweep_cursor = 0;
+            }
+            if let Some((entity, _)) = &self.nodes[self.sweep_cursor].leaf {
+                if !keep(*entity) {
+                    stale.push(*entity);
+                }
+            }
+            self.sweep_cursor += 1;
+        }
+        for entity in stale {
+            self.remove(entity);
+        }
+    }
+
+    fn allocate(&mut self) -> usize {
+        let node = Node {
+            bounds: Aabb::new(Point3::origin(), Point3::origin()),
+            parent: NULL,
+            left: NULL,
+            right: NULL,
+            height: 0,
+            leaf: None,
+        };
+        if self.free == NULL {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        } else {
+            let index = self.free;
+            self.free = self.nodes[index].parent;
+            self.nodes[index] = node;
+            index
+        }
+    }
+
+    fn free_node(&mut self, index: usize) {
+        let node = &mut self.nodes[index];
+        node.parent = self.free;
+        node.left = NULL;
+        node.right = NULL;
+        node.height = -1;
+        node.leaf = None;
+        self.free = index;
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        if self.root == NULL {
+            self.root = leaf;
+            self.nodes[leaf].parent = NULL;
+            return;
+        }
+
+        // Descend to the sibling which grows the total surface area the least.
+        let bounds = self.nodes[leaf].bounds;
+        let mut index = self.root;
+        while !self.nodes[index].is_leaf() {
+            let node = &self.nodes[index];
+            let area = node.bounds.surface_area();
+            let combined = node.bounds.merged(&bounds).surface_area();
+            let cost = 2.0 * combined;
+            let inheritance = 2.0 * (combined - area);
+            let child_cost = |child: usize| {
+                let child = &self.nodes[child];
+                let grown = child.bounds.merged(&bounds).surface_area();
+                if child.is_leaf() {
+                    grown + inheritance
+                } else {
+                    grown - child.bounds.surface_area() + inheritance
+                }
+            };
+            let left_cost = child_cost(node.left);
+            let right_cost = child_cost(node.right);
+            if cost < left_cost && cost < right_cost {
+                break;
+            }
+            index = if left_cost < right_cost {
+                node.left
+            } else {
+                node.right
+            };
+        }
+
+        let sibling = index;
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate();
+        {
+            let node = &mut self.nodes[new_parent];
+            node.parent = old_parent;
+            node.left = sibling;
+            node.right = leaf;
+        }
+        self.nodes[new_parent].bounds = self.nodes[sibling].bounds.merged(&bounds);
+        self.nodes[new_parent].height = self.nodes[sibling].height + 1;
+        self.nodes[sibling].parent = new_parent;
+        self.nodes[leaf].parent = new_parent;
+        if old_parent == NULL {
+            self.root = new_parent;
+        } else {
+            self.replace_child(old_parent, sibling, new_parent);
+        }
+
+        self.refit(new_parent);
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if leaf == self.root {
+            self.root = NULL;
+            return;
+        }
+
+        let parent = self.nodes[leaf].parent;
+        let grandparent = self.nodes[parent].parent;
+        let sibling = if self.nodes[parent].left == leaf {
+            self.nodes[parent].right
+        } else {
+            self.nodes[parent].left
+        };
+
+        self.nodes[sibling].parent = grandparent;
+        self.free_node(parent);
+        if grandparent == NULL {
+            self.root = sibling;
+        } else {
+            self.replace_child(grandparent, parent, sibling);
+            self.refit(grandparent);
+        }
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        let node = &mut self.nodes[parent];
+        if node.left == old {
+            node.left = new;
+        } else {
+            node.right = new;
+        }
+    }
+
+    /// Rebalances and recomputes the bounds of `index` and all its ancestors.
+    fn refit(&mut self, mut index: usize) {
+        while index != NULL {
+            index = self.balance(index);
+            let (left, right) = (self.nodes[index].left, self.nodes[index].right);
+            self.nodes[index].height = 1 + self.nodes[left].height.max(self.nodes[right].height);
+            self.nodes[index].bounds = self.nodes[left].bounds.merged(&self.nodes[right].bounds);
+            index = self.nodes[index].parent;
+        }
+    }
+
+    /// Rotates the higher child of `a` up if the heights of its subtrees differ by more than one,
+    /// and returns the node which took the place of `a`.
+    fn balance(&mut self, a: usize) -> usize {
+        if self.nodes[a].is_leaf() || self.nodes[a].height < 2 {
+            return a;
+        }
+
+        let b = self.nodes[a].left;
+        let c = self.nodes[a].right;
+        let balance = self.nodes[c].height - self.nodes[b].height;
+
+        if balance > 1 {
+            let (f, g) = (self.nodes[c].left, self.nodes[c].right);
+            self.rotate_up(a, c);
+            self.nodes[c].left = a;
+            let (keep, moved) = if self.nodes[f].height > self.nodes[g].height {
+                (f, g)
+            } else {
+                (g, f)
+            };
+            self.nodes[c].right = keep;
+            self.nodes[a].right = moved;
+            self.nodes[moved].parent = a;
+            self.update_node(a, b, moved);
+            self.update_node(c, a, keep);
+            return c;
+        }
+
+        if balance < -1 {
+            let (d, e) = (self.nodes[b].left, self.nodes[b].right);
+            self.rotate_up(a, b);
+            self.nodes[b].left = a;
+            let (keep, moved) = if self.nodes[d].height > self.nodes[e].height {
+                (d, e)
+            } else {
+                (e, d)
+            };
+            self.nodes[b].right = keep;
+            self.nodes[a].left = moved;
+            self.nodes[moved].parent = a;
+            self.update_node(a, c, moved);
+            self.update_node(b, a, keep);
+            return b;
+        }
+
+        a
+    }
+
+    /// Puts `child` in the place of its parent `a` and makes it the parent of `a`.
+    fn rotate_up(&mut self, a: usize, child: usize) {
+        let parent = self.nodes[a].parent;
+        self.nodes[child].parent = parent;
+        self.nodes[a].parent = child;
+        if parent == NULL {
+            self.root = child;
+        } else {
+            self.replace_child(parent, a, child);
+        }
+    }
+
+    /// Recomputes the bounds and height of `index` from two of its children.
+    fn update_node(&mut self, index: usize, first: usize, second: usize) {
+        self.nodes[index].bounds = self.nodes[first].bounds.merged(&self.nodes[second].bounds);
+        self.nodes[index].height = 1 + self.nodes[first].height.max(self.nodes[second].height);
+    }
+}
+
+/// Returns the world space bounding sphere of an entity, which is a unit sphere around its
+/// origin if it has no `BoundingSphere`.
+pub(crate) fn world_bounding_sphere(
+    transform: &Transform,
+    sphere: Option<&BoundingSphere>,
+) -> BoundingSphere {
+    let sphere = sphere
+        .map_or_else(
+            || Sphere::new(Point3::origin(), 1.0),
+            BoundingSphere::to_sphere,
+        )
+        .transformed_by(transform);
+    BoundingSphere::new(sphere.center, sphere.radius)
+}
+
+/// Keeps the [SpatialIndex] resource up to date with the entities that have a `Transform`.
+///
+/// Only entities whose `Transform` or `BoundingSphere` may have changed since the last frame are
+/// checked, and they are only moved in the index if their world space bounds changed.
+/// Deleted entities, and entities which lost their `Transform`, are removed over the following
+/// frames. This should run after the `TransformSystem`.
+#[derive(Debug, Default)]
+pub struct SpatialIndexSystem;
+
+impl System for SpatialIndexSystem {
+    fn build(self) -> Box<dyn ParallelRunnable> {
+        Box::new(
+            SystemBuilder::new("SpatialIndexSystem")
+                .write_resource::<SpatialIndex>()
+                .with_query(
+                    <(Entity, &Transform, Option<&BoundingSphere>)>::query()
+                        .filter(maybe_changed::<Transform>() | maybe_changed::<BoundingSphere>()),
+                )
+                .read_component::<Transform>()
+                .build(move |_commands, world, index, query| {
+                    #[cfg(feature = "profiler")]
+                    profile_scope!("spatial_index_system");
+
+                    index.maintained = true;
+
+                    // Change detection works per chunk, and the `TransformSystem` touches the
+                    // global matrices of children every frame, so most of these are unchanged.
+                    for (entity, transform, sphere) in query.iter(world) {
+                        let bounds = world_bounding_sphere(transform, sphere);
+                        if index.bounds(*entity) != Some(&bounds) {
+                            index.insert(*entity, bounds);
+                        }
+                    }
+
+                    index.sweep(SWEEP_BUDGET, |entity| {
+                        world
+                            .entry_ref(entity)
+                            .map_or(false, |entry| entry.get_component::<Transform>().is_ok())
+                    });
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities(count: usize) -> Vec<Entity> {
+        let mut world = World::default();
+        (0..count).map(|_| world.push(())).collect()
+    }
+
+    fn sphere(x: f32, y: f32, z: f32, radius: f32) -> BoundingSphere {
+        BoundingSphere::new(Point3::new(x, y, z), radius)
+    }
+
+    /// Checks the links, heights and bounds of every node below `index`, returning its height.
+    fn validate(index: &SpatialIndex, node: usize) -> i32 {
+        let n = &index.nodes[node];
+        if n.is_leaf() {
+            let (_, sphere) = n.leaf.as_ref().unwrap();
+            assert!(n.bounds.contains_aabb(&sphere.to_sphere().aabb()));
+            assert_eq!(0, n.height);
+            return 0;
+        }
+        assert_eq!(node, index.nodes[n.left].parent);
+        assert_eq!(node, index.nodes[n.right].parent);
+        assert!(n.bounds.contains_aabb(&index.nodes[n.left].bounds));
+        assert!(n.bounds.contains_aabb(&index.nodes[n.right].bounds));
+        let left = validate(index, n.left);
+        let right = validate(index, n.right);
+        assert_eq!(n.height, 1 + left.max(right));
+        n.height
+    }
+
+    #[test]
+    fn queries_match_brute_force_after_moves_and_removals() {
+        let entities = entities(300);
+        let mut index = SpatialIndex::new();
+        let position = |i: usize, frame: usize| {
+            let i = i as f32;
+            let frame = frame as f32;
+            sphere(
+                (i * 7.3 + frame * 3.1) % 100.0,
+                (i * 3.7) % 50.0,
+                (i * 1.9 + frame) % 80.0,
+                0.5 + (i % 4.0),
+            )
+        };
+
+        for frame in 0..3 {
+            for (i, entity) in entities.iter().enumerate() {
+                index.insert(*entity, position(i, frame));
+            }
+        }
+        for entity in entities.iter().step_by(3) {
+            assert!(index.remove(*entity));
+        }
+        assert!(!index.remove(entities[0]));
+        assert_eq!(200, index.len());
+        assert!(validate(&index, index.root) < 16);
+
+        let center = Point3::new(40.0, 20.0, 30.0);
+        let mut found = index.query_sphere(&center, 15.0);
+        found.sort();
+        let mut expected: Vec<_> = entities
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i % 3 != 0)
+            .filter(|(i, _)| {
+                let s = position(*i, 2);
+                (s.center - center).norm() <= 15.0 + s.radius
+            })
+            .map(|(_, entity)| *entity)
+            .collect();
+        expected.sort();
+        assert!(!expected.is_empty());
+        assert_eq!(expected, found);
+
+        let min = Point3::new(10.0, 0.0, 10.0);
+        let max = Point3::new(30.0, 10.0, 40.0);
+        let query = Aabb::new(min, max);
+        let mut found = index.query_aabb(&query);
+        found.sort();
+        let mut expected: Vec<_> = entities
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i % 3 != 0)
+            .filter(|(i, _)| {
+                let s = position(*i, 2);
+                query.distance_squared(&s.center) <= s.radius * s.radius
+            })
+            .map(|(_, entity)| *entity)
+            .collect();
+        expected.sort();
+        assert!(!expected.is_empty());
+        assert_eq!(expected, found);
+    }
+
+    #[test]
+    fn ray_cast_returns_hits_sorted_by_distance() {
+        let entities = entities(4);
+        let mut index = SpatialIndex::new();
+        index.insert(entities[0], sphere(0.0, 0.0, -10.0, 1.0));
+        index.insert(entities[1], sphere(0.0, 0.0, -5.0, 1.0));
+        index.insert(entities[2], sphere(5.0, 0.0, -5.0, 1.0));
+        index.insert(entities[3], sphere(0.0, 0.0, 5.0, 1.0));
+
+        let ray = Ray {
+            origin: Point3::origin(),
+            direction: -amethyst_core::math::Vector3::z(),
+        };
+        assert_eq!(
+            vec![(entities[1], 4.0), (entities[0], 9.0)],
+            index.cast_ray(&ray, 100.0)
+        );
+        assert_eq!(vec![(entities[1], 4.0)], index.cast_ray(&ray, 5.0));
+    }
+
+    #[test]
+    fn world_bounding_sphere_grows_with_largest_scale_axis() {
+        let mut transform = Transform::default();
+        transform.set_scale(amethyst_core::math::Vector3::new(1.0, 3.0, 1.0));
+        transform.set_rotation_z_axis(std::f32::consts::FRAC_PI_4);
+        transform.copy_local_to_global();
+
+        let bounds = world_bounding_sphere(&transform, Some(&sphere(0.0, 0.0, 0.0, 2.0)));
+        assert!((bounds.radius - 6.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn sweep_removes_rejected_entities() {
+        let entities = entities(10);
+        let mut index = SpatialIndex::new();
+        for (i, entity) in entities.iter().enumerate() {
+            index.insert(*entity, sphere(i as f32 * 3.0, 0.0, 0.0, 1.0));
+        }
+
+        let deleted = entities[4];
+        for _ in 0..10 {
+            index.sweep(4, |entity| entity != deleted);
+        }
+        assert!(!index.contains(deleted));
+        assert_eq!(9, index.len());
+        assert_eq!(None, index.bounds(deleted));
+        validate(&index, index.root);
+    }
+}
@@ -1,12 +1,12 @@
 //! Material abstraction submodule.
-use amethyst_assets::{AssetHandle, AssetStorage, Handle, LoadHandle, WeakHandle};
+use amethyst_assets::{is_detached, AssetHandle, AssetStorage, Handle, LoadHandle, WeakHandle};
 use amethyst_core::ecs::*;
 use glsl_layout::*;
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
 use crate::{
-    mtl::{Material, StaticTextureSet},
+    mtl::{Material, MaterialDefaults, StaticTextureSet},
     pod,
     rendy::{
         command::RenderPassEncoder,
@@ -260,9 +260,21 @@ impl<B: Backend, T: for<'a> StaticTextureSet<'a>> MaterialSub<B, T> {
         let mat = mat_storage.get(handle)?;
         // log::debug!("try_insert got material_id: {:?}", handle);
 
-        let has_tex = T::textures(mat).any(|t| {
+        // Textures left out of the material are taken from the default material.
+        let defaults = resources.get::<MaterialDefaults>();
+        let textures: Vec<&Handle<Texture>> = match defaults.as_ref() {
+            Some(defaults) => {
+                T::textures(mat)
+                    .zip(T::textures(&defaults.0))
+                    .map(|(t, default)| if is_detached(t) { default } else { t })
+                    .collect()
+            }
+            None => T::textures(mat).collect(),
+        };
+
+        let has_tex = textures.iter().any(|t| {
             !tex_storage
-                .get(t)
+                .get(*t)
                 .map_or(false, |tex| B::unwrap_texture(tex).is_some())
         });
         if has_tex {
@@ -290,12 +302,12 @@ impl<B: Backend, T: for<'a> StaticTextureSet<'a>> MaterialSub<B, T> {
         unsafe {
             let set = set.raw();
 
-            let tex_descs = T::textures(mat).enumerate().map(|(i, t)| {
+            let tex_descs = textures.iter().enumerate().map(|(i, t)| {
                 desc_write(
                     set,
                     (i + 1) as u32,
                     texture_desc(
-                        tex_storage.get(t).unwrap(),
+                        tex_storage.get(*t).unwrap(),
                         hal::image::Layout::ShaderReadOnlyOptimal,
                     )
                     .unwrap(),
@@ -14,7 +14,9 @@ use rendy::{
 use thread_profiler::profile_scope;
 
 use crate::{
+    lod::MeshBounds,
     mtl::Material,
+    picking::PickingMeshes,
     types::{Backend, Mesh, MeshData, Texture, TextureData},
 };
 
@@ -146,6 +148,8 @@ impl<B: Backend> System for MeshProcessorSystem<B> {
             SystemBuilder::new("MeshProcessorSystem")
                 .write_resource::<ProcessingQueue<MeshData>>()
                 .write_resource::<AssetStorage<Mesh>>()
+                .write_resource::<PickingMeshes>()
+                .write_resource::<MeshBounds>()
                 .read_resource::<QueueId>()
                 .read_resource::<Factory<B>>()
                 .build(
@@ -154,24 +158,39 @@ impl<B: Backend> System for MeshProcessorSystem<B> {
                           (
                         processing_queue,
                         mesh_storage,
+                        picking_meshes,
+                        mesh_bounds,
                         queue_id,
                         /* time, pool, */ factory,
                     ),
                           _| {
                         #[cfg(feature = "profiler")]
                         profile_scope!("mesh_processor");
-                        processing_queue.process(mesh_storage, |b, _, _| {
+                        processing_queue.process(mesh_storage, |b, _, handle| {
                             log::trace!("Processing Mesh: {:?}", b);
 
                             #[cfg(feature = "profiler")]
                             profile_scope!("process_mesh");
 
-                            b.0.build(**queue_id, factory)
+                            let MeshData(builder, triangles) = b;
+                            if let Some(triangles) = triangles {
+                                if let Some(sphere) = triangles.bounding_sphere() {
+                                    mesh_bounds.insert(*handle, sphere);
+                                }
+                                picking_meshes.insert(*handle, triangles);
+                            }
+                            builder
+                                .build(**queue_id, factory)
                                 .map(B::wrap_mesh)
                                 .map(ProcessingState::Loaded)
                                 .map_err(|e| e.into())
                         });
-                        mesh_storage.process_custom_drop(|_| {});
+                        let mut freed = false;
+                        mesh_storage.process_custom_drop(|_| freed = true);
+                        if freed {
+                            picking_meshes.retain_stored(mesh_storage);
+                            mesh_bounds.retain_stored(mesh_storage);
+                        }
                     },
                 ),
         )
@@ -7,12 +7,17 @@ use amethyst_assets::{
         serde_diff::{ApplyContext, DiffContext},
         SerdeDiff,
     },
-    Asset,
+    Asset, AssetSize,
 };
+use amethyst_core::ecs::Resources;
 use serde::{Deserialize, Serialize};
 use type_uuid::TypeUuid;
 
-use crate::system::{MeshProcessorSystem, TextureProcessorSystem};
+use crate::{
+    lod::MeshBounds,
+    picking::{MeshTriangles, PickingMeshes},
+    system::{MeshProcessorSystem, TextureProcessorSystem},
+};
 
 /// Extension of the rendy Backend trait.
 pub trait Backend: rendy::hal::Backend {
@@ -217,9 +222,15 @@ impl Backend for rendy::empty::Backend {
     }
 }
 
-amethyst_assets::register_asset_type!(MeshData => Mesh; MeshProcessorSystem<DefaultBackend>);
+amethyst_assets::register_asset_type!(MeshData => Mesh; MeshProcessorSystem<DefaultBackend>; create_mesh_resources);
 amethyst_assets::register_asset_type!(TextureData => Texture; TextureProcessorSystem<DefaultBackend>);
 
+/// Inserts the resources the `MeshProcessorSystem` keeps data of processed meshes in.
+fn create_mesh_resources(resources: &mut Resources) {
+    resources.get_or_insert_with(PickingMeshes::default);
+    resources.get_or_insert_with(MeshBounds::default);
+}
+
 impl Asset for Mesh {
     fn name() -> &'static str {
         "Mesh"
@@ -234,13 +245,84 @@ impl Asset for Texture {
     type Data = TextureData;
 }
 
+impl AssetSize for Mesh {
+    fn size_bytes(&self) -> usize {
+        match self {
+            #[cfg(target_os = "macos")]
+            Mesh::Metal(mesh) => mesh_size_bytes(mesh),
+            #[cfg(all(not(target_os = "macos"), not(feature = "empty")))]
+            Mesh::Vulkan(mesh) => mesh_size_bytes(mesh),
+            #[cfg(feature = "empty")]
+            Mesh::Empty(mesh) => mesh_size_bytes(mesh),
+        }
+    }
+}
+
+/// Approximates the GPU memory of a mesh from the number of vertices or indices it draws,
+/// assuming a `PosNormTangTex` vertex for each of them.
+fn mesh_size_bytes<B: rendy::hal::Backend>(mesh: &rendy::mesh::Mesh<B>) -> usize {
+    mesh.len() as usize * std::mem::size_of::<rendy::mesh::PosNormTangTex>()
+}
+
+impl AssetSize for Texture {
+    fn size_bytes(&self) -> usize {
+        match self {
+            #[cfg(target_os = "macos")]
+            Texture::Metal(texture) => texture_size_bytes(texture),
+            #[cfg(all(not(target_os = "macos"), not(feature = "empty")))]
+            Texture::Vulkan(texture) => texture_size_bytes(texture),
+            #[cfg(feature = "empty")]
+            Texture::Empty(texture) => texture_size_bytes(texture),
+        }
+    }
+}
+
+/// Approximates the GPU memory of a texture from its extent, layers, format and mip levels.
+fn texture_size_bytes<B: rendy::hal::Backend>(texture: &rendy::texture::Texture<B>) -> usize {
+    let info = texture.image().info();
+    let extent = info.kind.extent();
+    let bits_per_texel = info.format.surface_desc().bits as usize;
+    let base_level = extent.width as usize
+        * extent.height as usize
+        * extent.depth as usize
+        * info.kind.num_layers() as usize
+        * bits_per_texel
+        / 8;
+    // A full mip chain adds up to a third of the base level.
+    if info.levels > 1 {
+        base_level + base_level / 3
+    } else {
+        base_level
+    }
+}
+
 /// Newtype for MeshBuilder prefab usage.
+///
+/// Optionally carries a copy of the triangles of the mesh, which is kept for picking if
+/// `PickingMeshes` is enabled. Create it from a `MeshBuilder` with `From`.
+///
+/// The triangles are part of the serialized data, so importers producing `MeshData` have to bump
+/// their version when this layout changes.
 #[derive(Debug, Clone, Serialize, Deserialize, TypeUuid)]
 #[uuid = "c5870fe0-1733-4fb4-827c-4353f8c6002d"]
 pub struct MeshData(
     #[serde(deserialize_with = "deserialize_data")] pub rendy::mesh::MeshBuilder<'static>,
+    pub(crate) Option<MeshTriangles>,
 );
 
+impl MeshData {
+    /// Attaches a copy of the triangles of the mesh.
+    pub fn with_triangles(mut self, triangles: MeshTriangles) -> Self {
+        self.1 = Some(triangles);
+        self
+    }
+
+    /// Returns the copy of the triangles of the mesh, if it has one.
+    pub fn triangles(&self) -> Option<&MeshTriangles> {
+        self.1.as_ref()
+    }
+}
+
 /// Newtype for TextureBuilder prefab usage.
 #[derive(Debug, Clone, Serialize, Deserialize, TypeUuid)]
 #[uuid = "25063afd-6cc0-487e-982f-a63fed7d7393"]
@@ -248,7 +330,7 @@ pub struct TextureData(pub rendy::texture::TextureBuilder<'static>);
 
 impl From<rendy::mesh::MeshBuilder<'static>> for MeshData {
     fn from(builder: rendy::mesh::MeshBuilder<'static>) -> Self {
-        Self(builder)
+        Self(builder, None)
     }
 }
 
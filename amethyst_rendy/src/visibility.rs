@@ -1,9 +1,10 @@
 //! Transparency, visibility sorting and camera centroid culling for 3D Meshes.
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::HashMap};
 
 use amethyst_core::{
     ecs::{systems::ParallelRunnable, *},
-    math::{convert, distance_squared, Matrix4, Point3, Vector4},
+    geometry::{self, Sphere},
+    math::{convert, distance_squared, Matrix4, Point3, Vector3, Vector4},
     transform::Transform,
     Hidden, HiddenPropagate,
 };
@@ -14,6 +15,7 @@ use thread_profiler::profile_scope;
 
 use crate::{
     camera::{ActiveCamera, Camera},
+    spatial::{world_bounding_sphere, SpatialIndex},
     transparent::Transparent,
 };
 
@@ -25,6 +27,11 @@ pub struct Visibility {
     pub visible_unordered: IndexSet<Entity>,
     /// Visible entities that need to be drawn in the given order
     pub visible_ordered: Vec<Entity>,
+    /// The camera the visible entities were determined for
+    pub camera: Option<Entity>,
+    /// Fraction of the screen height covered by the projected bounding sphere of every visible
+    /// entity
+    pub screen_coverage: HashMap<Entity, f32>,
 }
 
 /// Defines a object's bounding sphere used by frustum culling.
@@ -58,6 +65,11 @@ impl BoundingSphere {
             radius,
         }
     }
+
+    /// Returns the bounding sphere as a `Sphere` for intersection tests.
+    pub fn to_sphere(&self) -> Sphere<f32> {
+        Sphere::new(self.center, self.radius)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -71,8 +83,10 @@ struct Internals {
 /// Determine what entities are visible to the camera, and which are not. Will also sort transparent
 /// entities back to front based on distance from camera.
 ///
-/// Note that this should run after `Transform` has been updated for the current frame, and
-/// before rendering occurs.
+/// Entities are culled against the [SpatialIndex] resource, so this should run after the
+/// [SpatialIndexSystem](crate::spatial::SpatialIndexSystem) has updated it for the current frame,
+/// and before rendering occurs. Without a `SpatialIndexSystem`, every entity with a `Transform`
+/// is tested instead.
 #[derive(Default, Debug)]
 pub struct VisibilitySortingSystem {
     centroids: Vec<Internals>,
@@ -84,86 +98,92 @@ impl System for VisibilitySortingSystem {
         Box::new(
             SystemBuilder::new("VisibilitySortingSystem")
                 .read_resource::<ActiveCamera>()
+                .read_resource::<SpatialIndex>()
                 .write_resource::<Visibility>()
-                .with_query(<(&Camera, &Transform)>::query())
                 .with_query(<(Entity, &Camera, &Transform)>::query())
-                .with_query(
-                    <(
-                        Entity,
-                        &Transform,
-                        Option<&Transparent>,
-                        Option<&BoundingSphere>,
-                    )>::query()
-                    .filter(!component::<Hidden>() & !component::<HiddenPropagate>()),
-                )
+                .with_query(<(Entity, &Camera, &Transform)>::query())
+                .with_query(<(Entity, &Transform, Option<&BoundingSphere>)>::query())
+                .read_component::<Transparent>()
+                .read_component::<Hidden>()
+                .read_component::<HiddenPropagate>()
                 .build(
                     move |commands,
                           world,
-                          (active_camera, visibility),
-                          (camera_query1, camera_query2, entity_query)| {
+                          (active_camera, index, visibility),
+                          (camera_query1, camera_query2, bounds_query)| {
                         #[cfg(feature = "profiler")]
                         profile_scope!("visibility_sorting_system");
 
                         visibility.visible_unordered.clear();
                         visibility.visible_ordered.clear();
+                        visibility.screen_coverage.clear();
+                        visibility.camera = None;
                         self.transparent.clear();
                         self.centroids.clear();
 
                         let origin = Point3::origin();
 
-                        let (camera, camera_transform) = match active_camera.entity.map_or_else(
-                            || camera_query1.iter(world).next(),
-                            |e| {
-                                camera_query2
-                                    .iter(world)
-                                    .find(|(camera_entity, _, _)| **camera_entity == e)
-                                    .map(|(_entity, camera, camera_transform)| {
-                                        (camera, camera_transform)
-                                    })
-                            },
-                        ) {
-                            Some(r) => r,
-                            None => return,
-                        };
+                        let (camera_entity, camera, camera_transform) =
+                            match active_camera.entity.map_or_else(
+                                || camera_query1.iter(world).next(),
+                                |e| {
+                                    camera_query2
+                                        .iter(world)
+                                        .find(|(camera_entity, _, _)| **camera_entity == e)
+                                },
+                            ) {
+                                Some(r) => r,
+                                None => return,
+                            };
+                        visibility.camera = Some(*camera_entity);
 
                         let camera_centroid =
                             camera_transform.global_matrix().transform_point(&origin);
-                        let frustum = Frustum::new(
-                            convert::<_, Matrix4<f32>>(camera.matrix)
-                                * camera_transform.global_matrix().try_inverse().unwrap(),
-                        );
+                        let projection = convert::<_, Matrix4<f32>>(camera.matrix);
+                        let view_projection =
+                            projection * camera_transform.global_matrix().try_inverse().unwrap();
+                        let frustum = geometry::Frustum::new(&view_projection);
+                        // Scale from a radius in view space to normalized device coordinates,
+                        // where the height of the screen is 2.
+                        let vertical_scale = projection[(1, 1)].abs();
 
-                        self.centroids.extend(
-                            entity_query
-                                .iter(world)
-                                .map(|(entity, transform, transparent, sphere)| {
-                                    let pos = sphere.map_or(origin, |s| s.center);
-                                    let matrix = transform.global_matrix();
-                                    (
-                                        *entity,
-                                        transparent.is_some(),
-                                        matrix.transform_point(&pos),
-                                        sphere.map_or(1.0, |s| s.radius)
-                                            * matrix[(0, 0)]
-                                                .max(matrix[(1, 1)])
-                                                .max(matrix[(2, 2)]),
-                                    )
-                                })
-                                .filter(|(_, _, centroid, radius)| {
-                                    frustum.check_sphere(centroid, *radius)
-                                })
-                                .map(|(entity, transparent, centroid, _)| {
-                                    Internals {
-                                        entity,
-                                        transparent,
-                                        centroid,
-                                        camera_distance: distance_squared(
-                                            &centroid,
-                                            &camera_centroid,
-                                        ),
-                                    }
-                                }),
-                        );
+                        let centroids = &mut self.centroids;
+                        let screen_coverage = &mut visibility.screen_coverage;
+                        let mut visit = |entity: Entity, sphere: &BoundingSphere| {
+                            if !frustum.intersects_sphere(&sphere.to_sphere()) {
+                                return;
+                            }
+                            // The index can still contain entities deleted this frame.
+                            let entry = match world.entry_ref(entity) {
+                                Ok(entry) => entry,
+                                Err(_) => return,
+                            };
+                            if entry.get_component::<Hidden>().is_ok()
+                                || entry.get_component::<HiddenPropagate>().is_ok()
+                            {
+                                return;
+                            }
+                            let w = (view_projection * sphere.center.to_homogeneous()).w;
+                            let coverage = if w > f32::EPSILON {
+                                sphere.radius * vertical_scale / w
+                            } else {
+                                f32::INFINITY
+                            };
+                            screen_coverage.insert(entity, coverage);
+                            centroids.push(Internals {
+                                entity,
+                                transparent: entry.get_component::<Transparent>().is_ok(),
+                                centroid: sphere.center,
+                                camera_distance: distance_squared(&sphere.center, &camera_centroid),
+                            });
+                        };
+                        if index.maintained {
+                            index.query(|bounds| frustum.intersects_aabb(bounds), visit);
+                        } else {
+                            for (entity, transform, sphere) in bounds_query.iter(world) {
+                                visit(*entity, &world_bounding_sphere(transform, sphere));
+                            }
+                        }
 
                         self.transparent
                             .extend(self.centroids.iter().filter(|c| c.transparent).cloned());
@@ -191,12 +211,17 @@ impl System for VisibilitySortingSystem {
 }
 
 /// Simple view Frustum implementation
+#[deprecated(
+    since = "0.16.0",
+    note = "use `amethyst_core::geometry::Frustum` instead"
+)]
 #[derive(Debug)]
 pub struct Frustum {
     /// The planes of the frustum
     pub planes: [Vector4<f32>; 6],
 }
 
+#[allow(deprecated)]
 impl Frustum {
     /// Create a new simple frustum from the provided matrix.
     pub fn new(matrix: Matrix4<f32>) -> Self {
@@ -221,6 +246,10 @@ impl Frustum {
     }
 
     /// Check if the given sphere is within the Frustum
+    #[deprecated(
+        since = "0.16.0",
+        note = "use `amethyst_core::geometry::Frustum::intersects_sphere` instead"
+    )]
     pub fn check_sphere(&self, center: &Point3<f32>, radius: f32) -> bool {
         for plane in &self.planes {
             if plane.xyz().dot(&center.coords) + plane.w <= -radius {
@@ -229,4 +258,26 @@ impl Frustum {
         }
         true
     }
+
+    /// Check if the axis aligned box from `min` to `max` is at least partially within the
+    /// Frustum. This is conservative: boxes close to a corner of the frustum can pass the check
+    /// without being visible.
+    #[deprecated(
+        since = "0.16.0",
+        note = "use `amethyst_core::geometry::Frustum::intersects_aabb` instead"
+    )]
+    pub fn check_aabb(&self, min: &Point3<f32>, max: &Point3<f32>) -> bool {
+        for plane in &self.planes {
+            // The corner of the box furthest along the plane normal.
+            let corner = Vector3::new(
+                if plane.x >= 0.0 { max.x } else { min.x },
+                if plane.y >= 0.0 { max.y } else { min.y },
+                if plane.z >= 0.0 { max.z } else { min.z },
+            );
+            if plane.xyz().dot(&corner) + plane.w < 0.0 {
+                return false;
+            }
+        }
+        true
+    }
 }
@@ -1,13 +1,13 @@
-use std::{any::Any, marker::PhantomData, panic, path::PathBuf, sync::Mutex};
+use std::{any::Any, marker::PhantomData, panic, path::PathBuf, sync::Mutex, time::Duration};
 
 use amethyst::{
     self,
-    core::{transform::TransformBundle, EventReader, RunNowDesc, SystemBundle},
+    assets::{LoaderBundle, Source, Sources},
+    core::{dispatcher::ThreadLocalSystem, transform::TransformBundle, EventReader},
     ecs::*,
     error::Error,
-    input::{BindingTypes, InputBundle},
+    input::InputBundle,
     prelude::*,
-    shred::Resource,
     ui::UiBundle,
     utils::application_root_dir,
     window::ScreenDimensions,
@@ -16,30 +16,11 @@ use amethyst::{
 use derivative::Derivative;
 use lazy_static::lazy_static;
 
-use crate::{
-    CustomDispatcherStateBuilder, FunctionState, GameUpdate, SequencerState,
-    SystemDescInjectionBundle, SystemInjectionBundle, ThreadLocalInjectionBundle,
-};
+use crate::{CustomDispatcherStateBuilder, FunctionState, GameUpdate, SequencerState};
 
-type BundleAddFn = Box<
-    dyn FnOnce(
-        DispatcherBuilder<'static, 'static>,
-    ) -> Result<DispatcherBuilder<'static, 'static>, Error>,
->;
-// Hack: Ideally we want a `SendBoxFnOnce`. However implementing it got too crazy:
-//
-// * When taking in `ApplicationBuilder<StateLocal>` as a parameter, I couldn't get the type
-//   parameters to be happy. `StateLocal` had to change depending on the first state, but it
-//   couldn't be consolidated with `T`.
-// * When using `SendBoxFnOnce<'w, (&'w mut World,)>`, the lifetime parameter for the function and
-//   the `World` could not agree &mdash; you can't coerce a `SendBoxFnOnce<'longer>` into a
-//   `SendBoxFnOnce<'shorter>`, which was necessary to indicate the length of the borrow of `World`
-//   for the function is not the `'w` needed to store the function in `AmethystApplication`.
-//   In addition, it requires the `World` (and hence the `ApplicationBuilder`) to be instantiated
-//   in a scope greater than the `AmethystApplication`'s lifetime, which detracts from the
-//   ergonomics of this test harness.
-type FnResourceAdd = Box<dyn FnMut(&mut World) + Send>;
-type FnSetup = Box<dyn FnOnce(&mut World) + Send>;
+type BundleAddFn = Box<dyn FnOnce(&mut DispatcherBuilder)>;
+type FnResourceAdd = Box<dyn FnOnce(&mut Resources)>;
+type FnSetup = Box<dyn FnOnce(&mut World, &mut Resources)>;
 type FnState<T, E> = Box<dyn FnOnce() -> Box<dyn State<T, E>>>;
 
 /// Screen width used in predefined display configuration.
@@ -49,6 +30,10 @@ pub const SCREEN_HEIGHT: u32 = 600;
 /// The ratio between the backing framebuffer resolution and the window size in screen pixels.
 /// This is typically one for a normal display and two for a retina display.
 pub const HIDPI: f64 = 1.;
+/// Duration of every frame of the application, fed to `Time` by a virtual clock.
+///
+/// This matches the default fixed update rate, so fixed systems run once per frame.
+pub const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
 
 // Use a mutex to prevent multiple tests that use Rendy from running simultaneously:
 //
@@ -61,28 +46,25 @@ lazy_static! {
 ///
 /// This provides varying levels of setup so that users do not have to register common bundles.
 ///
+/// The application is stepped one frame at a time with a virtual clock, so every run sees the
+/// same `Time`, and it does not start the asset daemon. Assets can be loaded from sources
+/// registered with [with_source](#method.with_source).
+///
 /// # Type Parameters
 ///
 /// * `T`: Game data type that holds the common dispatcher.
 /// * `E`: Custom event type shared between states.
+/// * `R`: Event reader of the state events.
 #[derive(Derivative, Default)]
 #[derivative(Debug)]
 pub struct AmethystApplication<T, E, R>
 where
     E: Send + Sync + 'static,
 {
-    /// Functions to add bundles to the game data.
-    ///
-    /// This is necessary because `System`s are not `Send`, and so we cannot send `DispatcherBuilder`
-    /// across a thread boundary, necessary to run the `Application` in a sub thread to avoid a
-    /// segfault caused by mesa and the software GL renderer.
+    /// Functions to add bundles and systems to the dispatcher.
     #[derivative(Debug = "ignore")]
     bundle_add_fns: Vec<BundleAddFn>,
-    /// Functions to add bundles to the game data.
-    ///
-    /// This is necessary because `System`s are not `Send`, and so we cannot send `DispatcherBuilder`
-    /// across a thread boundary, necessary to run the `Application` in a sub thread to avoid a
-    /// segfault caused by mesa and the software GL renderer.
+    /// Functions to add resources, run before the dispatcher is built.
     #[derivative(Debug = "ignore")]
     resource_add_fns: Vec<FnResourceAdd>,
     /// Setup functions to run, in user specified order.
@@ -95,10 +77,9 @@ where
     state_data: PhantomData<(T, E, R)>,
 }
 
-impl AmethystApplication<GameData<'static, 'static>, StateEvent, StateEventReader> {
+impl AmethystApplication<GameData, StateEvent, StateEventReader> {
     /// Returns an Amethyst application without any bundles.
-    pub fn blank() -> AmethystApplication<GameData<'static, 'static>, StateEvent, StateEventReader>
-    {
+    pub fn blank() -> AmethystApplication<GameData, StateEvent, StateEventReader> {
         AmethystApplication {
             bundle_add_fns: Vec::new(),
             resource_add_fns: Vec::new(),
@@ -108,15 +89,15 @@ impl AmethystApplication<GameData<'static, 'static>, StateEvent, StateEventReade
         }
     }
 
-    /// Returns an application with the Transform, Input, and UI bundles.
+    /// Returns an application with the Loader, Transform, Input, and UI bundles.
     ///
-    /// This also adds a `ScreenDimensions` resource to the `World` so that UI calculations can be
-    /// done.
-    pub fn ui_base<B: BindingTypes>(
-    ) -> AmethystApplication<GameData<'static, 'static>, StateEvent, StateEventReader> {
+    /// This also adds a `ScreenDimensions` resource to the `Resources` so that UI calculations
+    /// can be done.
+    pub fn ui_base() -> AmethystApplication<GameData, StateEvent, StateEventReader> {
         AmethystApplication::blank()
-            .add_bundle(TransformBundle::new())
-            .with_ui_bundles::<B>()
+            .add_bundle(LoaderBundle)
+            .add_bundle(TransformBundle)
+            .with_ui_bundles()
             .with_resource(ScreenDimensions::new(SCREEN_WIDTH, SCREEN_HEIGHT))
     }
 
@@ -126,137 +107,79 @@ impl AmethystApplication<GameData<'static, 'static>, StateEvent, StateEventReade
     }
 }
 
-impl<E, R> AmethystApplication<GameData<'static, 'static>, E, R>
+impl<E, R> AmethystApplication<GameData, E, R>
 where
     E: Clone + Send + Sync + 'static,
-    R: Default,
+    R: EventReader<Event = E> + Default + 'static,
 {
     /// Returns the built Application.
     ///
     /// If you are intending to run the `Application`, you can use the `run()` or `run_isolated()`
     /// methods instead.
-    pub fn build(self) -> Result<CoreApplication<'static, GameData<'static, 'static>, E, R>, Error>
-    where
-        for<'b> R: EventReader<'b, Event = E>,
-    {
-        let params = (
-            self.bundle_add_fns,
-            self.resource_add_fns,
-            self.setup_fns,
-            self.state_fns,
-        );
-        Self::build_internal(params)
-    }
+    pub fn build(self) -> Result<CoreApplication<'static, GameData, E, R>, Error> {
+        let mut dispatcher_builder = DispatcherBuilder::default();
+        for function in self.bundle_add_fns {
+            function(&mut dispatcher_builder);
+        }
 
-    // Hack to get around `S` or `T` not being `Send`
-    // We take a function that constructs `S`, and the function itself is `Send`.
-    // However, `Self` has `PhantomData<T>`, which means we cannot send `self` to a thread. Instead
-    // we have to take all of the other fields and send those through.
-    //
-    // Need to `#[allow(clippy::type_complexity)]` because the type declaration would have unused type
-    // parameters which causes a compilation failure.
-    #[allow(unknown_lints, clippy::type_complexity)]
-    fn build_internal(
-        (bundle_add_fns, resource_add_fns, setup_fns, state_fns): (
-            Vec<BundleAddFn>,
-            Vec<FnResourceAdd>,
-            Vec<FnSetup>,
-            Vec<FnState<GameData<'static, 'static>, E>>,
-        ),
-    ) -> Result<CoreApplication<'static, GameData<'static, 'static>, E, R>, Error>
-    where
-        for<'b> R: EventReader<'b, Event = E>,
-    {
-        let mut game_data = bundle_add_fns.into_iter().fold(
-            Ok(DispatcherBuilder::default()),
-            |game_data: Result<DispatcherBuilder<'_, '_>, Error>, function: BundleAddFn| {
-                game_data.and_then(function)
-            },
-        )?;
-
-        let mut states = Vec::<Box<dyn State<GameData<'static, 'static>, E>>>::new();
-        state_fns
+        let states = self
+            .state_fns
             .into_iter()
             .rev()
-            .for_each(|state_fn| states.push(state_fn()));
-        Self::build_application(
-            SequencerState::new(states),
-            game_data,
-            resource_add_fns,
-            setup_fns,
-        )
-    }
+            .map(|state_fn| state_fn())
+            .collect::<Vec<Box<dyn State<GameData, E>>>>();
 
-    fn build_application<S>(
-        first_state: S,
-        game_data: DispatcherBuilder<'static, 'static>,
-        resource_add_fns: Vec<FnResourceAdd>,
-        setup_fns: Vec<FnSetup>,
-    ) -> Result<CoreApplication<'static, GameData<'static, 'static>, E, R>, Error>
-    where
-        S: State<GameData<'static, 'static>, E> + 'static,
-        for<'b> R: EventReader<'b, Event = E>,
-    {
         let assets_dir =
             AmethystApplication::assets_dir().expect("Failed to get default assets dir.");
-        let mut application_builder = CoreApplication::build(assets_dir, first_state)?;
+        let mut application_builder =
+            CoreApplication::build(assets_dir, SequencerState::new(states))?
+                .with_virtual_clock(FRAME_DURATION);
+        #[cfg(feature = "asset-daemon")]
         {
-            let world = &mut application_builder.world;
-            for mut function in resource_add_fns {
-                function(world);
-            }
-            for function in setup_fns {
-                function(world);
-            }
+            application_builder = application_builder.with_asset_daemon(false);
         }
-        application_builder.build(game_data)
+        for function in self.resource_add_fns {
+            function(&mut application_builder.resources);
+        }
+        for function in self.setup_fns {
+            function(
+                &mut application_builder.world,
+                &mut application_builder.resources,
+            );
+        }
+        application_builder.build(dispatcher_builder)
     }
 
     /// Runs the application and returns `Ok(())` if nothing went wrong.
-    pub fn run(self) -> Result<(), Error>
-    where
-        for<'b> R: EventReader<'b, Event = E>,
-    {
-        let params = (
-            self.bundle_add_fns,
-            self.resource_add_fns,
-            self.setup_fns,
-            self.state_fns,
-        );
-
+    pub fn run(self) -> Result<(), Error> {
         // `CoreApplication` is `!UnwindSafe`, but wrapping it in a `Mutex` allows us to
         // recover from a panic.
-        let application = Mutex::new(Self::build_internal(params)?);
+        let application = Mutex::new(self.build()?);
         panic::catch_unwind(move || {
-            application
+            let mut application = application
                 .lock()
-                .expect("Expected to get application lock")
-                .run()
+                .expect("Expected to get application lock");
+            while application.step() {}
         })
         .map_err(Self::box_any_to_error)
     }
 
-    /// Run the application in a sub thread.
+    /// Runs the application while no other isolated application is running.
     ///
     /// Historically this has been used for the following reasons:
     ///
     /// * To avoid segmentation faults using [X and mesa][mesa].
     /// * To avoid multiple threads sharing the same memory in [Vulkan][vulkan].
     ///
-    /// This must **NOT** be used when including the `AudioBundle` on Windows, as it causes a
-    /// [segfault][audio].
-    ///
     /// [mesa]: <https://github.com/rust-windowing/glutin/issues/1038>
     /// [vulkan]: <https://github.com/amethyst/rendy/issues/151>
-    /// [audio]: <https://github.com/amethyst/amethyst/issues/1595>
-    pub fn run_isolated(self) -> Result<(), Error>
-    where
-        for<'b> R: EventReader<'b, Event = E>,
-    {
+    pub fn run_isolated(self) -> Result<(), Error> {
         // Acquire a lock due to memory access issues when using Rendy:
         //
         // See: <https://github.com/amethyst/rendy/issues/151>
-        let _guard = RENDY_MEMORY_MUTEX.lock().unwrap();
+        let _guard = RENDY_MEMORY_MUTEX
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
 
         self.run()
     }
@@ -296,7 +219,7 @@ where
     pub fn with_custom_event_type<Evt, Rdr>(self) -> AmethystApplication<T, Evt, Rdr>
     where
         Evt: Send + Sync + 'static,
-        for<'b> Rdr: EventReader<'b, Event = Evt>,
+        Rdr: EventReader<Event = Evt>,
     {
         if !self.state_fns.is_empty() {
             panic!(
@@ -321,78 +244,57 @@ where
     /// * `bundle`: Bundle to add.
     pub fn add_bundle<B>(mut self, bundle: B) -> Self
     where
-        B: SystemBundle<'static, 'static> + Send + 'static,
+        B: SystemBundle + 'static,
     {
-        // We need to use `SendBoxFnOnce` because:
-        //
-        // * `FnOnce` takes itself by value when you call it.
-        // * To pass a `FnOnce` around (transferring ownership), it must be boxed, since it's not
-        //   `Sized`.
-        // * A `Box<FnOnce()>` is a `Sized` type with a reference to the `FnOnce`
-        // * To call the function inside the `Box<FnOnce()>`, it must be moved out of the box
-        //   because we need to own the `FnOnce` to be able to call it by value, whereas the `Box`
-        //   only holds the reference.
-        // * To own it, we would have to move it onto the stack.
-        // * However, since it's not `Sized`, we can't do that.
-        //
-        // To make this work, we can implement a trait for `FnOnce` with a trait function which
-        // takes `Box<Self>` and can invoke the `FnOnce` whilst inside the Box.
-        // `SendBoxFnOnce` is an implementation of this.
-        //
-        // See <https://users.rust-lang.org/t/move-a-boxed-function-inside-a-closure/18199>
-        self.bundle_add_fns.push(Box::new(
-            |game_data: DispatcherBuilder<'static, 'static>| game_data.add_bundle(bundle),
-        ));
+        self.bundle_add_fns
+            .push(Box::new(move |builder: &mut DispatcherBuilder| {
+                builder.add_bundle(bundle);
+            }));
         self
     }
 
-    /// Adds a bundle to the list of bundles.
-    ///
-    /// This provides an alternative to `.add_bundle(B)` where `B` is `!Send`. The function that
-    /// instantiates the bundle must be `Send`.
+    /// Registers `InputBundle` and `UiBundle` with this application.
+    pub fn with_ui_bundles(self) -> Self {
+        self.add_bundle(InputBundle::new())
+            .add_bundle(UiBundle::<u32>::new())
+    }
+
+    /// Adds a resource to the `Resources`.
     ///
     /// # Parameters
     ///
-    /// * `bundle_function`: Function to instantiate the Bundle.
-    pub fn add_bundle_fn<FnBundle, B>(mut self, bundle_function: FnBundle) -> Self
+    /// * `resource`: Resource to add.
+    pub fn with_resource<Res>(mut self, resource: Res) -> Self
     where
-        FnBundle: FnOnce() -> B + Send + 'static,
-        B: SystemBundle<'static, 'static> + 'static,
+        Res: Resource,
     {
-        self.bundle_add_fns.push(Box::new(
-            move |game_data: DispatcherBuilder<'static, 'static>| {
-                game_data.add_bundle(bundle_function())
-            },
-        ));
+        self.resource_add_fns
+            .push(Box::new(move |resources: &mut Resources| {
+                resources.insert(resource);
+            }));
         self
     }
 
-    /// Registers `InputBundle` and `UiBundle` with this application.
-    ///
-    /// # Type Parameters
+    /// Registers an asset source that the `DefaultLoader` loads assets from with
+    /// `Loader::load_from`.
     ///
-    /// * `B`: Type representing the input binding types.
-    pub fn with_ui_bundles(self) -> Self {
-        self.add_bundle(InputBundle::new())
-            .add_bundle(UiBundle::new())
-    }
-
-    /// Adds a resource to the `World`.
+    /// The source is handed to the loader when the `LoaderBundle` is loaded.
     ///
     /// # Parameters
     ///
-    /// * `resource`: Bundle to add.
-    pub fn with_resource<Res>(mut self, resource: Res) -> Self
+    /// * `name`: Name to register the source with.
+    /// * `source`: The asset source, e.g. an `InMemorySource`.
+    pub fn with_source<N, S>(mut self, name: N, source: S) -> Self
     where
-        Res: Resource,
+        N: Into<String>,
+        S: Source,
     {
-        let mut resource_opt = Some(resource);
+        let name = name.into();
         self.resource_add_fns
-            .push(Box::new(move |world: &mut World| {
-                let resource = resource_opt.take();
-                if let Some(resource) = resource {
-                    world.insert(resource);
-                }
+            .push(Box::new(move |resources: &mut Resources| {
+                resources
+                    .get_mut_or_insert_with(Sources::default)
+                    .add(name, source);
             }));
         self
     }
@@ -405,7 +307,7 @@ where
     pub fn with_state<S, FnStateLocal>(mut self, state_fn: FnStateLocal) -> Self
     where
         S: State<T, E> + 'static,
-        FnStateLocal: FnOnce() -> S + Send + Sync + 'static,
+        FnStateLocal: FnOnce() -> S + 'static,
     {
         // Box up the state
         let closure = move || Box::new((state_fn)()) as Box<dyn State<T, E>>;
@@ -417,56 +319,32 @@ where
     ///
     /// # Parameters
     ///
-    /// * `system`: `System` to run.
-    /// * `name`: Name to register the system with, used for dependency ordering.
-    /// * `deps`: Names of systems that must run before this system.
-    pub fn with_system<S, N>(self, system: S, name: N, deps: &[N]) -> Self
-    where
-        S: for<'sys_local> System<'sys_local> + Send + 'static,
-        N: Into<String> + Clone,
-    {
-        let name = Into::<String>::into(name);
-        let deps = deps
-            .iter()
-            .map(Clone::clone)
-            .map(Into::<String>::into)
-            .collect::<Vec<String>>();
-        self.add_bundle_fn(move || SystemInjectionBundle::new(system, name, deps))
-    }
-
-    /// Registers a `System` into this application's `GameData`.
-    ///
-    /// # Parameters
-    ///
-    /// * `system_desc`: Descriptor to instantiate the `System`.
-    /// * `name`: Name to register the system with, used for dependency ordering.
-    /// * `deps`: Names of systems that must run before this system.
-    pub fn with_system_desc<SD, S, N>(self, system_desc: SD, name: N, deps: &[N]) -> Self
+    /// * `system`: `System` to run, e.g. a function returning a `ParallelRunnable`.
+    pub fn with_system<S>(mut self, system: S) -> Self
     where
-        SD: SystemDesc<'static, 'static, S> + Send + Sync + 'static,
-        S: for<'sys_local> System<'sys_local> + Send + 'static,
-        N: Into<String> + Clone,
+        S: System + 'static,
     {
-        let name = Into::<String>::into(name);
-        let deps = deps
-            .iter()
-            .map(Clone::clone)
-            .map(Into::<String>::into)
-            .collect::<Vec<String>>();
-        self.add_bundle_fn(move || SystemDescInjectionBundle::new(system_desc, name, deps))
+        self.bundle_add_fns
+            .push(Box::new(move |builder: &mut DispatcherBuilder| {
+                builder.add_system(system);
+            }));
+        self
     }
 
     /// Registers a thread local `System` into this application's `GameData`.
     ///
     /// # Parameters
     ///
-    /// * `run_now_desc`: Descriptor to instantiate the thread local system.
-    pub fn with_thread_local<RNDesc, RN>(self, run_now_desc: RNDesc) -> Self
+    /// * `system`: Thread local system to run, e.g. a function returning a `Runnable`.
+    pub fn with_thread_local<S>(mut self, system: S) -> Self
     where
-        RNDesc: RunNowDesc<'static, 'static, RN> + Send + Sync + 'static,
-        RN: for<'sys_local> RunNow<'sys_local> + Send + 'static,
+        S: ThreadLocalSystem<'static> + 'static,
     {
-        self.add_bundle_fn(move || ThreadLocalInjectionBundle::new(run_now_desc))
+        self.bundle_add_fns
+            .push(Box::new(move |builder: &mut DispatcherBuilder| {
+                builder.add_thread_local(system);
+            }));
+        self
     }
 
     /// Registers a `System` to run in a `CustomDispatcherState`.
@@ -477,51 +355,13 @@ where
     /// # Parameters
     ///
     /// * `system`: `System` to run.
-    /// * `name`: Name to register the system with, used for dependency ordering.
-    /// * `deps`: Names of systems that must run before this system.
-    pub fn with_system_single<S, N>(self, system: S, name: N, deps: &[N]) -> Self
-    where
-        S: for<'sys_local> System<'sys_local> + Send + Sync + 'static,
-        N: Into<String> + Clone,
-    {
-        let name = Into::<String>::into(name);
-        let deps = deps
-            .iter()
-            .map(Clone::clone)
-            .map(Into::<String>::into)
-            .collect::<Vec<String>>();
-        self.with_state(move || {
-            CustomDispatcherStateBuilder::new()
-                .with_system(system, name, deps)
-                .build()
-        })
-    }
-
-    /// Registers a `System` to run in a `CustomDispatcherState`.
-    ///
-    /// This will run the system once in a dedicated `State`, allowing you to inspect the effects of
-    /// the system after setting up the world to a desired state.
-    ///
-    /// # Parameters
-    ///
-    /// * `system_desc`: Descriptor to instantiate the `System`.
-    /// * `name`: Name to register the system with, used for dependency ordering.
-    /// * `deps`: Names of systems that must run before this system.
-    pub fn with_system_desc_single<SD, S, N>(self, system_desc: SD, name: N, deps: &[N]) -> Self
+    pub fn with_system_single<S>(self, system: S) -> Self
     where
-        SD: SystemDesc<'static, 'static, S> + Send + Sync + 'static,
-        S: for<'sys_local> System<'sys_local> + Send + Sync + 'static,
-        N: Into<String> + Clone,
+        S: System + 'static,
     {
-        let name = Into::<String>::into(name);
-        let deps = deps
-            .iter()
-            .map(Clone::clone)
-            .map(Into::<String>::into)
-            .collect::<Vec<String>>();
         self.with_state(move || {
             CustomDispatcherStateBuilder::new()
-                .with_system_desc(system_desc, name, deps)
+                .with_system(system)
                 .build()
         })
     }
@@ -533,21 +373,22 @@ where
     /// * `func`: Function to execute.
     pub fn with_fn<F>(self, func: F) -> Self
     where
-        F: FnOnce(&mut World) + Send + Sync + 'static,
+        F: FnOnce(&mut World, &mut Resources) + 'static,
     {
         self.with_state(move || FunctionState::new(func))
     }
 
-    /// Registers a function that sets up the `World`.
+    /// Registers a function that sets up the `World` and `Resources`.
     ///
-    /// This is an alias to `.with_fn(F)`.
+    /// Setup functions run before the dispatcher is built, so they can insert resources that
+    /// bundles and systems need.
     ///
     /// # Parameters
     ///
     /// * `setup_fn`: Function to execute.
     pub fn with_setup<F>(mut self, setup_fn: F) -> Self
     where
-        F: FnOnce(&mut World) + Send + 'static,
+        F: FnOnce(&mut World, &mut Resources) + 'static,
     {
         self.setup_fns.push(Box::new(setup_fn));
         self
@@ -562,7 +403,7 @@ where
     /// * `effect_fn`: Function that executes an effect.
     pub fn with_effect<F>(self, effect_fn: F) -> Self
     where
-        F: FnOnce(&mut World) + Send + Sync + 'static,
+        F: FnOnce(&mut World, &mut Resources) + 'static,
     {
         self.with_fn(effect_fn)
     }
@@ -576,7 +417,7 @@ where
     /// * `assertion_fn`: Function that asserts the expected state.
     pub fn with_assertion<F>(self, assertion_fn: F) -> Self
     where
-        F: FnOnce(&mut World) + Send + Sync + 'static,
+        F: FnOnce(&mut World, &mut Resources) + 'static,
     {
         self.with_fn(assertion_fn)
     }
@@ -584,21 +425,24 @@ where
 
 #[cfg(test)]
 mod test {
-    use std::marker::PhantomData;
+    use std::{marker::PhantomData, time::Duration};
 
     use amethyst::{
-        assets::{Asset, AssetStorage, DefaultLoader, Handle, Loader, ProcessingState, Processor},
-        core::{bundle::SystemBundle, SystemDesc},
-        derive::SystemDesc,
-        ecs::*,
+        assets::{
+            self as amethyst_assets, register_asset_type, Asset, AssetProcessorSystem,
+            AssetStorage, DefaultLoader, Handle, Loader, LoaderBundle, ProcessingQueue, TypeUuid,
+        },
+        core::Time,
+        ecs::{systems::Fetch, *},
         error::Error,
         prelude::*,
         ui::FontAsset,
         window::ScreenDimensions,
     };
+    use serde::{Deserialize, Serialize};
 
-    use super::AmethystApplication;
-    use crate::{EffectReturn, FunctionState, PopState};
+    use super::{AmethystApplication, FRAME_DURATION};
+    use crate::{EffectReturn, FunctionState, InMemorySource, PopState, IN_MEMORY_SOURCE_ID};
 
     #[test]
     fn bundle_build_is_ok() -> Result<(), Error> {
@@ -615,9 +459,9 @@ mod test {
 
     #[test]
     fn assertion_when_resource_is_added_succeeds() -> Result<(), Error> {
-        let assertion_fn = |world: &mut World| {
-            world.read_resource::<ApplicationResource>();
-            world.read_resource::<ApplicationResourceNonDefault>();
+        let assertion_fn = |_world: &mut World, resources: &mut Resources| {
+            fetch::<ApplicationResource>(resources);
+            fetch::<ApplicationResourceNonDefault>(resources);
         };
 
         AmethystApplication::blank()
@@ -628,11 +472,11 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "Tried to fetch resource of type `ApplicationResource`")]
+    #[should_panic(expected = "ApplicationResource` to exist.")]
     fn assertion_when_resource_is_not_added_should_panic() {
-        let assertion_fn = |world: &mut World| {
+        let assertion_fn = |_world: &mut World, resources: &mut Resources| {
             // Panics if `ApplicationResource` was not added.
-            world.read_resource::<ApplicationResource>();
+            fetch::<ApplicationResource>(resources);
         };
 
         AmethystApplication::blank()
@@ -645,8 +489,8 @@ mod test {
     #[test]
     fn assertion_switch_with_loading_state_with_add_resource_succeeds() -> Result<(), Error> {
         let state_fns = || {
-            let assertion_fn = |world: &mut World| {
-                world.read_resource::<LoadResource>();
+            let assertion_fn = |_world: &mut World, resources: &mut Resources| {
+                fetch::<LoadResource>(resources);
             };
 
             // Necessary if the State being tested is a loading state that returns `Trans::Switch`
@@ -662,8 +506,8 @@ mod test {
         // Alternative to embedding the `FunctionState` is to switch to a `PopState` but still
         // provide the assertion function
         let state_fns = || LoadingState::new(PopState);
-        let assertion_fn = |world: &mut World| {
-            world.read_resource::<LoadResource>();
+        let assertion_fn = |_world: &mut World, resources: &mut Resources| {
+            fetch::<LoadResource>(resources);
         };
 
         AmethystApplication::blank()
@@ -673,11 +517,11 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "Tried to fetch resource of type `LoadResource`")]
+    #[should_panic(expected = "LoadResource` to exist.")]
     fn assertion_switch_with_loading_state_without_add_resource_should_panic() {
         let state_fns = || {
-            let assertion_fn = |world: &mut World| {
-                world.read_resource::<LoadResource>();
+            let assertion_fn = |_world: &mut World, resources: &mut Resources| {
+                fetch::<LoadResource>(resources);
             };
 
             SwitchState::new(FunctionState::new(assertion_fn))
@@ -690,13 +534,13 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "Tried to fetch resource of type `LoadResource`")]
+    #[should_panic(expected = "LoadResource` to exist.")]
     fn assertion_push_with_loading_state_without_add_resource_should_panic() {
         // Alternative to embedding the `FunctionState` is to switch to a `PopState` but still
         // provide the assertion function
         let state_fns = || SwitchState::new(PopState);
-        let assertion_fn = |world: &mut World| {
-            world.read_resource::<LoadResource>();
+        let assertion_fn = |_world: &mut World, resources: &mut Resources| {
+            fetch::<LoadResource>(resources);
         };
 
         AmethystApplication::blank()
@@ -708,30 +552,24 @@ mod test {
 
     #[test]
     fn game_data_must_update_before_assertion() -> Result<(), Error> {
-        let effect_fn = |world: &mut World| {
+        let effect_fn = |_world: &mut World, resources: &mut Resources| {
             let handles = vec![
-                AssetZeroLoader::load(world, AssetZero(10)).unwrap(),
-                AssetZeroLoader::load(world, AssetZero(20)).unwrap(),
+                load_asset_zero(resources, AssetZero(10)),
+                load_asset_zero(resources, AssetZero(20)),
             ];
 
-            world.insert::<Vec<AssetZeroHandle>>(handles);
+            resources.insert::<Vec<AssetZeroHandle>>(handles);
         };
-        let assertion_fn = |world: &mut World| {
-            let asset_translation_zero_handles = world.read_resource::<Vec<AssetZeroHandle>>();
+        let assertion_fn = |_world: &mut World, resources: &mut Resources| {
+            let asset_zero_handles = fetch::<Vec<AssetZeroHandle>>(resources);
 
-            let store = world.read_resource::<AssetStorage<AssetZero>>();
-            assert_eq!(
-                Some(&AssetZero(10)),
-                store.get(&asset_translation_zero_handles[0])
-            );
-            assert_eq!(
-                Some(&AssetZero(20)),
-                store.get(&asset_translation_zero_handles[1])
-            );
+            let store = fetch::<AssetStorage<AssetZero>>(resources);
+            assert_eq!(Some(&AssetZero(10)), store.get(&asset_zero_handles[0]));
+            assert_eq!(Some(&AssetZero(20)), store.get(&asset_zero_handles[1]));
         };
 
         AmethystApplication::blank()
-            .add_bundle(BundleAsset)
+            .add_bundle(LoaderBundle)
             .with_effect(effect_fn)
             .with_assertion(assertion_fn)
             .run()
@@ -740,32 +578,31 @@ mod test {
     #[test]
     fn execution_order_is_setup_state_effect_assertion() -> Result<(), Error> {
         struct Setup;
-        let setup_fns = |world: &mut World| world.insert(Setup);
+        let setup_fns = |_world: &mut World, resources: &mut Resources| resources.insert(Setup);
         let state_fns = || {
-            LoadingState::new(FunctionState::new(|world: &mut World| {
-                // Panics if setup is not run before this.
-                world.read_resource::<Setup>();
-            }))
+            LoadingState::new(FunctionState::new(
+                |_world: &mut World, resources: &mut Resources| {
+                    // Panics if setup is not run before this.
+                    fetch::<Setup>(resources);
+                },
+            ))
         };
-        let effect_fn = |world: &mut World| {
+        let effect_fn = |_world: &mut World, resources: &mut Resources| {
             // If `LoadingState` is not run before this, this will panic
-            world.read_resource::<LoadResource>();
+            fetch::<LoadResource>(resources);
 
-            let handles = vec![AssetZeroLoader::load(world, AssetZero(10)).unwrap()];
-            world.insert(handles);
+            let handles = vec![load_asset_zero(resources, AssetZero(10))];
+            resources.insert(handles);
         };
-        let assertion_fn = |world: &mut World| {
-            let asset_translation_zero_handles = world.read_resource::<Vec<AssetZeroHandle>>();
+        let assertion_fn = |_world: &mut World, resources: &mut Resources| {
+            let asset_zero_handles = fetch::<Vec<AssetZeroHandle>>(resources);
 
-            let store = world.read_resource::<AssetStorage<AssetZero>>();
-            assert_eq!(
-                Some(&AssetZero(10)),
-                store.get(&asset_translation_zero_handles[0])
-            );
+            let store = fetch::<AssetStorage<AssetZero>>(resources);
+            assert_eq!(Some(&AssetZero(10)), store.get(&asset_zero_handles[0]));
         };
 
         AmethystApplication::blank()
-            .add_bundle(BundleAsset)
+            .add_bundle(LoaderBundle)
             .with_setup(setup_fns)
             .with_state(state_fns)
             .with_effect(effect_fn)
@@ -775,12 +612,12 @@ mod test {
 
     #[test]
     fn base_application_can_load_ui() -> Result<(), Error> {
-        let assertion_fn = |world: &mut World| {
-            // Next line would panic if `UiBundle` wasn't added.
-            world.read_resource::<AssetStorage<FontAsset>>();
-            // `.base()` should add `ScreenDimensions` as this is necessary for `UiBundle` to
+        let assertion_fn = |_world: &mut World, resources: &mut Resources| {
+            // Next line would panic if the asset storages were not set up.
+            fetch::<AssetStorage<FontAsset>>(resources);
+            // `.ui_base()` should add `ScreenDimensions` as this is necessary for `UiBundle` to
             // initialize properly.
-            world.read_resource::<ScreenDimensions>();
+            fetch::<ScreenDimensions>(resources);
         };
 
         AmethystApplication::ui_base()
@@ -790,76 +627,70 @@ mod test {
 
     #[test]
     fn with_system_runs_system_every_tick() -> Result<(), Error> {
-        let effect_fn = |world: &mut World| {
+        let effect_fn = |world: &mut World, resources: &mut Resources| {
             let entity = world.push((ComponentZero(0),));
 
-            world.insert(EffectReturn(entity));
-        };
-
-        fn get_component_zero_value(world: &mut World) -> i32 {
-            let entity = world.read_resource::<EffectReturn<Entity>>().0;
-
-            let component_zero_storage = world.read_storage::<ComponentZero>();
-            let component_zero = component_zero_storage
-                .get(entity)
-                .expect("Entity should have a `ComponentZero` component.");
-
-            component_zero.0
+            resources.insert(EffectReturn(entity));
         };
 
         AmethystApplication::blank()
-            .with_system(SystemEffect, "system_effect", &[])
+            .with_system(system_effect)
             .with_effect(effect_fn)
-            .with_assertion(|world| assert_eq!(1, get_component_zero_value(world)))
-            .with_assertion(|world| assert_eq!(2, get_component_zero_value(world)))
+            .with_assertion(|world, resources| {
+                assert_eq!(1, component_zero_value(world, resources))
+            })
+            .with_assertion(|world, resources| {
+                assert_eq!(2, component_zero_value(world, resources))
+            })
             .run()
     }
 
     #[test]
     fn with_system_invoked_twice_should_not_panic() {
         AmethystApplication::blank()
-            .with_system(SystemZero, "zero", &[])
-            .with_system(SystemOne, "one", &["zero"]);
+            .with_system(system_zero)
+            .with_system(system_one);
     }
 
     #[test]
     fn with_system_single_runs_system_once() -> Result<(), Error> {
-        let assertion_fn = |world: &mut World| {
-            let entity = world.read_resource::<EffectReturn<Entity>>().0;
-
-            let component_zero_storage = world.read_storage::<ComponentZero>();
-            let component_zero = component_zero_storage
-                .get(entity)
-                .expect("Entity should have a `ComponentZero` component.");
-
+        let assertion_fn = |world: &mut World, resources: &mut Resources| {
             // If the system ran, the value in the `ComponentZero` should be 1.
-            assert_eq!(1, component_zero.0);
+            assert_eq!(1, component_zero_value(world, resources));
         };
 
         AmethystApplication::blank()
-            .with_setup(|world| {
-                world.register::<ComponentZero>();
-
+            .with_setup(|world, resources| {
                 let entity = world.push((ComponentZero(0),));
-                world.insert(EffectReturn(entity));
+                resources.insert(EffectReturn(entity));
             })
-            .with_system_single(SystemEffect, "system_effect", &[])
+            .with_system_single(system_effect)
             .with_assertion(assertion_fn)
             .with_assertion(assertion_fn)
             .run()
     }
 
+    #[test]
+    fn with_thread_local_runs_system_every_tick() -> Result<(), Error> {
+        AmethystApplication::blank()
+            .with_resource(Counter(0))
+            .with_thread_local(system_count)
+            .with_assertion(|_world, resources| assert_eq!(1, fetch::<Counter>(resources).0))
+            .with_assertion(|_world, resources| assert_eq!(2, fetch::<Counter>(resources).0))
+            .run()
+    }
+
     // Double usage tests
     // If the second call panics, then the setup functions were not executed in the right order.
 
     #[test]
     fn with_setup_invoked_twice_should_run_in_specified_order() -> Result<(), Error> {
         AmethystApplication::blank()
-            .with_setup(|world| {
-                world.insert(ApplicationResource);
+            .with_setup(|_world, resources| {
+                resources.insert(ApplicationResource);
             })
-            .with_setup(|world| {
-                world.read_resource::<ApplicationResource>();
+            .with_setup(|_world, resources| {
+                fetch::<ApplicationResource>(resources);
             })
             .run()
     }
@@ -867,11 +698,11 @@ mod test {
     #[test]
     fn with_effect_invoked_twice_should_run_in_the_specified_order() -> Result<(), Error> {
         AmethystApplication::blank()
-            .with_effect(|world| {
-                world.insert(ApplicationResource);
+            .with_effect(|_world, resources| {
+                resources.insert(ApplicationResource);
             })
-            .with_effect(|world| {
-                world.read_resource::<ApplicationResource>();
+            .with_effect(|_world, resources| {
+                fetch::<ApplicationResource>(resources);
             })
             .run()
     }
@@ -879,11 +710,11 @@ mod test {
     #[test]
     fn with_assertion_invoked_twice_should_run_in_the_specified_order() -> Result<(), Error> {
         AmethystApplication::blank()
-            .with_assertion(|world| {
-                world.insert(ApplicationResource);
+            .with_assertion(|_world, resources| {
+                resources.insert(ApplicationResource);
             })
-            .with_assertion(|world| {
-                world.read_resource::<ApplicationResource>();
+            .with_assertion(|_world, resources| {
+                fetch::<ApplicationResource>(resources);
             })
             .run()
     }
@@ -892,13 +723,13 @@ mod test {
     fn with_state_invoked_twice_should_run_in_the_specified_order() -> Result<(), Error> {
         AmethystApplication::blank()
             .with_state(|| {
-                FunctionState::new(|world| {
-                    world.insert(ApplicationResource);
+                FunctionState::new(|_world: &mut World, resources: &mut Resources| {
+                    resources.insert(ApplicationResource);
                 })
             })
             .with_state(|| {
-                FunctionState::new(|world| {
-                    world.read_resource::<ApplicationResource>();
+                FunctionState::new(|_world: &mut World, resources: &mut Resources| {
+                    fetch::<ApplicationResource>(resources);
                 })
             })
             .run()
@@ -909,8 +740,8 @@ mod test {
         AmethystApplication::blank()
             .with_resource(ApplicationResource)
             .with_state(|| {
-                FunctionState::new(|world| {
-                    world.read_resource::<ApplicationResource>();
+                FunctionState::new(|_world: &mut World, resources: &mut Resources| {
+                    fetch::<ApplicationResource>(resources);
                 })
             })
             .run()
@@ -919,8 +750,36 @@ mod test {
     #[test]
     fn setup_runs_before_system() -> Result<(), Error> {
         AmethystApplication::blank()
-            .with_setup(|world| world.insert(ApplicationResourceNonDefault))
-            .with_system(SystemNonDefault, "", &[])
+            .with_setup(|_world, resources| resources.insert(ApplicationResourceNonDefault))
+            .with_system(system_non_default)
+            .run()
+    }
+
+    #[test]
+    fn source_is_handed_to_the_loader() -> Result<(), Error> {
+        AmethystApplication::blank()
+            .with_source(IN_MEMORY_SOURCE_ID, InMemorySource::new())
+            .add_bundle(LoaderBundle)
+            .with_assertion(|_world, resources| {
+                let loader = fetch::<DefaultLoader>(resources);
+                assert!(loader.sources().get(IN_MEMORY_SOURCE_ID).is_some());
+            })
+            .run()
+    }
+
+    #[test]
+    fn time_advances_by_frame_duration_every_frame() -> Result<(), Error> {
+        AmethystApplication::blank()
+            .with_assertion(|_world, resources| {
+                let time = fetch::<Time>(resources);
+                assert_eq!(FRAME_DURATION, time.delta_time());
+                resources.insert(EffectReturn(time.absolute_time()));
+            })
+            .with_assertion(|_world, resources| {
+                let previous = fetch::<EffectReturn<Duration>>(resources).0;
+                let time = fetch::<Time>(resources);
+                assert_eq!(previous + FRAME_DURATION, time.absolute_time());
+            })
             .run()
     }
 
@@ -939,33 +798,24 @@ mod test {
     /// cargo test --features audio
     /// ```
     ///
-    /// **Note:** If you simply need an audio file to be loaded, just add a `Processor::<Source>` in
-    /// the test setup:
-    ///
-    /// ```
-    /// use amethyst::{assets::Processor, audio::Source};
-    ///
-    /// AmethystApplication::blank().with_system(Processor::<Source>::new(), "source_processor", &[])
-    /// // ...
-    /// ```
-    ///
     /// For more details, see <https://github.com/amethyst/amethyst/issues/1595>.
     #[cfg(feature = "test_audio")]
     mod audio_test {
         use amethyst::{
-            assets::AssetStorage,
+            assets::{AssetStorage, LoaderBundle},
             audio::{AudioBundle, Source},
             error::Error,
         };
 
-        use super::AmethystApplication;
+        use super::{fetch, AmethystApplication};
 
         #[test]
         fn audio_zero() -> Result<(), Error> {
             AmethystApplication::blank()
+                .add_bundle(LoaderBundle)
                 .add_bundle(AudioBundle::default())
-                .with_assertion(|world| {
-                    world.read_resource::<AssetStorage<Source>>();
+                .with_assertion(|_world, resources| {
+                    fetch::<AssetStorage<Source>>(resources);
                 })
                 .run()
         }
@@ -973,32 +823,43 @@ mod test {
         #[test]
         fn audio_one() -> Result<(), Error> {
             AmethystApplication::blank()
+                .add_bundle(LoaderBundle)
                 .add_bundle(AudioBundle::default())
-                .with_assertion(|world| {
-                    world.read_resource::<AssetStorage<Source>>();
+                .with_assertion(|_world, resources| {
+                    fetch::<AssetStorage<Source>>(resources);
                 })
                 .run()
         }
+    }
 
-        #[test]
-        fn audio_two() -> Result<(), Error> {
-            AmethystApplication::blank()
-                .add_bundle_fn(|| AudioBundle::default())
-                .with_assertion(|world| {
-                    world.read_resource::<AssetStorage<Source>>();
-                })
-                .run()
-        }
+    /// Fetches a resource, panicking with the resource type name if it does not exist.
+    fn fetch<R: Resource>(resources: &Resources) -> Fetch<'_, R> {
+        resources.get::<R>().unwrap_or_else(|| {
+            panic!(
+                "Expected resource `{}` to exist.",
+                std::any::type_name::<R>()
+            )
+        })
+    }
 
-        #[test]
-        fn audio_three() -> Result<(), Error> {
-            AmethystApplication::blank()
-                .add_bundle_fn(|| AudioBundle::default())
-                .with_assertion(|world| {
-                    world.read_resource::<AssetStorage<Source>>();
-                })
-                .run()
-        }
+    fn component_zero_value(world: &World, resources: &Resources) -> i32 {
+        let entity = fetch::<EffectReturn<Entity>>(resources).0;
+
+        let entry = world.entry_ref(entity).expect("Expected entity to exist.");
+        let component_zero = entry
+            .get_component::<ComponentZero>()
+            .expect("Entity should have a `ComponentZero` component.");
+
+        component_zero.0
+    }
+
+    fn load_asset_zero(resources: &Resources, asset_zero: AssetZero) -> AssetZeroHandle {
+        let loader = fetch::<DefaultLoader>(resources);
+        loader.load_from_data(
+            asset_zero,
+            (),
+            &fetch::<ProcessingQueue<AssetZero>>(resources),
+        )
     }
 
     // === Resources === //
@@ -1008,19 +869,21 @@ mod test {
     struct ApplicationResourceNonDefault;
     #[derive(Debug)]
     struct LoadResource;
+    #[derive(Debug)]
+    struct Counter(u32);
 
     // === States === //
-    struct LoadingState<'a, 'b, S, E>
+    struct LoadingState<S, E>
     where
-        S: State<GameData<'a, 'b>, E> + 'static,
+        S: State<GameData, E> + 'static,
         E: Send + Sync + 'static,
     {
         next_state: Option<S>,
-        state_data: PhantomData<dyn State<GameData<'a, 'b>, E>>,
+        state_data: PhantomData<E>,
     }
-    impl<'a, 'b, S, E> LoadingState<'a, 'b, S, E>
+    impl<S, E> LoadingState<S, E>
     where
-        S: State<GameData<'a, 'b>, E> + 'static,
+        S: State<GameData, E> + 'static,
         E: Send + Sync + 'static,
     {
         fn new(next_state: S) -> Self {
@@ -1030,14 +893,14 @@ mod test {
             }
         }
     }
-    impl<'a, 'b, S, E> State<GameData<'a, 'b>, E> for LoadingState<'a, 'b, S, E>
+    impl<S, E> State<GameData, E> for LoadingState<S, E>
     where
-        S: State<GameData<'a, 'b>, E> + 'static,
+        S: State<GameData, E> + 'static,
         E: Send + Sync + 'static,
     {
-        fn update(&mut self, data: StateData<'_, GameData>) -> Trans<GameData<'a, 'b>, E> {
-            data.data.update(&data.world);
-            data.world.insert(LoadResource);
+        fn update(&mut self, data: StateData<'_, GameData>) -> Trans<GameData, E> {
+            data.data.update(data.world, data.resources);
+            data.resources.insert(LoadResource);
             Trans::Switch(Box::new(self.next_state.take().unwrap()))
         }
     }
@@ -1073,89 +936,73 @@ mod test {
     }
 
     // === Systems === //
-    #[derive(Debug, SystemDesc)]
-    struct SystemZero;
-    impl<'s> System<'s> for SystemZero {
-        type SystemData = ();
-        fn run(&mut self, _: Self::SystemData) {}
+    fn system_zero() -> impl ParallelRunnable {
+        SystemBuilder::new("SystemZero").build(|_, _, _, _| {})
     }
 
-    #[derive(Debug, SystemDesc)]
-    struct SystemOne;
-    type SystemOneData<'s> = Read<'s, ApplicationResource>;
-    impl<'s> System<'s> for SystemOne {
-        type SystemData = SystemOneData<'s>;
-        fn run(&mut self, _: Self::SystemData) {}
+    fn system_one() -> impl ParallelRunnable {
+        SystemBuilder::new("SystemOne")
+            .read_resource::<ApplicationResource>()
+            .build(|_, _, _, _| {})
     }
 
-    #[derive(Debug, SystemDesc)]
-    #[system_desc(insert(ApplicationResourceNonDefault))]
-    struct SystemNonDefault;
-    type SystemNonDefaultData<'s> = ReadExpect<'s, ApplicationResourceNonDefault>;
-    impl<'s> System<'s> for SystemNonDefault {
-        type SystemData = SystemNonDefaultData<'s>;
-        fn run(&mut self, _: Self::SystemData) {}
+    fn system_non_default() -> impl ParallelRunnable {
+        SystemBuilder::new("SystemNonDefault")
+            .read_resource::<ApplicationResourceNonDefault>()
+            .build(|_, _, _, _| {})
     }
 
-    #[derive(Debug, SystemDesc)]
-    struct SystemEffect;
-    type SystemEffectData<'s> = WriteStorage<'s, ComponentZero>;
-    impl<'s> System<'s> for SystemEffect {
-        type SystemData = SystemEffectData<'s>;
-        fn run(&mut self, mut component_zero_storage: Self::SystemData) {
-            for mut component_zero in (&mut component_zero_storage).join() {
-                component_zero.0 += 1
-            }
-        }
+    fn system_effect() -> impl ParallelRunnable {
+        SystemBuilder::new("SystemEffect")
+            .with_query(<Write<ComponentZero>>::query())
+            .build(|_, world, _, query| {
+                for component_zero in query.iter_mut(world) {
+                    component_zero.0 += 1
+                }
+            })
+    }
+
+    fn system_count() -> impl Runnable {
+        SystemBuilder::new("SystemCount")
+            .write_resource::<Counter>()
+            .build(|_, _, counter, _| counter.0 += 1)
     }
 
     // === Bundles === //
     #[derive(Debug)]
     struct BundleZero;
-    impl<'a, 'b> SystemBundle<'a, 'b> for BundleZero {
-        fn build(
-            self,
+    impl SystemBundle for BundleZero {
+        fn load(
+            &mut self,
             _world: &mut World,
-            builder: &mut DispatcherBuilder<'a, 'b>,
+            _resources: &mut Resources,
+            builder: &mut DispatcherBuilder,
         ) -> Result<(), Error> {
-            builder.add(SystemZero, "system_zero", &[]);
+            builder.add_system(system_zero);
             Ok(())
         }
     }
 
     #[derive(Debug)]
     struct BundleOne;
-    impl<'a, 'b> SystemBundle<'a, 'b> for BundleOne {
-        fn build(
-            self,
-            world: &mut World,
-            builder: &mut DispatcherBuilder<'a, 'b>,
-        ) -> Result<(), Error> {
-            builder.add(SystemOne, "system_one", &["system_zero"]);
-            builder.add(SystemNonDefault.build(world), "system_non_default", &[]);
-            Ok(())
-        }
-    }
-
-    #[derive(Debug)]
-    struct BundleAsset;
-    impl<'a, 'b> SystemBundle<'a, 'b> for BundleAsset {
-        fn build(
-            self,
+    impl SystemBundle for BundleOne {
+        fn load(
+            &mut self,
             _world: &mut World,
-            builder: &mut DispatcherBuilder<'a, 'b>,
+            resources: &mut Resources,
+            builder: &mut DispatcherBuilder,
         ) -> Result<(), Error> {
-            builder.add(
-                Processor::<AssetZero>::new(),
-                "asset_translation_zero_processor",
-                &[],
-            );
+            resources.get_or_insert_with(ApplicationResource::default);
+            resources.insert(ApplicationResourceNonDefault);
+            builder.add_system(system_one);
+            builder.add_system(system_non_default);
             Ok(())
         }
     }
 
     // === Assets === //
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Deserialize, PartialEq, Serialize, TypeUuid)]
+    #[uuid = "5c4a8b16-9e0d-4f3b-8a47-2d61c9e0b7f3"]
     struct AssetZero(u32);
     impl Asset for AssetZero {
         fn name() -> &'static str {
@@ -1163,29 +1010,9 @@ mod test {
         }
         type Data = Self;
     }
-    impl From<AssetZero> for Result<ProcessingState<AssetZero>, Error> {
-        fn from(asset_translation_zero: AssetZero) -> Result<ProcessingState<AssetZero>, Error> {
-            Ok(ProcessingState::Loaded(asset_translation_zero))
-        }
-    }
+    register_asset_type!(AssetZero => AssetZero; AssetProcessorSystem<AssetZero>);
     type AssetZeroHandle = Handle<AssetZero>;
 
-    // === System delegates === //
-    struct AssetZeroLoader;
-    impl AssetZeroLoader {
-        fn load(
-            world: &World,
-            asset_translation_zero: AssetZero,
-        ) -> Result<AssetZeroHandle, Error> {
-            let loader = data.resources.get::<DefaultLoader>().unwrap();
-            Ok(loader.load_from_data(
-                asset_translation_zero,
-                (),
-                &world.read_resource::<AssetStorage<AssetZero>>(),
-            ))
-        }
-    }
-
     // === Components === //
     struct ComponentZero(pub i32);
 }
@@ -1,8 +1,8 @@
 use amethyst::{
     animation::{Animation, InterpolationFunction, MaterialChannel, MaterialPrimitive, Sampler},
-    assets::{AssetStorage, DefaultLoader, Handle, Loader},
+    assets::{AssetStorage, DefaultLoader, Handle, Loader, ProcessingQueue},
     ecs::*,
-    renderer::{loaders::load_from_srgba, palette::Srgba, Material},
+    renderer::{loaders::load_from_srgba, palette::Srgba, types::TextureData, Material},
 };
 
 use crate::EffectReturn;
@@ -12,19 +12,20 @@ use crate::EffectReturn;
 pub struct MaterialAnimationFixture;
 
 impl MaterialAnimationFixture {
-    /// Loads a material animation into the `World`.
+    /// Loads a material animation into the `Resources`.
     ///
     /// # Parameters
     ///
-    /// * `world`: `World` to load the material animation into.
-    pub fn effect(world: &mut World) {
+    /// * `_world`: `World` of the application.
+    /// * `resources`: `Resources` to load the material animation into.
+    pub fn effect(_world: &mut World, resources: &mut Resources) {
         // Load the animation.
         let animation_handle = {
-            let loader = data.resources.get::<DefaultLoader>().unwrap();
+            let loader = resources.get::<DefaultLoader>().unwrap();
             let tex_handle = loader.load_from_data(
                 load_from_srgba(Srgba::new(0.5, 0.5, 0.5, 0.5)).into(),
                 (),
-                &world.read_resource(),
+                &resources.get::<ProcessingQueue<TextureData>>().unwrap(),
             );
 
             let texture_sampler = Sampler {
@@ -38,10 +39,13 @@ impl MaterialAnimationFixture {
                 function: InterpolationFunction::Step,
             };
 
+            let sampler_queue = resources
+                .get::<ProcessingQueue<Sampler<MaterialPrimitive>>>()
+                .unwrap();
             let texture_animation_handle =
-                loader.load_from_data(texture_sampler, (), &world.read_resource());
+                loader.load_from_data(texture_sampler, (), &sampler_queue);
             let sampler_animation_handle =
-                loader.load_from_data(sprite_offset_sampler, (), &world.read_resource());
+                loader.load_from_data(sprite_offset_sampler, (), &sampler_queue);
 
             let animation = Animation::<Material> {
                 nodes: vec![
@@ -50,23 +54,32 @@ impl MaterialAnimationFixture {
                 ],
             };
 
-            loader.load_from_data::<Animation<Material>, ()>(animation, (), &world.read_resource())
+            loader.load_from_data(
+                animation,
+                (),
+                &resources
+                    .get::<ProcessingQueue<Animation<Material>>>()
+                    .unwrap(),
+            )
         };
-        world.insert(EffectReturn(animation_handle));
+        resources.insert(EffectReturn(animation_handle));
     }
 
-    /// Asserts that the material animation is present in the `World`.
+    /// Asserts that the material animation is present in the `Resources`.
     ///
     /// # Parameters
     ///
-    /// * `world`: `World` that the material animation is loaded in.
-    pub fn assertion(world: &mut World) {
+    /// * `_world`: `World` of the application.
+    /// * `resources`: `Resources` that the material animation is loaded in.
+    pub fn assertion(_world: &mut World, resources: &mut Resources) {
         // Read the animation.
-        let animation_handle = &world
-            .read_resource::<EffectReturn<Handle<Animation<Material>>>>()
-            .0;
+        let animation_handle = resources
+            .get::<EffectReturn<Handle<Animation<Material>>>>()
+            .unwrap();
 
-        let store = world.read_resource::<AssetStorage<Animation<Material>>>();
-        assert!(store.get(animation_handle).is_some());
+        let store = resources
+            .get::<AssetStorage<Animation<Material>>>()
+            .unwrap();
+        assert!(store.get(&animation_handle.0).is_some());
     }
 }
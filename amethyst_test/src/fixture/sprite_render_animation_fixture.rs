@@ -2,10 +2,14 @@ use amethyst::{
     animation::{
         Animation, InterpolationFunction, Sampler, SpriteRenderChannel, SpriteRenderPrimitive,
     },
-    assets::{AssetStorage, DefaultLoader, Handle, Loader},
+    assets::{AssetStorage, DefaultLoader, Handle, Loader, ProcessingQueue},
     ecs::*,
     renderer::{
-        loaders::load_from_srgba, palette::Srgba, Sprite, SpriteRender, SpriteSheet, Texture,
+        loaders::load_from_srgba,
+        palette::Srgba,
+        sprite::{SpriteList, SpritePosition, Sprites},
+        types::TextureData,
+        SpriteRender, SpriteSheet,
     },
 };
 
@@ -16,25 +20,32 @@ use crate::EffectReturn;
 pub struct SpriteRenderAnimationFixture;
 
 impl SpriteRenderAnimationFixture {
-    /// Loads a sprite render animation into the `World`.
+    /// Loads a sprite render animation into the `Resources`.
     ///
     /// # Parameters
     ///
-    /// * `world`: `World` to load the sprite render animation into.
-    pub fn effect(world: &mut World) {
+    /// * `_world`: `World` of the application.
+    /// * `resources`: `Resources` to load the sprite render animation into.
+    pub fn effect(_world: &mut World, resources: &mut Resources) {
         // Load the animation.
         let animation_handle = {
-            // This invocation sequence of read_resource / write_resource is to satisfy the borrow
-            // checker, since we don't have NLL yet.
-            let tex_handle = world.read_resource::<DefaultLoader>().load_from_data(
+            let loader = resources.get::<DefaultLoader>().unwrap();
+            let texture = loader.load_from_data(
                 load_from_srgba(Srgba::new(0.5, 0.5, 0.5, 0.5)).into(),
                 (),
-                &world.read_resource::<AssetStorage<Texture>>(),
+                &resources.get::<ProcessingQueue<TextureData>>().unwrap(),
+            );
+            let sprites = loader.load_from_data(
+                Self::sprites(),
+                (),
+                &resources.get::<ProcessingQueue<Sprites>>().unwrap(),
             );
 
-            let loader = data.resources.get::<DefaultLoader>().unwrap();
-            let sprite_sheet_handle =
-                loader.load_from_data(Self::sprite_sheet(tex_handle), (), &world.read_resource());
+            let sprite_sheet_handle = loader.load_from_data(
+                SpriteSheet { texture, sprites },
+                (),
+                &resources.get::<ProcessingQueue<SpriteSheet>>().unwrap(),
+            );
             let sprite_sheet_sampler = Sampler {
                 input: vec![0.0],
                 output: vec![SpriteRenderPrimitive::SpriteSheet(sprite_sheet_handle)],
@@ -46,10 +57,13 @@ impl SpriteRenderAnimationFixture {
                 function: InterpolationFunction::Step,
             };
 
+            let sampler_queue = resources
+                .get::<ProcessingQueue<Sampler<SpriteRenderPrimitive>>>()
+                .unwrap();
             let sprite_sheet_animation_handle =
-                loader.load_from_data(sprite_sheet_sampler, (), &world.read_resource());
+                loader.load_from_data(sprite_sheet_sampler, (), &sampler_queue);
             let sprite_index_animation_handle =
-                loader.load_from_data(sprite_index_sampler, (), &world.read_resource());
+                loader.load_from_data(sprite_index_sampler, (), &sampler_queue);
 
             let animation = Animation::<SpriteRender> {
                 nodes: vec![
@@ -66,39 +80,48 @@ impl SpriteRenderAnimationFixture {
                 ],
             };
 
-            loader.load_from_data::<Animation<SpriteRender>, ()>(
+            loader.load_from_data(
                 animation,
                 (),
-                &world.read_resource(),
+                &resources
+                    .get::<ProcessingQueue<Animation<SpriteRender>>>()
+                    .unwrap(),
             )
         };
-        world.insert(EffectReturn(animation_handle));
+        resources.insert(EffectReturn(animation_handle));
     }
 
-    /// Asserts that the sprite render animation is present in the `World`.
+    /// Asserts that the sprite render animation is present in the `Resources`.
     ///
     /// # Parameters
     ///
-    /// * `world`: `World` that the sprite render animation is loaded in.
-    pub fn assertion(world: &mut World) {
+    /// * `_world`: `World` of the application.
+    /// * `resources`: `Resources` that the sprite render animation is loaded in.
+    pub fn assertion(_world: &mut World, resources: &mut Resources) {
         // Read the animation.
-        let animation_handle = &world
-            .read_resource::<EffectReturn<Handle<Animation<SpriteRender>>>>()
-            .0;
+        let animation_handle = resources
+            .get::<EffectReturn<Handle<Animation<SpriteRender>>>>()
+            .unwrap();
 
-        let store = world.read_resource::<AssetStorage<Animation<SpriteRender>>>();
-        assert!(store.get(animation_handle).is_some());
+        let store = resources
+            .get::<AssetStorage<Animation<SpriteRender>>>()
+            .unwrap();
+        assert!(store.get(&animation_handle.0).is_some());
     }
 
-    fn sprite_sheet(texture: Handle<Texture>) -> SpriteSheet {
-        SpriteSheet {
-            texture,
-            sprites: vec![Sprite {
-                width: 10.0,
-                height: 10.0,
-                offsets: [5.; 2],
-                tex_coords: [0.0, 1.0, 0.0, 1.0].into(),
+    fn sprites() -> Sprites {
+        Sprites::List(SpriteList {
+            texture_width: 10,
+            texture_height: 10,
+            sprites: vec![SpritePosition {
+                x: 0,
+                y: 0,
+                width: 10,
+                height: 10,
+                offsets: Some([5.; 2]),
+                flip_horizontal: false,
+                flip_vertical: false,
             }],
-        }
+        })
     }
 }
@@ -7,12 +7,13 @@ pub trait GameUpdate {
     /// # Parameters
     ///
     /// * `world`: `World` in which the game takes place.
-    fn update(&mut self, _world: &World) {}
+    /// * `resources`: `Resources` shared by the systems.
+    fn update(&mut self, _world: &mut World, _resources: &mut Resources) {}
 }
 
 // Implement for built-in Amethyst `GameData`
-impl<'a, 'b> GameUpdate for GameData<'a, 'b> {
-    fn update(&mut self, world: &World) {
-        GameData::update(self, world);
+impl GameUpdate for GameData {
+    fn update(&mut self, world: &mut World, resources: &mut Resources) {
+        GameData::update(self, world, resources);
     }
 }
@@ -23,14 +23,15 @@
 //! bundles, and can take in logic that is normally masked behind a number of layers through a thin
 //! interface.
 //!
+//! The application is stepped one frame at a time with a virtual clock that advances by
+//! [FRAME_DURATION] per frame, so tests see the same `Time` on every run.
+//!
 //! # Usage
 //!
 //! The following shows a simple example of testing a `State`. More examples are in the
 //! [Examples](#Examples) section.
 //!
 //! ```
-//! # use std::marker::PhantomData;
-//! #
 //! # use amethyst_test::prelude::*;
 //! # use amethyst::{
 //! #     ecs::*,
@@ -49,14 +50,14 @@
 //! #     }
 //! # }
 //! #
-//! # impl<'a, 'b, E> State<GameData<'a, 'b>, E> for LoadingState
+//! # impl<E> State<GameData, E> for LoadingState
 //! # where
 //! #     E: Send + Sync + 'static,
 //! # {
-//! #     fn update(&mut self, data: StateData<'_, GameData>) -> Trans<GameData<'a, 'b>, E> {
-//! #         data.data.update(&data.world);
+//! #     fn update(&mut self, data: StateData<'_, GameData>) -> Trans<GameData, E> {
+//! #         data.data.update(data.world, data.resources);
 //! #
-//! #         data.world.insert(LoadResource);
+//! #         data.resources.insert(LoadResource);
 //! #
 //! #         Trans::Pop
 //! #     }
@@ -66,8 +67,8 @@
 //! fn loading_state_adds_load_resource() {
 //!     assert!(AmethystApplication::blank()
 //!         .with_state(|| LoadingState::new())
-//!         .with_assertion(|world| {
-//!             world.read_resource::<LoadResource>();
+//!         .with_assertion(|_world, resources| {
+//!             assert!(resources.contains::<LoadResource>());
 //!         })
 //!         .run()
 //!         .is_ok());
@@ -89,12 +90,10 @@
 //!
 //!     // Start with the following bundles:
 //!     //
+//!     // * `LoaderBundle`
 //!     // * `TransformBundle`
 //!     // * `InputBundle`
 //!     // * `UiBundle`
-//!     //
-//!     // The type parameters here are the Axis and Action types for the
-//!     // `InputBundle` and `UiBundle`.
 //!     AmethystApplication::ui_base();
 //!
 //!     // If you need types from the rendering bundle, make sure you have
@@ -114,35 +113,29 @@
 //! Next, attach the logic you wish to test using the various `.with_*(..)` methods:
 //!
 //! ```no_run
+//! # use amethyst_test::prelude::*;
 //! # use amethyst::{
-//! #     core::bundle::SystemBundle,
 //! #     ecs::*,
 //! #     prelude::*,
 //! # };
 //! #
-//! # #[derive(Debug)]
-//! # struct MySystem;
-//! #
-//! # impl<'s> System<'s> for MySystem {
-//! #     type SystemData = ();
-//! #     fn run(&mut self, _: Self::SystemData) {}
+//! # fn my_system() -> impl ParallelRunnable {
+//! #     SystemBuilder::new("MySystem").build(|_, _, _, _| {})
 //! # }
 //! #
 //! #[test]
 //! fn test_name() {
-//!     let visibility = false; // Whether the window should be shown
-//!     AmethystApplication::render_base::<String, String, _>("test_name", visibility)
+//!     AmethystApplication::blank()
 //!         .add_bundle(MyBundle::new()) // Registers a bundle.
-//!         .add_bundle_fn(|| MyNonSendBundle::new()) // Registers a `!Send` bundle.
-//!         .with_resource(MyResource::new()) // Adds a resource to the world.
-//!         .with_system(MySystem, "my_sys", &[]) // Registers a system with the main
-//!         // dispatcher.
+//!         .with_resource(MyResource::new()) // Adds a resource to the `Resources`.
+//!         .with_system(my_system) // Registers a system with the main dispatcher.
+//!         .with_source(IN_MEMORY_SOURCE_ID, InMemorySource::new()) // Registers an asset source.
 //!         // These are run in the order they are invoked.
 //!         // You may invoke them multiple times.
-//!         .with_setup(|world| { /* do something */ })
+//!         .with_setup(|world, resources| { /* do something */ })
 //!         .with_state(|| MyState::new())
-//!         .with_effect(|world| { /* do something */ })
-//!         .with_assertion(|world| { /* do something */ })
+//!         .with_effect(|world, resources| { /* do something */ })
+//!         .with_assertion(|world, resources| { /* do something */ })
 //!     // ...
 //! }
 //! ```
@@ -153,8 +146,7 @@
 //! ```no_run
 //! #[test]
 //! fn test_name() {
-//!     let visibility = false; // Whether the window should be shown
-//!     assert!(AmethystApplication::render_base("test_name", visibility)
+//!     assert!(AmethystApplication::blank()
 //!         // ...
 //!         .run()
 //!         .is_ok());
@@ -168,30 +160,32 @@
 //! ```
 //! # use amethyst_test::prelude::*;
 //! # use amethyst::{
-//! #     core::bundle::SystemBundle,
 //! #     ecs::*,
 //! #     prelude::*,
+//! #     Error,
 //! # };
 //! #
 //! # #[derive(Debug)]
 //! # struct ApplicationResource;
 //! #
-//! # #[derive(Debug)]
-//! # struct MySystem;
-//! #
-//! # impl<'s> System<'s> for MySystem {
-//! #     type SystemData = ReadExpect<'s, ApplicationResource>;
-//! #
-//! #     fn run(&mut self, _: Self::SystemData) {}
+//! # fn my_system() -> impl ParallelRunnable {
+//! #     SystemBuilder::new("MySystem")
+//! #         .read_resource::<ApplicationResource>()
+//! #         .build(|_, _, _, _| {})
 //! # }
 //! #
 //! # #[derive(Debug)]
 //! # struct MyBundle;
-//! # impl<'a, 'b> SystemBundle<'a, 'b> for MyBundle {
-//! #     fn build(self, world: &mut World, builder: &mut DispatcherBuilder<'a, 'b>)
-//! #     -> amethyst::Result<()> {
-//! #         world.insert(ApplicationResource);
-//! #         builder.add(MySystem, "my_system", &[]);
+//! #
+//! # impl SystemBundle for MyBundle {
+//! #     fn load(
+//! #         &mut self,
+//! #         _world: &mut World,
+//! #         resources: &mut Resources,
+//! #         builder: &mut DispatcherBuilder,
+//! #     ) -> Result<(), Error> {
+//! #         resources.insert(ApplicationResource);
+//! #         builder.add_system(my_system);
 //! #         Ok(())
 //! #     }
 //! # }
@@ -200,8 +194,8 @@
 //! fn bundle_registers_system_with_resource() {
 //!     assert!(AmethystApplication::blank()
 //!         .add_bundle(MyBundle)
-//!         .with_assertion(|world| {
-//!             world.read_resource::<ApplicationResource>();
+//!         .with_assertion(|_world, resources| {
+//!             assert!(resources.contains::<ApplicationResource>());
 //!         })
 //!         .run()
 //!         .is_ok());
@@ -221,33 +215,30 @@
 //! #
 //! # struct MyComponent(pub i32);
 //! #
-//! # #[derive(Debug)]
-//! # struct MySystem;
-//! #
-//! # impl<'s> System<'s> for MySystem {
-//! #     type SystemData = WriteStorage<'s, MyComponent>;
-//! #
-//! #     fn run(&mut self, mut my_component_storage: Self::SystemData) {
-//! #         for mut my_component in (&mut my_component_storage).join() {
-//! #             my_component.0 += 1
-//! #         }
-//! #     }
+//! # fn my_system() -> impl ParallelRunnable {
+//! #     SystemBuilder::new("MySystem")
+//! #         .with_query(<Write<MyComponent>>::query())
+//! #         .build(|_, world, _, query| {
+//! #             for my_component in query.iter_mut(world) {
+//! #                 my_component.0 += 1;
+//! #             }
+//! #         })
 //! # }
 //! #
 //! // #[test]
 //! fn system_increases_component_value_by_one() {
 //!     assert!(AmethystApplication::blank()
-//!         .with_system(MySystem, "my_system", &[])
-//!         .with_effect(|world| {
+//!         .with_system(my_system)
+//!         .with_effect(|world, resources| {
 //!             let entity = world.push((MyComponent(0),));
-//!             world.insert(EffectReturn(entity));
+//!             resources.insert(EffectReturn(entity));
 //!         })
-//!         .with_assertion(|world| {
-//!             let entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+//!         .with_assertion(|world, resources| {
+//!             let entity = resources.get::<EffectReturn<Entity>>().unwrap().0;
 //!
-//!             let my_component_storage = world.read_storage::<MyComponent>();
-//!             let my_component = my_component_storage
-//!                 .get(entity)
+//!             let entry = world.entry(entity).expect("Expected entity to exist.");
+//!             let my_component = entry
+//!                 .get_component::<MyComponent>()
 //!                 .expect("Entity should have a `MyComponent` component.");
 //!
 //!             // If the system ran, the value in the `MyComponent` should be 1.
@@ -273,26 +264,21 @@
 //! # // !Default
 //! # struct MyResource(pub i32);
 //! #
-//! # #[derive(Debug)]
-//! # struct MySystem;
-//! #
-//! # impl<'s> System<'s> for MySystem {
-//! #     type SystemData = WriteExpect<'s, MyResource>;
-//! #
-//! #     fn run(&mut self, mut my_resource: Self::SystemData) {
-//! #         my_resource.0 += 1
-//! #     }
+//! # fn my_system() -> impl ParallelRunnable {
+//! #     SystemBuilder::new("MySystem")
+//! #         .write_resource::<MyResource>()
+//! #         .build(|_, _, my_resource, _| my_resource.0 += 1)
 //! # }
 //! #
 //! // #[test]
 //! fn system_increases_resource_value_by_one() {
 //!     assert!(AmethystApplication::blank()
-//!         .with_setup(|world| {
-//!             world.insert(MyResource(0));
+//!         .with_setup(|_world, resources| {
+//!             resources.insert(MyResource(0));
 //!         })
-//!         .with_system_single(MySystem, "my_system", &[])
-//!         .with_assertion(|world| {
-//!             let my_resource = world.read_resource::<MyResource>();
+//!         .with_system_single(my_system)
+//!         .with_assertion(|_world, resources| {
+//!             let my_resource = resources.get::<MyResource>().unwrap();
 //!
 //!             // If the system ran, the value in the `MyResource` should be 1.
 //!             assert_eq!(1, my_resource.0);
@@ -307,7 +293,7 @@
 #[cfg(feature = "animation")]
 pub use crate::fixture::{MaterialAnimationFixture, SpriteRenderAnimationFixture};
 pub use crate::{
-    amethyst_application::{AmethystApplication, SCREEN_HEIGHT, SCREEN_WIDTH},
+    amethyst_application::{AmethystApplication, FRAME_DURATION, SCREEN_HEIGHT, SCREEN_WIDTH},
     effect_return::EffectReturn,
     game_update::GameUpdate,
     in_memory_source::{InMemorySource, IN_MEMORY_SOURCE_ID},
@@ -317,11 +303,6 @@ pub use crate::{
     },
     wait_for_load::WaitForLoad,
 };
-pub(crate) use crate::{
-    system_desc_injection_bundle::SystemDescInjectionBundle,
-    system_injection_bundle::SystemInjectionBundle,
-    thread_local_injection_bundle::ThreadLocalInjectionBundle,
-};
 
 mod amethyst_application;
 mod effect_return;
@@ -330,7 +311,4 @@ mod game_update;
 mod in_memory_source;
 pub mod prelude;
 mod state;
-mod system_desc_injection_bundle;
-mod system_injection_bundle;
-mod thread_local_injection_bundle;
 mod wait_for_load;
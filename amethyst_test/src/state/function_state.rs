@@ -4,11 +4,11 @@ use crate::GameUpdate;
 
 /// Runs a function in `.update()` then `Pop`s itself.
 ///
-/// The function is run before `GameUpdate#update(world)` is called.
+/// The function is run after `GameUpdate#update(world, resources)` is called.
 #[derive(Debug)]
 pub struct FunctionState<F>
 where
-    F: FnOnce(&mut World),
+    F: FnOnce(&mut World, &mut Resources),
 {
     /// Function to run in `update`.
     function: Option<F>,
@@ -16,7 +16,7 @@ where
 
 impl<F> FunctionState<F>
 where
-    F: FnOnce(&mut World),
+    F: FnOnce(&mut World, &mut Resources),
 {
     /// Returns a new `FunctionState`
     pub fn new(function: F) -> Self {
@@ -28,15 +28,20 @@ where
 
 impl<F, T, E> State<T, E> for FunctionState<F>
 where
-    F: FnOnce(&mut World),
+    F: FnOnce(&mut World, &mut Resources),
     T: GameUpdate,
     E: Send + Sync + 'static,
 {
-    fn update(&mut self, mut data: StateData<'_, T>) -> Trans<T, E> {
-        data.data.update(&data.world);
+    fn update(&mut self, data: StateData<'_, T>) -> Trans<T, E> {
+        let StateData {
+            world,
+            resources,
+            data,
+        } = data;
+        data.update(world, resources);
 
         if let Some(function) = self.function.take() {
-            (function)(&mut data.world);
+            (function)(world, resources);
         }
 
         Trans::Pop
@@ -1,6 +1,11 @@
 use std::time::Duration;
 
-use amethyst::{assets::ProgressCounter, core::Stopwatch, ecs::World, State, StateData, Trans};
+use amethyst::{
+    assets::{DefaultLoader, LoadProgress},
+    core::Stopwatch,
+    ecs::{Resources, World},
+    State, StateData, Trans,
+};
 use derivative::Derivative;
 use log::warn;
 
@@ -9,7 +14,7 @@ use crate::GameUpdate;
 /// Time limit before outputting a warning message.
 const LOADING_TIME_LIMIT: Duration = Duration::from_secs(10);
 
-/// Reads a `ProgressCounter` resource and waits for it to be `complete()`.
+/// Reads a `LoadProgress` resource and waits for it to be complete.
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct WaitForLoad {
@@ -19,14 +24,25 @@ pub struct WaitForLoad {
     stopwatch: Stopwatch,
     /// Function to determine loading is complete.
     #[derivative(Debug = "ignore")]
-    fn_complete: fn(&World) -> bool,
+    fn_complete: fn(&mut World, &mut Resources) -> bool,
 }
 
 impl WaitForLoad {
-    /// Returns a `WaitForLoad` that assumes a `ProgressCounter` resource exists in the `World`.
+    /// Returns a `WaitForLoad` that assumes a `LoadProgress` resource exists in the `Resources`.
+    ///
+    /// The progress is updated from the `DefaultLoader` every frame.
     pub fn new() -> Self {
         WaitForLoad {
-            fn_complete: |world| world.read_resource::<ProgressCounter>().is_complete(),
+            fn_complete: |_, resources| {
+                let loader = resources
+                    .get::<DefaultLoader>()
+                    .expect("Expected `DefaultLoader` to exist, is the `LoaderBundle` added?");
+                let mut progress = resources
+                    .get_mut::<LoadProgress>()
+                    .expect("Expected `LoadProgress` resource to exist.");
+                progress.update(&*loader);
+                progress.is_complete()
+            },
             stopwatch: Stopwatch::new(),
         }
     }
@@ -36,7 +52,7 @@ impl WaitForLoad {
     /// # Parameters
     ///
     /// * `fn_complete`: Function to determine loading is complete.
-    pub fn new_with_fn(fn_complete: fn(&World) -> bool) -> Self {
+    pub fn new_with_fn(fn_complete: fn(&mut World, &mut Resources) -> bool) -> Self {
         WaitForLoad {
             fn_complete,
             stopwatch: Stopwatch::new(),
@@ -58,9 +74,14 @@ where
     }
 
     fn update(&mut self, data: StateData<'_, T>) -> Trans<T, E> {
-        data.data.update(&data.world);
-
-        if !(self.fn_complete)(&data.world) {
+        let StateData {
+            world,
+            resources,
+            data,
+        } = data;
+        data.update(world, resources);
+
+        if !(self.fn_complete)(world, resources) {
             if let Stopwatch::Started(..) = &self.stopwatch {
                 let elapsed = self.stopwatch.elapsed();
                 if elapsed > LOADING_TIME_LIMIT {
@@ -68,7 +89,7 @@ where
 
                     warn!(
                         "Loading has not completed in 10 seconds, please ensure that you have \
-                         registered the relevant `Processor::<A>`s in the dispatcher.",
+                         registered the relevant asset types with `register_asset_type!`.",
                     );
                 }
             }
@@ -84,9 +105,12 @@ where
 mod tests {
     use amethyst::{
         assets::{
-            Asset, AssetStorage, Handle, Loader, ProcessingState, Processor, ProgressCounter,
-            RonFormat,
+            self as amethyst_assets, distill_importer,
+            distill_importer::{typetag, SerdeImportable},
+            register_asset_type, register_importer, Asset, AssetProcessorSystem, AssetStorage,
+            DefaultLoader, Format, Handle, LoadProgress, Loader, LoaderBundle, TypeUuid,
         },
+        error::{format_err, ResultExt},
         Error,
     };
     use serde::{Deserialize, Serialize};
@@ -94,95 +118,82 @@ mod tests {
     use super::WaitForLoad;
     use crate::{AmethystApplication, InMemorySource, IN_MEMORY_SOURCE_ID};
 
+    fn in_memory_source() -> InMemorySource {
+        InMemorySource::new().with("file.test_asset", b"(val: 123)".to_vec())
+    }
+
+    fn load_test_asset(resources: &amethyst::ecs::Resources) -> (Handle<TestAsset>, LoadProgress) {
+        let loader = resources
+            .get::<DefaultLoader>()
+            .expect("Expected `DefaultLoader` to exist.");
+        let test_asset_handle = loader.load_from("file.test_asset", IN_MEMORY_SOURCE_ID);
+        let mut progress = LoadProgress::new();
+        progress.track(&test_asset_handle);
+        (test_asset_handle, progress)
+    }
+
+    fn assert_test_asset_loaded(resources: &amethyst::ecs::Resources) {
+        let test_asset_handle = resources
+            .get::<Handle<TestAsset>>()
+            .expect("Expected `Handle<TestAsset>` to exist.");
+        let test_assets = resources
+            .get::<AssetStorage<TestAsset>>()
+            .expect("Expected `AssetStorage<TestAsset>` to exist.");
+        let test_asset = test_assets
+            .get(&*test_asset_handle)
+            .expect("Expected `TestAsset` to be loaded.");
+
+        assert_eq!(&TestAsset { val: 123 }, test_asset);
+    }
+
     #[test]
-    fn pops_when_progress_counter_is_complete() -> Result<(), Error> {
+    fn pops_when_load_progress_is_complete() -> Result<(), Error> {
         AmethystApplication::blank()
-            .with_system(Processor::<TestAsset>::new(), "test_asset_processor", &[])
-            .with_effect(|world| {
-                let mut in_memory_source = InMemorySource::new();
-                in_memory_source.insert(String::from("file.ron"), b"(val: 123)".to_vec());
-
-                let mut loader = world.write_resource::<DefaultLoader>();
-                loader.add_source(IN_MEMORY_SOURCE_ID, in_memory_source);
-            })
-            .with_effect(|world| {
-                let mut progress_counter = ProgressCounter::new();
-                let test_asset_handle = {
-                    let loader = data.resources.get::<DefaultLoader>().unwrap();
-                    loader.load_from(
-                        "file.ron",
-                        RonFormat,
-                        IN_MEMORY_SOURCE_ID,
-                        &mut progress_counter,
-                        &world.read_resource::<AssetStorage<TestAsset>>(),
-                    )
-                };
-
-                world.insert(test_asset_handle);
-                world.insert(progress_counter);
+            .with_source(IN_MEMORY_SOURCE_ID, in_memory_source())
+            .add_bundle(LoaderBundle)
+            .with_effect(|_, resources| {
+                let (test_asset_handle, progress) = load_test_asset(resources);
+                resources.insert(test_asset_handle);
+                resources.insert(progress);
             })
             .with_state(WaitForLoad::new)
-            .with_assertion(|world| {
-                let test_asset_handle = world.read_resource::<Handle<TestAsset>>();
-                let test_assets = world.read_resource::<AssetStorage<TestAsset>>();
-                let test_asset = test_assets
-                    .get(&test_asset_handle)
-                    .expect("Expected `TestAsset` to be loaded.");
-
-                assert_eq!(&TestAsset { val: 123 }, test_asset);
-            })
+            .with_assertion(|_, resources| assert_test_asset_loaded(resources))
             .run()
     }
 
     #[test]
     fn uses_custom_completion_function() -> Result<(), Error> {
         AmethystApplication::blank()
-            .with_system(Processor::<TestAsset>::new(), "test_asset_processor", &[])
-            .with_effect(|world| {
-                let mut in_memory_source = InMemorySource::new();
-                in_memory_source.insert(String::from("file.ron"), b"(val: 123)".to_vec());
-
-                let mut loader = world.write_resource::<DefaultLoader>();
-                loader.add_source(IN_MEMORY_SOURCE_ID, in_memory_source);
-            })
-            .with_effect(|world| {
-                let mut progress_counter = ProgressCounter::new();
-                let test_asset_handle = {
-                    let loader = data.resources.get::<DefaultLoader>().unwrap();
-                    loader.load_from(
-                        "file.ron",
-                        RonFormat,
-                        IN_MEMORY_SOURCE_ID,
-                        &mut progress_counter,
-                        &world.read_resource::<AssetStorage<TestAsset>>(),
-                    )
-                };
-
-                world.insert(test_asset_handle);
-                world.insert(vec![progress_counter]);
+            .with_source(IN_MEMORY_SOURCE_ID, in_memory_source())
+            .add_bundle(LoaderBundle)
+            .with_effect(|_, resources| {
+                let (test_asset_handle, progress) = load_test_asset(resources);
+                resources.insert(test_asset_handle);
+                resources.insert(vec![progress]);
             })
             .with_state(|| {
-                WaitForLoad::new_with_fn(|world| {
-                    world
-                        .read_resource::<Vec<ProgressCounter>>()
-                        .first()
-                        .expect("Expected `Vec<ProgressCounter>` with one element.")
-                        .is_complete()
+                WaitForLoad::new_with_fn(|_, resources| {
+                    let loader = resources
+                        .get::<DefaultLoader>()
+                        .expect("Expected `DefaultLoader` to exist.");
+                    let mut progresses = resources
+                        .get_mut::<Vec<LoadProgress>>()
+                        .expect("Expected `Vec<LoadProgress>` to exist.");
+                    let progress = progresses
+                        .first_mut()
+                        .expect("Expected `Vec<LoadProgress>` with one element.");
+                    progress.update(&*loader);
+                    progress.is_complete()
                 })
             })
-            .with_assertion(|world| {
-                let test_asset_handle = world.read_resource::<Handle<TestAsset>>();
-                let test_assets = world.read_resource::<AssetStorage<TestAsset>>();
-                let test_asset = test_assets
-                    .get(&test_asset_handle)
-                    .expect("Expected `TestAsset` to be loaded.");
-
-                assert_eq!(&TestAsset { val: 123 }, test_asset);
-            })
+            .with_assertion(|_, resources| assert_test_asset_loaded(resources))
             .run()
     }
 
-    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[derive(
+        Clone, Debug, Default, Deserialize, PartialEq, Serialize, TypeUuid, SerdeImportable,
+    )]
+    #[uuid = "1d5e4b8e-4a8f-4c6a-9d0f-5f7f3f0b6b51"]
     pub struct TestAsset {
         val: u32,
     }
@@ -190,12 +201,27 @@ mod tests {
     impl Asset for TestAsset {
         type Data = Self;
 
-        const NAME: &'static str = concat!(module_path!(), "::", stringify!(TestAsset));
+        fn name() -> &'static str {
+            "TestAsset"
+        }
     }
 
-    impl From<TestAsset> for Result<ProcessingState<TestAsset>, Error> {
-        fn from(asset_data: TestAsset) -> Result<ProcessingState<TestAsset>, Error> {
-            Ok(ProcessingState::Loaded(asset_data))
+    register_asset_type!(TestAsset => TestAsset; AssetProcessorSystem<TestAsset>);
+
+    /// Format for loading `TestAsset`s from RON files with the `.test_asset` extension.
+    #[derive(Clone, Debug, Default, Deserialize, Serialize, TypeUuid)]
+    #[uuid = "8b0e9c71-63b4-4b0e-a7f1-3b2b7c2a4e90"]
+    pub struct TestAssetFormat;
+
+    impl Format<TestAsset> for TestAssetFormat {
+        fn name(&self) -> &'static str {
+            "TestAsset"
+        }
+
+        fn import_simple(&self, bytes: Vec<u8>) -> Result<TestAsset, Error> {
+            ron::de::from_bytes(&bytes).with_context(|_| format_err!("Failed parsing Ron file"))
         }
     }
+
+    register_importer!(".test_asset", TestAssetFormat);
 }
@@ -2,7 +2,7 @@
 
 use std::marker::PhantomData;
 
-use amethyst_assets::ProcessingQueue;
+use amethyst_assets::{AssetStorage, ProcessingQueue};
 use amethyst_core::{ecs::*, shrev::EventChannel};
 use amethyst_error::Error;
 use amethyst_rendy::types::DefaultBackend;
@@ -13,6 +13,7 @@ use crate::{
     button::{ui_button_action_retrigger_event_system, UiButtonSystem},
     drag::DragWidgetSystem,
     event::UiMouseSystem,
+    format::FontAsset,
     glyphs::{GlyphTextureData, GlyphTextureProcessorSystem},
     layout::UiTransformSystem,
     resize::ResizeSystem,
@@ -56,6 +57,9 @@ where
         resources.insert(CachedSelectionOrderResource::default());
 
         resources.insert(ProcessingQueue::<GlyphTextureData>::default());
+        if let Some(mut storage) = resources.get_mut::<AssetStorage<FontAsset>>() {
+            storage.report_sizes();
+        }
         builder.add_system(GlyphTextureProcessorSystem::<DefaultBackend>::default());
 
         log::debug!("Creating UI EventChannel Readers");
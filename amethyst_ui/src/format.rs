@@ -1,5 +1,5 @@
 use amethyst_assets::{
-    register_asset_type, Asset, AssetProcessorSystem, AssetStorage, Format, LoadHandle,
+    register_asset_type, Asset, AssetProcessorSystem, AssetSize, AssetStorage, Format, LoadHandle,
     ProcessableAsset, ProcessingState,
 };
 use amethyst_error::{format_err, Error, ResultExt};
@@ -45,6 +45,15 @@ impl Asset for FontAsset {
     type Data = FontData;
 }
 
+/// Approximate size of the outline and metrics data of one glyph in a font file.
+const APPROX_BYTES_PER_GLYPH: usize = 128;
+
+impl AssetSize for FontAsset {
+    fn size_bytes(&self) -> usize {
+        self.0.glyph_count() * APPROX_BYTES_PER_GLYPH
+    }
+}
+
 impl ProcessableAsset for FontAsset {
     fn process(
         data: FontData,
@@ -67,7 +67,10 @@ impl<B: Backend> RenderPlugin<B> for RenderUi {
     ) -> Result<(), Error> {
         resources.insert(UiGlyphsResource::new(resources));
 
-        builder.add_system(crate::glyphs::UiGlyphsSystem::<B>::default());
+        builder.add_system_ordered(
+            crate::glyphs::UiGlyphsSystem::<B>::default(),
+            SystemOrder::new().stage(stage::RENDER_PREP),
+        );
         Ok(())
     }
 
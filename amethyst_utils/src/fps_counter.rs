@@ -1,6 +1,6 @@
 //! Util Resources
 
-use amethyst_core::{ecs::*, Time};
+use amethyst_core::{ecs::*, SystemTimings, Time};
 use amethyst_error::Error;
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
@@ -20,6 +20,9 @@ use crate::circular_buffer::CircularBuffer;
 /// sampled_fps will return the averaged framerate. This gives a better approximation of the "felt"
 /// framerate by the user.
 ///
+/// To see where the frame time goes, add the `SystemTimings` resource as well, e.g. with
+/// `FpsCounterBundle::with_system_timings`, and show its `system_breakdown` next to the FPS.
+///
 /// # Example
 /// ```
 /// # use amethyst::utils::fps_counter::FpsCounter;
@@ -105,6 +108,7 @@ impl System for FpsCounterSystem {
 #[derive(Default, Debug)]
 pub struct FpsCounterBundle {
     samplesize: Option<usize>,
+    system_timings: Option<usize>,
 }
 
 impl FpsCounterBundle {
@@ -117,6 +121,16 @@ impl FpsCounterBundle {
     pub fn sample_size(self, samplesize: usize) -> Self {
         Self {
             samplesize: Some(samplesize),
+            ..self
+        }
+    }
+
+    /// Also insert a [`SystemTimings`] resource keeping the system timings of the last `window`
+    /// frames, so the FPS can be shown next to a breakdown of where the frame time goes.
+    pub fn with_system_timings(self, window: usize) -> Self {
+        Self {
+            system_timings: Some(window),
+            ..self
         }
     }
 }
@@ -132,6 +146,9 @@ impl SystemBundle for FpsCounterBundle {
             self.samplesize
                 .map_or_else(FpsCounter::default, FpsCounter::new),
         );
+        if let Some(window) = self.system_timings {
+            resources.get_or_insert_with(|| SystemTimings::new(window));
+        }
         builder.add_system(FpsCounterSystem);
         Ok(())
     }
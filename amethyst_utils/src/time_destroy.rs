@@ -1,6 +1,11 @@
 //! Allows you to automatically delete an entity after a set time has elapsed.
+//!
+//! The systems built with a clock read the `Clocks` resource next to `Time`, which the
+//! application inserts.
 
-use amethyst_core::{ecs::*, Time};
+use std::time::Duration;
+
+use amethyst_core::{ecs::*, ClockSource, Clocks, Time};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
@@ -9,7 +14,7 @@ use thread_profiler::profile_scope;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DestroyAtTime {
     /// The time at which the entity should be destroyed in seconds.
-    /// Compared to `Time::absolute_time_seconds`.
+    /// Compared to the absolute time of the clock the system uses.
     pub time: f64,
 }
 
@@ -17,11 +22,12 @@ pub struct DestroyAtTime {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DestroyInTime {
     /// The amount of time before the entity should be destroyed in seconds.
-    /// Compared to `Time::absolute_time_seconds`.
+    /// Counted down with the delta time of the clock the system uses.
     pub timer: f64,
 }
 
-/// The system in charge of destroying entities with the `DestroyAtTime` component.
+/// The system in charge of destroying entities with the `DestroyAtTime` component, measured with
+/// the game `Time`.
 pub fn build_destroy_at_time_system() -> impl Runnable {
     SystemBuilder::new("destroy_at_time_system")
         .read_resource::<Time>()
@@ -30,15 +36,32 @@ pub fn build_destroy_at_time_system() -> impl Runnable {
             #[cfg(feature = "profiler")]
             profile_scope!("destroy_at_time_system");
 
-            for (ent, dat) in dat_query.iter_mut(subworld) {
-                if time.absolute_time().as_secs_f64() > dat.time {
-                    commands.remove(*ent);
-                }
-            }
+            destroy_at(commands, subworld, dat_query, time.absolute_time());
+        })
+}
+
+/// The system in charge of destroying entities with the `DestroyAtTime` component, measured with
+/// the given clock. Requires the `Clocks` resource.
+pub fn build_destroy_at_time_system_with_clock(clock: ClockSource) -> impl Runnable {
+    SystemBuilder::new("destroy_at_time_system")
+        .read_resource::<Time>()
+        .read_resource::<Clocks>()
+        .with_query(<(Entity, Read<DestroyAtTime>)>::query())
+        .build(move |commands, subworld, (time, clocks), dat_query| {
+            #[cfg(feature = "profiler")]
+            profile_scope!("destroy_at_time_system");
+
+            destroy_at(
+                commands,
+                subworld,
+                dat_query,
+                clock.absolute_time(&time, &clocks),
+            );
         })
 }
 
-/// The system in charge of destroying entities with the `DestroyInTime` component.
+/// The system in charge of destroying entities with the `DestroyInTime` component, measured with
+/// the game `Time`.
 pub fn build_destroy_in_time_system() -> impl Runnable {
     SystemBuilder::new("destroy_in_time_system")
         .read_resource::<Time>()
@@ -47,12 +70,56 @@ pub fn build_destroy_in_time_system() -> impl Runnable {
             #[cfg(feature = "profiler")]
             profile_scope!("destroy_in_time_system");
 
-            for (ent, mut dit) in dit_query.iter_mut(subworld) {
-                if dit.timer <= 0f64 {
-                    commands.remove(*ent);
-                }
+            destroy_in(commands, subworld, dit_query, time.delta_time());
+        })
+}
 
-                dit.timer -= time.delta_time().as_secs_f64();
-            }
+/// The system in charge of destroying entities with the `DestroyInTime` component, measured with
+/// the given clock. Requires the `Clocks` resource.
+pub fn build_destroy_in_time_system_with_clock(clock: ClockSource) -> impl Runnable {
+    SystemBuilder::new("destroy_in_time_system")
+        .read_resource::<Time>()
+        .read_resource::<Clocks>()
+        .with_query(<(Entity, Write<DestroyInTime>)>::query())
+        .build(move |commands, subworld, (time, clocks), dit_query| {
+            #[cfg(feature = "profiler")]
+            profile_scope!("destroy_in_time_system");
+
+            destroy_in(
+                commands,
+                subworld,
+                dit_query,
+                clock.delta_time(&time, &clocks),
+            );
         })
 }
+
+fn destroy_at(
+    commands: &mut CommandBuffer,
+    subworld: &mut SubWorld<'_>,
+    query: &mut Query<(Entity, Read<DestroyAtTime>)>,
+    now: Duration,
+) {
+    let now = now.as_secs_f64();
+    for (ent, dat) in query.iter_mut(subworld) {
+        if now > dat.time {
+            commands.remove(*ent);
+        }
+    }
+}
+
+fn destroy_in(
+    commands: &mut CommandBuffer,
+    subworld: &mut SubWorld<'_>,
+    query: &mut Query<(Entity, Write<DestroyInTime>)>,
+    delta: Duration,
+) {
+    let delta = delta.as_secs_f64();
+    for (ent, mut dit) in query.iter_mut(subworld) {
+        if dit.timer <= 0f64 {
+            commands.remove(*ent);
+        }
+
+        dit.timer -= delta;
+    }
+}
@@ -18,7 +18,7 @@ use amethyst::{
             WriteStorage,
         },
         math::{Unit, UnitQuaternion, Vector3},
-        Time, Transform, TransformBundle,
+        Time, TimeControl, Transform, TransformBundle,
     },
     error::Error,
     gltf::GltfSceneLoaderSystemDesc,
@@ -364,8 +364,8 @@ impl SimpleState for Example {
         profile_scope!("example update");
 
         {
-            let mut time = data.world.write_resource::<Time>();
-            time.set_time_scale(if self.bullet_time { 0.2 } else { 1.0 });
+            let mut control = data.resources.get_mut::<TimeControl>().unwrap();
+            control.set_time_scale(if self.bullet_time { 0.2 } else { 1.0 });
         }
 
         if !self.initialized {
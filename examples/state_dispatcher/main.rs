@@ -1,9 +1,7 @@
-//! An example showing how to create a dispatcher inside of a State.
-
-use std::marker::PhantomData;
+//! An example showing how to give a State its own dispatcher.
 
 use amethyst::{
-    ecs::{Dispatcher, DispatcherBuilder},
+    ecs::{DispatcherBuilder, World},
     prelude::*,
     shrev::EventChannel,
     utils::application_root_dir,
@@ -16,39 +14,66 @@ impl SimpleState for StateA {
     fn update(&mut self, data: &mut StateData<'_, GameData>) -> SimpleTrans {
         println!("StateA::update()");
         // Shows how to push a `Trans` through the event queue.
-        // If you do use TransQueue, you will be forced to use the 'static lifetime on your states.
-        data.world
-            .write_resource::<EventChannel<TransEvent<GameData<'static, 'static>, StateEvent>>>()
-            .single_write(Box::new(|| Trans::Push(Box::new(StateB::default()))));
+        data.resources
+            .get_mut::<EventChannel<TransEvent<GameData, StateEvent>>>()
+            .unwrap()
+            .single_write(Box::new(|| Trans::Push(Box::new(StateB))));
 
         // You can also use normal Trans at the same time!
         // Those will be executed before the ones in the EventChannel
-        // Trans::Push(Box::new(StateB::default()))
+        // Trans::Push(Box::new(StateB))
 
         Trans::None
     }
 }
 
-/// StateB isn't Send + Sync
-struct StateB<'a> {
-    dispatcher: Dispatcher,
-    _phantom: &'a PhantomData<()>,
-}
+/// Counts the frames StateB was active for.
+struct Frames(u32);
 
-impl<'a> Default for StateB<'a> {
-    fn default() -> Self {
-        StateB {
-            dispatcher: DispatcherBuilder::new().build(),
-            _phantom: &PhantomData,
-        }
+/// Bundle which only lives as long as StateB.
+struct StateBBundle;
+
+impl SystemBundle for StateBBundle {
+    fn load(
+        &mut self,
+        _world: &mut World,
+        resources: &mut Resources,
+        builder: &mut DispatcherBuilder,
+    ) -> Result<(), Error> {
+        println!("StateBBundle::load()");
+        resources.insert(Frames(0));
+        builder.add_thread_local_fn(|_, resources| {
+            let mut frames = resources.get_mut::<Frames>().unwrap();
+            frames.0 += 1;
+            println!("StateB dispatcher, frame {}", frames.0);
+        });
+        Ok(())
+    }
+
+    fn unload(&mut self, _world: &mut World, resources: &mut Resources) -> Result<(), Error> {
+        println!("StateBBundle::unload()");
+        resources.remove::<Frames>();
+        Ok(())
     }
 }
 
-impl<'a> SimpleState for StateB<'a> {
+struct StateB;
+
+impl SimpleState for StateB {
+    fn dispatcher(&mut self) -> Option<DispatcherBuilder> {
+        let mut builder = DispatcherBuilder::default();
+        builder.add_bundle(StateBBundle);
+        Some(builder)
+    }
+
     fn update(&mut self, data: &mut StateData<'_, GameData>) -> SimpleTrans {
         println!("StateB::update()");
-        self.dispatcher.dispatch(&data.world);
-        Trans::Quit
+        // The dispatcher of StateB runs after this update, so it has counted one frame less.
+        if data.resources.get::<Frames>().unwrap().0 >= 2 {
+            Trans::Quit
+        } else {
+            Trans::None
+        }
     }
 }
 
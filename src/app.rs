@@ -17,17 +17,19 @@ use winit::event::{Event, WindowEvent};
 
 #[cfg(feature = "asset-daemon")]
 use crate::assets::AssetDaemon;
+#[cfg(feature = "asset-packfile")]
+use crate::assets::Packfile;
 use crate::{
-    assets::{DefaultLoader, Source},
+    assets::{Source, Sources},
     core::{
         frame_limiter::{FrameLimiter, FrameRateLimitConfig, FrameRateLimitStrategy},
         shrev::{EventChannel, ReaderId},
-        ArcThreadPool, EventReader, Stopwatch, Time,
+        ArcThreadPool, Clocks, EventReader, FixedUpdateInterpolation, Stopwatch, Time, TimeControl,
     },
     ecs::*,
     error::Error,
     game_data::{DataDispose, DataInit},
-    state::{State, StateData, StateMachine, TransEvent},
+    state::{State, StateData, StateMachine, StateTransitionEvent, Trans, TransEvent},
     state_event::{StateEvent, StateEventReader},
 };
 
@@ -64,10 +66,12 @@ where
     trans_reader_id: ReaderId<TransEvent<T, E>>,
     states: StateMachine<'a, T, E>,
     ignore_window_close: bool,
+    virtual_delta_time: Option<Duration>,
+    initialized: bool,
     data: T,
     #[cfg(feature = "asset-daemon")]
     #[derivative(Debug = "ignore")]
-    asset_daemon: AssetDaemon,
+    asset_daemon: Option<AssetDaemon>,
 }
 
 /// An Application is the root object of the game engine. It binds the OS
@@ -234,9 +238,9 @@ where
             None
         };
 
-        self.initialize();
-
-        self.resources.get_mut::<Stopwatch>().unwrap().start();
+        if !self.initialized {
+            self.initialize();
+        }
 
         while self.states.is_running() {
             self.advance_frame();
@@ -245,22 +249,91 @@ where
                 profile_scope!("frame_limiter wait");
                 self.resources.get_mut::<FrameLimiter>().unwrap().wait();
             }
-            {
-                let mut stopwatch = self.resources.get_mut::<Stopwatch>().unwrap();
-                let elapsed = stopwatch.elapsed();
-                let mut time = self.resources.get_mut::<Time>().unwrap();
-                time.advance_frame(elapsed);
-                stopwatch.stop();
-                stopwatch.restart();
-            }
+            self.advance_time();
         }
         self.shutdown();
     }
 
+    /// Advances the game by a single frame and returns control to the caller, e.g. to run a
+    /// game server from an external loop or to inspect the `World` and `Resources` in tests.
+    ///
+    /// The first call starts the state machine. Unlike [run](#method.run), this does not wait
+    /// for the `FrameLimiter`, so combine it with
+    /// [with_virtual_clock](struct.ApplicationBuilder.html#method.with_virtual_clock) to get
+    /// the same `Time` every run. When the last state stops the application is shut down.
+    ///
+    /// # Returns
+    ///
+    /// Returns whether the application is still running.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use amethyst::prelude::*;
+    ///
+    /// struct NullState;
+    /// impl EmptyState for NullState {}
+    ///
+    /// # fn main() -> amethyst::Result<()> {
+    /// let mut game = Application::build("assets/", NullState)?
+    ///     .with_virtual_clock(Duration::from_millis(10))
+    ///     .build(())?;
+    ///
+    /// for _ in 0..100 {
+    ///     game.step();
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn step(&mut self) -> bool {
+        if !self.initialized {
+            self.initialize();
+        }
+        if self.states.is_running() {
+            self.advance_frame();
+            self.advance_time();
+            if !self.states.is_running() {
+                self.shutdown();
+            }
+        }
+        self.states.is_running()
+    }
+
+    /// Checks whether the state machine is still running.
+    pub fn is_running(&self) -> bool {
+        self.states.is_running()
+    }
+
+    /// Returns the game world.
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Returns the game world mutably.
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Returns the resources of the game.
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Returns the resources of the game mutably.
+    pub fn resources_mut(&mut self) -> &mut Resources {
+        &mut self.resources
+    }
+
     /// Sets up the application.
     fn initialize(&mut self) {
+        self.initialized = true;
+
         #[cfg(feature = "asset-daemon")]
-        self.asset_daemon.start_on_new_thread();
+        if let Some(asset_daemon) = &mut self.asset_daemon {
+            asset_daemon.start_on_new_thread();
+        }
 
         #[cfg(feature = "profiler")]
         profile_scope!("initialize");
@@ -271,6 +344,35 @@ where
                 &mut self.data,
             ))
             .expect("Tried to start state machine without any states present");
+
+        self.resources.get_mut::<Stopwatch>().unwrap().start();
+    }
+
+    /// Feeds the duration of the last frame to `Time`, measured by the `Stopwatch` or taken
+    /// from the virtual clock.
+    fn advance_time(&mut self) {
+        let elapsed = match self.virtual_delta_time {
+            Some(delta_time) => delta_time,
+            None => {
+                let mut stopwatch = self.resources.get_mut::<Stopwatch>().unwrap();
+                let elapsed = stopwatch.elapsed();
+                stopwatch.stop();
+                stopwatch.restart();
+                elapsed
+            }
+        };
+        let time_scale = self
+            .resources
+            .get::<TimeControl>()
+            .map_or(1.0, |control| control.effective_time_scale());
+        {
+            let mut time = self.resources.get_mut::<Time>().unwrap();
+            time.set_time_scale(time_scale);
+            time.advance_frame(elapsed);
+        }
+        if let Some(mut clocks) = self.resources.get_mut::<Clocks>() {
+            clocks.advance(elapsed);
+        }
     }
 
     // React to window close events
@@ -312,7 +414,10 @@ where
             let world = &mut self.world;
             let resources = &mut self.resources;
             let states = &mut self.states;
-            states.stop(StateData::new(world, resources, &mut self.data));
+            states.transition(
+                Trans::Quit,
+                StateData::new(world, resources, &mut self.data),
+            );
         }
 
         // Read the Trans queue and apply changes.
@@ -347,18 +452,40 @@ where
             #[cfg(feature = "profiler")]
             profile_scope!("fixed_update");
 
-            while {
-                self.resources
-                    .get_mut::<Time>()
-                    .unwrap()
-                    .step_fixed_update()
-            } {
+            // Fixed updates follow the scaled game time, so they slow down and pause with it.
+            let max_steps = self
+                .resources
+                .get::<TimeControl>()
+                .map_or(u32::MAX, |control| control.max_fixed_updates());
+            let mut steps = 0;
+            loop {
+                {
+                    let mut time = self.resources.get_mut::<Time>().unwrap();
+                    if steps == max_steps {
+                        // Drop the steps beyond the cap instead of catching up on them later.
+                        while time.step_fixed_update() {}
+                        break;
+                    }
+                    if !time.step_fixed_update() {
+                        break;
+                    }
+                }
+                steps += 1;
                 self.states.fixed_update(StateData::new(
                     &mut self.world,
                     &mut self.resources,
                     &mut self.data,
                 ));
             }
+
+            let (delta_time, fixed_time) = {
+                let time = self.resources.get::<Time>().unwrap();
+                (time.delta_time(), time.fixed_time())
+            };
+            self.resources
+                .get_mut::<FixedUpdateInterpolation>()
+                .unwrap()
+                .update(delta_time, steps, fixed_time);
         }
         {
             #[cfg(feature = "profiler")]
@@ -379,7 +506,9 @@ where
     /// Cleans up after the quit signal is received.
     fn shutdown(&mut self) {
         #[cfg(feature = "asset-daemon")]
-        self.asset_daemon.stop_and_join();
+        if let Some(asset_daemon) = &mut self.asset_daemon {
+            asset_daemon.stop_and_join();
+        }
 
         info!("Engine is shutting down");
         self.data.dispose(&mut self.world, &mut self.resources);
@@ -413,8 +542,13 @@ pub struct ApplicationBuilder<S, T, E, R> {
     /// Used by bundles to initialize any resources in the world
     pub resources: Resources,
     ignore_window_close: bool,
+    virtual_delta_time: Option<Duration>,
+    thread_count: Option<usize>,
+    thread_name: Option<Box<dyn FnMut(usize) -> String + Send>>,
     #[allow(dead_code)]
     asset_dirs: Vec<PathBuf>,
+    #[cfg(feature = "asset-daemon")]
+    start_asset_daemon: bool,
     phantom: PhantomData<(T, E, R)>,
 }
 
@@ -443,11 +577,6 @@ where
     /// - `S`: A type that implements the `State` trait. e.g. Your initial
     ///        game logic.
     ///
-    /// # Errors
-    ///
-    /// CoreApplication will return an error if the internal threadpool fails
-    /// to initialize correctly because of systems resource limitations
-    ///
     /// # Examples
     ///
     /// ```no_run
@@ -516,27 +645,14 @@ where
         let world = World::default();
         let mut resources = Resources::default();
 
-        let thread_pool_builder = ThreadPoolBuilder::new();
-        #[cfg(feature = "profiler")]
-        let thread_pool_builder = thread_pool_builder.start_handler(|_index| {
-            register_thread_with_profiler();
-        });
-        let pool: ArcThreadPool;
-        if let Some(thread_count) = thread_count {
-            debug!("Running Amethyst with fixed thread pool: {}", thread_count);
-            pool = thread_pool_builder
-                .num_threads(thread_count)
-                .build()
-                .map(Arc::new)?;
-        } else {
-            pool = thread_pool_builder.build().map(Arc::new)?;
-        }
-        resources.insert(pool);
         resources.insert(EventChannel::<Event<'static, ()>>::with_capacity(2000));
         //resources.insert(EventChannel::<UiEvent>::with_capacity(40));
         resources.insert(FrameLimiter::default());
         resources.insert(Stopwatch::default());
         resources.insert(Time::default());
+        resources.insert(TimeControl::default());
+        resources.insert(Clocks::default());
+        resources.insert(FixedUpdateInterpolation::default());
 
         let asset_dirs = vec![path.as_ref().to_path_buf()];
 
@@ -545,8 +661,13 @@ where
             world,
             resources,
             ignore_window_close: false,
+            virtual_delta_time: None,
+            thread_count,
+            thread_name: None,
             phantom: PhantomData,
             asset_dirs,
+            #[cfg(feature = "asset-daemon")]
+            start_asset_daemon: true,
         })
     }
 
@@ -606,6 +727,10 @@ where
     /// effect will be a replacement of the older store with the new one.
     /// No warning or panic will result from this action.
     ///
+    /// The store is handed to the `DefaultLoader` when the `LoaderBundle` is loaded, so
+    /// this has to be called before [build](#method.build). Assets are loaded from it with
+    /// `Loader::load_from`.
+    ///
     /// # Parameters
     ///
     /// - `name`: A unique name or key to identify the asset storage location. `name`
@@ -625,17 +750,19 @@ where
     ///
     /// ```no_run
     /// use amethyst::{
-    ///     assets::{DefaultLoader, Directory, Handle, Loader},
+    ///     assets::{DefaultLoader, Directory, Handle, Loader, LoaderBundle},
     ///     prelude::*,
-    ///     renderer::{formats::mesh::ObjFormat, Mesh},
+    ///     renderer::Mesh,
     /// };
     ///
     /// # fn main() -> amethyst::Result<()> {
+    /// let mut dispatcher = DispatcherBuilder::default();
+    /// dispatcher.add_bundle(LoaderBundle);
     /// let assets_dir = "assets/";
     /// let game = Application::build(assets_dir, LoadingState)?
-    ///     // Register the directory "custom_directory" under the name "resources".
+    ///     // Register the directory "custom_directory" under the name "custom_store".
     ///     .with_source("custom_store", Directory::new("custom_directory"))
-    ///     .build(DispatcherBuilder::default())?
+    ///     .build(dispatcher)?
     ///     .run();
     /// #     Ok(())
     /// # }
@@ -645,25 +772,27 @@ where
     ///     fn on_start(&mut self, data: StateData<'_, GameData>) {
     ///         let loader = data.resources.get::<DefaultLoader>().unwrap();
     ///         // Load a teapot mesh from the directory that registered above.
-    ///         let mesh: Handle<Mesh> = loader.load("teapot.obj");
+    ///         let mesh: Handle<Mesh> = loader.load_from("teapot.obj", "custom_store");
     ///     }
     /// }
     /// ```
-    pub fn with_source<I, O>(self, _name: I, _store: O) -> Self
+    pub fn with_source<I, O>(mut self, name: I, store: O) -> Self
     where
         I: Into<String>,
         O: Source,
     {
-        {
-            let _loader = self.resources.get_mut::<DefaultLoader>().unwrap();
-            // FIXME Update the source on the loader
-            // loader.add_source(name, store);
-        }
+        self.resources
+            .get_mut_or_insert_with(Sources::default)
+            .add(name, store);
         self
     }
 
     /// Registers the default asset store with the loader logic of the Application.
     ///
+    /// `Loader::load` loads assets from the default store instead of the asset daemon.
+    /// The store is handed to the `DefaultLoader` when the `LoaderBundle` is loaded, so
+    /// this has to be called before [build](#method.build).
+    ///
     /// # Parameters
     ///
     /// - `store`: The asset store being registered.
@@ -707,18 +836,79 @@ where
     ///     }
     /// }
     /// ```
-    pub fn with_default_source<O>(self, _store: O) -> Self
+    pub fn with_default_source<O>(mut self, store: O) -> Self
     where
         O: Source,
     {
+        self.resources
+            .get_mut_or_insert_with(Sources::default)
+            .set_default(store);
+        self
+    }
+
+    /// Loads all assets from a baked packfile instead of the asset daemon.
+    ///
+    /// The packfile is handed to the `DefaultLoader` when the `LoaderBundle` is loaded, so
+    /// this has to be called before [build](#method.build). This also disables the asset daemon.
+    ///
+    /// # Parameters
+    ///
+    /// - `packfile`: The packfile, usually baked with `Packfile::bake` and read with
+    ///               `Packfile::open`.
+    ///
+    /// # Returns
+    ///
+    /// This function returns ApplicationBuilder after it has modified it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use amethyst::{
+    ///     assets::{LoaderBundle, Packfile},
+    ///     prelude::*,
+    /// };
+    ///
+    /// # fn main() -> amethyst::Result<()> {
+    /// # struct LoadingState;
+    /// # impl SimpleState for LoadingState {}
+    /// let mut dispatcher = DispatcherBuilder::default();
+    /// dispatcher.add_bundle(LoaderBundle);
+    /// let game = Application::build("assets/", LoadingState)?
+    ///     .with_packfile(Packfile::open("assets.pack")?)
+    ///     .build(dispatcher)?
+    ///     .run();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "asset-packfile")]
+    pub fn with_packfile(mut self, packfile: Packfile) -> Self {
+        self.resources.insert(packfile);
+        #[cfg(feature = "asset-daemon")]
         {
-            // let _loader = self.resources.get_mut::<DefaultLoader>().unwrap();
-            // FIXME Update the location on the loader
-            // loader.set_default_source(store);
+            self.start_asset_daemon = false;
         }
         self
     }
 
+    /// Sets whether the asset daemon is started together with the application. Defaults to true.
+    ///
+    /// Applications which load all of their assets from sources registered with
+    /// [with_default_source](#method.with_default_source) or
+    /// [with_source](#method.with_source) do not need the daemon.
+    ///
+    /// # Parameters
+    ///
+    /// `start`: Whether or not the asset daemon should be started.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    #[cfg(feature = "asset-daemon")]
+    pub fn with_asset_daemon(mut self, start: bool) -> Self {
+        self.start_asset_daemon = start;
+        self
+    }
+
     /// Sets the maximum frames per second of this game.
     ///
     /// # Parameters
@@ -765,6 +955,75 @@ where
         self
     }
 
+    /// Drives `Time` from a virtual clock which advances by `delta_time` every frame, instead of
+    /// measuring the real duration of frames with the `Stopwatch`.
+    ///
+    /// Together with [CoreApplication::step](struct.CoreApplication.html#method.step) this runs
+    /// the game deterministically, e.g. on a headless server or in tests.
+    ///
+    /// # Parameters
+    ///
+    /// `delta_time`: The duration of every frame.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    pub fn with_virtual_clock(mut self, delta_time: Duration) -> Self {
+        self.virtual_delta_time = Some(delta_time);
+        self
+    }
+
+    /// Sets the number of worker threads of the thread pool which executes the systems.
+    ///
+    /// This overrides the `AMETHYST_NUM_THREADS` environment variable. By default, rayon picks
+    /// the number of threads from the number of CPUs.
+    ///
+    /// # Parameters
+    ///
+    /// `count`: The number of worker threads.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    pub fn with_thread_count(mut self, count: usize) -> Self {
+        self.thread_count = Some(count);
+        self
+    }
+
+    /// Sets the names of the worker threads of the thread pool which executes the systems.
+    ///
+    /// # Parameters
+    ///
+    /// `name`: Returns the name of the worker thread with the given index.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    pub fn with_thread_name<F>(mut self, name: F) -> Self
+    where
+        F: FnMut(usize) -> String + Send + 'static,
+    {
+        self.thread_name = Some(Box::new(name));
+        self
+    }
+
+    /// Sets how dispatchers execute their systems.
+    ///
+    /// Use `ExecutionMode::SingleThreaded` to run all systems one after another on the main
+    /// thread, which makes the execution order deterministic when debugging.
+    ///
+    /// # Parameters
+    ///
+    /// `mode`: How systems are executed. Systems run in parallel by default.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    pub fn with_execution_mode(mut self, mode: ExecutionMode) -> Self {
+        self.resources.insert(mode);
+        self
+    }
+
     /// Tells the resulting application window to ignore close events if ignore is true.
     /// This will make your game window unresponsive to operating system close commands.
     /// Use with caution.
@@ -789,8 +1048,9 @@ where
     ///
     /// # Errors
     ///
-    /// This function currently will not produce an error, returning a result
-    /// type was strictly for future possibilities.
+    /// This function will return an error if the thread pool fails to initialize
+    /// correctly because of system resource limitations, or if the game data fails
+    /// to build.
     ///
     /// # Notes
     ///
@@ -815,6 +1075,11 @@ where
         #[cfg(feature = "profiler")]
         profile_scope!("new");
 
+        if !self.resources.contains::<ArcThreadPool>() {
+            let pool = self.build_thread_pool()?;
+            self.resources.insert(pool);
+        }
+
         let data = init.build(&mut self.world, &mut self.resources)?;
 
         let event_reader_id = self
@@ -826,6 +1091,8 @@ where
         let mut trans_event_channel = EventChannel::<TransEvent<T, E>>::with_capacity(2);
         let trans_reader_id = trans_event_channel.register_reader();
         self.resources.insert(trans_event_channel);
+        self.resources
+            .get_or_insert_with(EventChannel::<StateTransitionEvent>::new);
 
         let mut reader = X::default();
         reader.setup(&mut self.resources);
@@ -837,11 +1104,162 @@ where
             reader,
             events: Vec::new(),
             ignore_window_close: self.ignore_window_close,
+            virtual_delta_time: self.virtual_delta_time,
+            initialized: false,
             data,
             event_reader_id,
             trans_reader_id,
             #[cfg(feature = "asset-daemon")]
-            asset_daemon: AssetDaemon::new(self.asset_dirs),
+            asset_daemon: if self.start_asset_daemon {
+                Some(AssetDaemon::new(self.asset_dirs))
+            } else {
+                None
+            },
         })
     }
+
+    /// Builds the thread pool used to execute systems, as configured by `with_thread_count`
+    /// and `with_thread_name`.
+    fn build_thread_pool(&mut self) -> Result<ArcThreadPool, Error> {
+        let mut thread_pool_builder = ThreadPoolBuilder::new();
+        #[cfg(feature = "profiler")]
+        {
+            thread_pool_builder = thread_pool_builder.start_handler(|_index| {
+                register_thread_with_profiler();
+            });
+        }
+        if let Some(thread_count) = self.thread_count {
+            debug!("Running Amethyst with fixed thread pool: {}", thread_count);
+            thread_pool_builder = thread_pool_builder.num_threads(thread_count);
+        }
+        if let Some(thread_name) = self.thread_name.take() {
+            thread_pool_builder = thread_pool_builder.thread_name(thread_name);
+        }
+        Ok(thread_pool_builder.build().map(Arc::new)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmptyState, EmptyTrans};
+
+    struct Frames(u32);
+
+    struct CountingState;
+    impl EmptyState for CountingState {
+        fn update(&mut self, data: StateData<'_, ()>) -> EmptyTrans {
+            let mut frames = data.resources.get_mut::<Frames>().unwrap();
+            frames.0 += 1;
+            if frames.0 == 3 {
+                Trans::Quit
+            } else {
+                Trans::None
+            }
+        }
+    }
+
+    #[test]
+    fn step_advances_virtual_clock() {
+        let builder = Application::build("assets/", CountingState)
+            .unwrap()
+            .with_resource(Frames(0))
+            .with_virtual_clock(Duration::from_millis(10));
+        #[cfg(feature = "asset-daemon")]
+        let builder = builder.with_asset_daemon(false);
+        let mut game = builder.build(()).unwrap();
+
+        assert!(game.step());
+        assert!(game.step());
+        {
+            let time = game.resources().get::<Time>().unwrap();
+            assert_eq!(time.delta_time(), Duration::from_millis(10));
+            assert_eq!(time.absolute_time(), Duration::from_millis(20));
+        }
+        assert!(!game.step());
+        assert!(!game.step());
+        assert_eq!(game.resources().get::<Frames>().unwrap().0, 3);
+    }
+
+    struct FixedSteps(u32);
+
+    struct FixedCountingState;
+    impl EmptyState for FixedCountingState {
+        fn fixed_update(&mut self, data: StateData<'_, ()>) -> EmptyTrans {
+            data.resources.get_mut::<FixedSteps>().unwrap().0 += 1;
+            Trans::None
+        }
+    }
+
+    #[test]
+    fn time_control_scales_pauses_and_caps_fixed_updates() {
+        let mut control = TimeControl::default();
+        control.set_time_scale(0.5);
+        let builder = Application::build("assets/", FixedCountingState)
+            .unwrap()
+            .with_resource(FixedSteps(0))
+            .with_resource(control)
+            .with_fixed_step_length(Duration::from_millis(10))
+            .with_virtual_clock(Duration::from_millis(100));
+        #[cfg(feature = "asset-daemon")]
+        let builder = builder.with_asset_daemon(false);
+        let mut game = builder.build(()).unwrap();
+        let fixed_steps =
+            |game: &Application<'_, ()>| game.resources().get::<FixedSteps>().unwrap().0;
+
+        // Fixed updates run one frame after the time advanced.
+        game.step();
+        game.step();
+        assert_eq!(5, fixed_steps(&game));
+
+        game.resources()
+            .get_mut::<TimeControl>()
+            .unwrap()
+            .set_paused(true);
+        // The pause applies to the time advanced at the end of the next frame, while that frame
+        // still runs the fixed updates of the time advanced before the pause.
+        game.step();
+        game.step();
+        assert_eq!(10, fixed_steps(&game));
+        assert_eq!(
+            Duration::from_millis(100),
+            game.resources().get::<Time>().unwrap().absolute_time()
+        );
+        let clocks = game.resources().get::<Clocks>().unwrap();
+        let ui = clocks.get(Clocks::UI).unwrap();
+        assert_eq!(Duration::from_millis(400), ui.absolute_time());
+        drop(clocks);
+
+        {
+            let mut control = game.resources().get_mut::<TimeControl>().unwrap();
+            control.set_paused(false);
+            control.set_time_scale(1.0);
+            control.set_max_fixed_updates(3);
+        }
+        game.step();
+        game.step();
+        assert_eq!(13, fixed_steps(&game));
+    }
+
+    #[test]
+    fn builds_configured_thread_pool() {
+        let builder = Application::build("assets/", CountingState)
+            .unwrap()
+            .with_resource(Frames(0))
+            .with_thread_count(2)
+            .with_thread_name(|index| format!("app-test-{}", index))
+            .with_execution_mode(ExecutionMode::SingleThreaded);
+        #[cfg(feature = "asset-daemon")]
+        let builder = builder.with_asset_daemon(false);
+        let game = builder.build(()).unwrap();
+
+        let pool = game.resources().get::<ArcThreadPool>().unwrap();
+        assert_eq!(pool.current_num_threads(), 2);
+        let name = pool.install(|| std::thread::current().name().map(String::from));
+        assert!(name.unwrap().starts_with("app-test-"));
+        assert_eq!(
+            *game.resources().get::<ExecutionMode>().unwrap(),
+            ExecutionMode::SingleThreaded
+        );
+    }
 }
@@ -33,6 +33,13 @@ impl GameData {
         }
     }
 
+    /// Update game data by executing the fixed update systems of the internal [Dispatcher]
+    pub fn fixed_update(&mut self, world: &mut World, resources: &mut Resources) {
+        if let Some(dispatcher) = &mut self.dispatcher {
+            dispatcher.execute_fixed(world, resources);
+        }
+    }
+
     /// Dispose game data, dropping the dispatcher
     pub fn dispose(&mut self, world: &mut World, resources: &mut Resources) {
         if let Some(dispatcher) = self.dispatcher.take() {
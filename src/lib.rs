@@ -113,8 +113,8 @@ pub use self::{
     error::Error,
     game_data::{DataDispose, DataInit, GameData},
     state::{
-        EmptyState, EmptyTrans, SimpleState, SimpleTrans, State, StateData, StateMachine, Trans,
-        TransEvent,
+        EmptyState, EmptyTrans, SimpleState, SimpleTrans, State, StateData, StateMachine,
+        StateTransitionEvent, StateTransitionKind, Trans, TransEvent,
     },
     state_event::{StateEvent, StateEventReader},
 };
@@ -2,12 +2,17 @@
 
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 
+use amethyst_core::shrev::EventChannel;
 use amethyst_input::is_close_requested;
 use derivative::Derivative;
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
-use crate::{ecs::*, GameData, StateEvent};
+use crate::{
+    ecs::*,
+    error::{format_err, Error},
+    GameData, StateEvent,
+};
 
 /// Error type for errors occurring in `StateMachine`
 #[derive(Debug)]
@@ -109,6 +114,32 @@ pub type SimpleTrans = Trans<GameData, StateEvent>;
 
 /// A trait which defines game states that can be used by the state machine.
 pub trait State<T, E: Send + Sync + 'static> {
+    /// Name of the state, used by `StateTransitionEvent`s and to inspect the state stack.
+    ///
+    /// Defaults to the type name of the state without its module path, like the names of
+    /// bundles in `SystemToggles`.
+    fn name(&self) -> &str {
+        amethyst_core::short_type_name::<Self>()
+    }
+
+    /// Systems which only run while this state is on the stack.
+    ///
+    /// Called by the `StateMachine` right before `on_start`. The built dispatcher is executed
+    /// after every `update` and `fixed_update` of this state, and unloaded right after `on_stop`,
+    /// so the resources its bundles insert are torn down together with the state.
+    ///
+    /// The `StateMachine` panics if the dispatcher fails to build, e.g. because of a cycle in the
+    /// ordering constraints of its systems.
+    fn dispatcher(&mut self) -> Option<DispatcherBuilder> {
+        None
+    }
+
+    /// Whether the dispatcher of this state keeps running while it is paused, next to
+    /// `shadow_update` and `shadow_fixed_update`. Defaults to `false`.
+    fn dispatch_when_paused(&self) -> bool {
+        false
+    }
+
     /// Executed when the game state begins.
     fn on_start(&mut self, _data: StateData<'_, T>) {}
 
@@ -153,6 +184,32 @@ pub trait State<T, E: Send + Sync + 'static> {
 
 /// An empty `State` trait. It contains no `StateData` or custom `StateEvent`.
 pub trait EmptyState {
+    /// Name of the state, used by `StateTransitionEvent`s and to inspect the state stack.
+    ///
+    /// Defaults to the type name of the state without its module path, like the names of
+    /// bundles in `SystemToggles`.
+    fn name(&self) -> &str {
+        amethyst_core::short_type_name::<Self>()
+    }
+
+    /// Systems which only run while this state is on the stack.
+    ///
+    /// Called by the `StateMachine` right before `on_start`. The built dispatcher is executed
+    /// after every `update` and `fixed_update` of this state, and unloaded right after `on_stop`,
+    /// so the resources its bundles insert are torn down together with the state.
+    ///
+    /// The `StateMachine` panics if the dispatcher fails to build, e.g. because of a cycle in the
+    /// ordering constraints of its systems.
+    fn dispatcher(&mut self) -> Option<DispatcherBuilder> {
+        None
+    }
+
+    /// Whether the dispatcher of this state keeps running while it is paused, next to
+    /// `shadow_update` and `shadow_fixed_update`. Defaults to `false`.
+    fn dispatch_when_paused(&self) -> bool {
+        false
+    }
+
     /// Executed when the game state begins.
     fn on_start(&mut self, _data: StateData<'_, ()>) {}
 
@@ -202,6 +259,21 @@ pub trait EmptyState {
 }
 
 impl<T: EmptyState> State<(), StateEvent> for T {
+    /// Name of the state.
+    fn name(&self) -> &str {
+        EmptyState::name(self)
+    }
+
+    /// Systems which only run while this state is on the stack.
+    fn dispatcher(&mut self) -> Option<DispatcherBuilder> {
+        EmptyState::dispatcher(self)
+    }
+
+    /// Whether the dispatcher of this state keeps running while it is paused.
+    fn dispatch_when_paused(&self) -> bool {
+        EmptyState::dispatch_when_paused(self)
+    }
+
     /// Executed when the game state begins.
     fn on_start(&mut self, data: StateData<'_, ()>) {
         self.on_start(data)
@@ -256,6 +328,32 @@ impl<T: EmptyState> State<(), StateEvent> for T {
 
 /// A simple `State` trait. It contains `GameData` as its `StateData` and no custom `StateEvent`.
 pub trait SimpleState {
+    /// Name of the state, used by `StateTransitionEvent`s and to inspect the state stack.
+    ///
+    /// Defaults to the type name of the state without its module path, like the names of
+    /// bundles in `SystemToggles`.
+    fn name(&self) -> &str {
+        amethyst_core::short_type_name::<Self>()
+    }
+
+    /// Systems which only run while this state is on the stack.
+    ///
+    /// Called by the `StateMachine` right before `on_start`. The built dispatcher is executed
+    /// after every `update` and `fixed_update` of this state, and unloaded right after `on_stop`,
+    /// so the resources its bundles insert are torn down together with the state.
+    ///
+    /// The `StateMachine` panics if the dispatcher fails to build, e.g. because of a cycle in the
+    /// ordering constraints of its systems.
+    fn dispatcher(&mut self) -> Option<DispatcherBuilder> {
+        None
+    }
+
+    /// Whether the dispatcher of this state keeps running while it is paused, next to
+    /// `shadow_update` and `shadow_fixed_update`. Defaults to `false`.
+    fn dispatch_when_paused(&self) -> bool {
+        false
+    }
+
     /// Executed when the game state begins.
     fn on_start(&mut self, _data: StateData<'_, GameData>) {}
 
@@ -305,6 +403,21 @@ pub trait SimpleState {
 }
 
 impl<T: SimpleState> State<GameData, StateEvent> for T {
+    /// Name of the state.
+    fn name(&self) -> &str {
+        SimpleState::name(self)
+    }
+
+    /// Systems which only run while this state is on the stack.
+    fn dispatcher(&mut self) -> Option<DispatcherBuilder> {
+        SimpleState::dispatcher(self)
+    }
+
+    /// Whether the dispatcher of this state keeps running while it is paused.
+    fn dispatch_when_paused(&self) -> bool {
+        SimpleState::dispatch_when_paused(self)
+    }
+
     /// Executed when the game state begins.
     fn on_start(&mut self, data: StateData<'_, GameData>) {
         self.on_start(data)
@@ -333,7 +446,18 @@ impl<T: SimpleState> State<GameData, StateEvent> for T {
     /// Executed repeatedly at stable, predictable intervals (1/60th of a second
     /// by default).
     fn fixed_update(&mut self, data: StateData<'_, GameData>) -> SimpleTrans {
-        self.fixed_update(data)
+        let StateData {
+            world,
+            resources,
+            data,
+        } = data;
+        let r = self.fixed_update(StateData {
+            world,
+            resources,
+            data,
+        });
+        data.fixed_update(world, resources);
+        r
     }
 
     /// Executed on every frame immediately, as fast as the engine will allow (taking into account the frame rate limit).
@@ -359,13 +483,124 @@ impl<T: SimpleState> State<GameData, StateEvent> for T {
     }
 }
 
+/// Kind of a state transition, see `Trans`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateTransitionKind {
+    /// The state machine was started with its initial state.
+    Start,
+    /// A state was pushed onto the stack.
+    Push,
+    /// The active state was removed from the stack.
+    Pop,
+    /// The active state was replaced with a different one.
+    Switch,
+    /// All states were replaced with a different one.
+    Replace,
+    /// All states were replaced with a new stack.
+    NewStack,
+    /// All states were removed and the state machine stopped.
+    Quit,
+}
+
+/// Event written to `EventChannel<StateTransitionEvent>` after every state transition performed
+/// by the `StateMachine`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateTransitionEvent {
+    /// The kind of transition.
+    pub kind: StateTransitionKind,
+    /// Name of the state that was active before the transition, if any.
+    pub from: Option<String>,
+    /// Name of the state that is active after the transition, if any.
+    pub to: Option<String>,
+}
+
 /// A simple stack-based state machine (pushdown automaton).
+///
+/// If the `EventChannel<StateTransitionEvent>` resource exists, every transition is published
+/// to it.
+///
+/// States can declare their own systems with `State::dispatcher`. The dispatcher is built right
+/// before the state starts, executed after the state is updated while it is active, and unloaded
+/// right after the state stops, so the resources of its bundles only live as long as the state.
+/// If the dispatcher of a state fails to build, the error is logged and the state machine stops.
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct StateMachine<'a, T, E> {
     running: bool,
     #[derivative(Debug = "ignore")]
-    state_stack: Vec<Box<dyn State<T, E> + 'a>>,
+    state_stack: Vec<StackEntry<'a, T, E>>,
+}
+
+/// A state on the stack of a `StateMachine`, together with its own dispatcher.
+struct StackEntry<'a, T, E> {
+    state: Box<dyn State<T, E> + 'a>,
+    dispatcher: Option<Dispatcher>,
+}
+
+impl<'a, T, E: Send + Sync + 'static> StackEntry<'a, T, E> {
+    fn new(state: Box<dyn State<T, E> + 'a>) -> Self {
+        StackEntry {
+            state,
+            dispatcher: None,
+        }
+    }
+
+    /// Builds the dispatcher of the state, then starts the state.
+    ///
+    /// The state is not started if its dispatcher fails to build.
+    fn start(
+        &mut self,
+        world: &mut World,
+        resources: &mut Resources,
+        data: &mut T,
+    ) -> Result<(), Error> {
+        if let Some(mut builder) = self.state.dispatcher() {
+            let dispatcher = builder.build(world, resources).map_err(|err| {
+                format_err!(
+                    "Failed to build the dispatcher of state {}: {}",
+                    self.state.name(),
+                    err
+                )
+            })?;
+            self.dispatcher = Some(dispatcher);
+        }
+        self.state.on_start(StateData {
+            world,
+            resources,
+            data,
+        });
+        Ok(())
+    }
+
+    /// Stops the state, then unloads its dispatcher.
+    fn stop(mut self, world: &mut World, resources: &mut Resources, data: &mut T) {
+        self.state.on_stop(StateData {
+            world,
+            resources,
+            data,
+        });
+        if let Some(dispatcher) = self.dispatcher.take() {
+            if let Err(err) = dispatcher.unload(world, resources) {
+                log::error!(
+                    "Failed to unload the dispatcher of state {}: {}",
+                    self.state.name(),
+                    err
+                );
+            }
+        }
+    }
+
+    fn execute(&mut self, world: &mut World, resources: &mut Resources) {
+        if let Some(dispatcher) = &mut self.dispatcher {
+            dispatcher.execute(world, resources);
+        }
+    }
+
+    fn execute_fixed(&mut self, world: &mut World, resources: &mut Resources) {
+        if let Some(dispatcher) = &mut self.dispatcher {
+            dispatcher.execute_fixed(world, resources);
+        }
+    }
 }
 
 impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
@@ -373,7 +608,7 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
     pub fn new<S: State<T, E> + 'a>(initial_state: S) -> StateMachine<'a, T, E> {
         StateMachine {
             running: false,
-            state_stack: vec![Box::new(initial_state)],
+            state_stack: vec![StackEntry::new(Box::new(initial_state))],
         }
     }
 
@@ -382,15 +617,36 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
         self.running
     }
 
+    /// Returns the states on the stack, from the bottom to the active state.
+    pub fn states(&self) -> impl DoubleEndedIterator<Item = &(dyn State<T, E> + 'a)> {
+        self.state_stack.iter().map(|entry| entry.state.as_ref())
+    }
+
+    /// Returns the active state, which is the state on top of the stack.
+    pub fn active_state(&self) -> Option<&(dyn State<T, E> + 'a)> {
+        self.state_stack.last().map(|entry| entry.state.as_ref())
+    }
+
+    /// Returns the names of the states on the stack, from the bottom to the active state.
+    pub fn state_names(&self) -> Vec<&str> {
+        self.states().map(|state| state.name()).collect()
+    }
+
     /// Initializes the state machine.
     pub fn start(&mut self, data: StateData<'_, T>) -> Result<(), StateError> {
         if !self.running {
-            let state = self
-                .state_stack
-                .last_mut()
-                .ok_or(StateError::NoStatesPresent)?;
-            state.on_start(data);
+            let StateData {
+                world,
+                resources,
+                data,
+            } = data;
+            if self.state_stack.is_empty() {
+                return Err(StateError::NoStatesPresent);
+            }
             self.running = true;
+            if self.start_active(world, resources, data) {
+                self.publish_transition(resources, StateTransitionKind::Start, None);
+            }
         }
         Ok(())
     }
@@ -404,8 +660,8 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
         } = data;
         if self.running {
             let trans = match self.state_stack.last_mut() {
-                Some(state) => {
-                    state.handle_event(
+                Some(entry) => {
+                    entry.state.handle_event(
                         StateData {
                             world,
                             resources,
@@ -429,6 +685,9 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
     }
 
     /// Updates the currently active state at a steady, fixed interval.
+    ///
+    /// The fixed systems of the active state's dispatcher, and of paused states which
+    /// `dispatch_when_paused`, are executed as well.
     pub fn fixed_update(&mut self, data: StateData<'_, T>) {
         let StateData {
             world,
@@ -437,25 +696,31 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
         } = data;
         if self.running {
             let trans = match self.state_stack.last_mut() {
-                Some(state) => {
+                Some(entry) => {
                     #[cfg(feature = "profiler")]
                     profile_scope!("stack fixed_update");
-                    state.fixed_update(StateData {
+                    let trans = entry.state.fixed_update(StateData {
                         world,
                         resources,
                         data,
-                    })
+                    });
+                    entry.execute_fixed(world, resources);
+                    trans
                 }
                 None => Trans::None,
             };
-            for state in &mut self.state_stack {
+            let paused = self.state_stack.len().saturating_sub(1);
+            for (index, entry) in self.state_stack.iter_mut().enumerate() {
                 #[cfg(feature = "profiler")]
                 profile_scope!("stack shadow_fixed_update");
-                state.shadow_fixed_update(StateData {
+                entry.state.shadow_fixed_update(StateData {
                     world,
                     resources,
                     data,
                 });
+                if index < paused && entry.state.dispatch_when_paused() {
+                    entry.execute_fixed(world, resources);
+                }
             }
             {
                 #[cfg(feature = "profiler")]
@@ -473,6 +738,9 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
     }
 
     /// Updates the currently active state immediately.
+    ///
+    /// The active state's dispatcher, and those of paused states which `dispatch_when_paused`,
+    /// are executed as well.
     pub fn update(&mut self, data: StateData<'_, T>) {
         let StateData {
             world,
@@ -481,25 +749,31 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
         } = data;
         if self.running {
             let trans = match self.state_stack.last_mut() {
-                Some(state) => {
+                Some(entry) => {
                     #[cfg(feature = "profiler")]
                     profile_scope!("stack update");
-                    state.update(StateData {
+                    let trans = entry.state.update(StateData {
                         world,
                         resources,
                         data,
-                    })
+                    });
+                    entry.execute(world, resources);
+                    trans
                 }
                 None => Trans::None,
             };
-            for state in &mut self.state_stack {
+            let paused = self.state_stack.len().saturating_sub(1);
+            for (index, entry) in self.state_stack.iter_mut().enumerate() {
                 #[cfg(feature = "profiler")]
                 profile_scope!("stack shadow_update");
-                state.shadow_update(StateData {
+                entry.state.shadow_update(StateData {
                     world,
                     resources,
                     data,
                 });
+                if index < paused && entry.state.dispatch_when_paused() {
+                    entry.execute(world, resources);
+                }
             }
 
             {
@@ -522,15 +796,44 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
     /// This method can also be called when there are one or multiple `Trans` stored in the
     /// global `EventChannel<TransEvent<T, E>>`. Such `Trans` will be passed to this method
     /// sequentially in the order of insertion.
+    ///
+    /// Every transition except `Trans::None` and `Trans::Sequence`, which publishes the
+    /// transitions it contains, is published to the `EventChannel<StateTransitionEvent>`.
     pub fn transition(&mut self, request: Trans<T, E>, data: StateData<'_, T>) {
         if self.running {
-            match request {
-                Trans::None => (),
-                Trans::Pop => self.pop(data),
-                Trans::Push(state) => self.push(state, data),
-                Trans::Switch(state) => self.switch(state, data),
-                Trans::Replace(state) => self.replace(state, data),
-                Trans::NewStack(states) => self.new_stack(states, data),
+            let StateData {
+                world,
+                resources,
+                data: game_data,
+            } = data;
+            let from = self.active_state().map(|state| state.name().to_string());
+            let data = StateData {
+                world,
+                resources: &mut *resources,
+                data: game_data,
+            };
+            let kind = match request {
+                Trans::None => return,
+                Trans::Pop => {
+                    self.pop(data);
+                    StateTransitionKind::Pop
+                }
+                Trans::Push(state) => {
+                    self.push(state, data);
+                    StateTransitionKind::Push
+                }
+                Trans::Switch(state) => {
+                    self.switch(state, data);
+                    StateTransitionKind::Switch
+                }
+                Trans::Replace(state) => {
+                    self.replace(state, data);
+                    StateTransitionKind::Replace
+                }
+                Trans::NewStack(states) => {
+                    self.new_stack(states, data);
+                    StateTransitionKind::NewStack
+                }
                 Trans::Sequence(sequence) => {
                     for trans in sequence {
                         let temp_data = StateData {
@@ -540,9 +843,30 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
                         };
                         self.transition(trans, temp_data);
                     }
+                    return;
                 }
-                Trans::Quit => self.stop(data),
-            }
+                Trans::Quit => {
+                    self.stop(data);
+                    StateTransitionKind::Quit
+                }
+            };
+            self.publish_transition(resources, kind, from);
+        }
+    }
+
+    /// Writes a `StateTransitionEvent` if the event channel exists.
+    fn publish_transition(
+        &self,
+        resources: &Resources,
+        kind: StateTransitionKind,
+        from: Option<String>,
+    ) {
+        if let Some(mut channel) = resources.get_mut::<EventChannel<StateTransitionEvent>>() {
+            channel.single_write(StateTransitionEvent {
+                kind,
+                from,
+                to: self.active_state().map(|state| state.name().to_string()),
+            });
         }
     }
 
@@ -554,23 +878,13 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
                 resources,
                 data,
             } = data;
-            if let Some(mut state) = self.state_stack.pop() {
-                state.on_stop(StateData {
-                    world,
-                    resources,
-                    data,
-                });
+            if let Some(entry) = self.state_stack.pop() {
+                entry.stop(world, resources, data);
             }
 
-            self.state_stack.push(state);
+            self.state_stack.push(StackEntry::new(state));
 
-            //State was just pushed, thus pop will always succeed
-            let new_state = self.state_stack.last_mut().unwrap();
-            new_state.on_start(StateData {
-                world,
-                resources,
-                data,
-            });
+            self.start_active(world, resources, data);
         }
     }
 
@@ -582,23 +896,17 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
                 resources,
                 data,
             } = data;
-            if let Some(state) = self.state_stack.last_mut() {
-                state.on_pause(StateData {
+            if let Some(entry) = self.state_stack.last_mut() {
+                entry.state.on_pause(StateData {
                     world,
                     resources,
                     data,
                 });
             }
 
-            self.state_stack.push(state);
+            self.state_stack.push(StackEntry::new(state));
 
-            //State was just pushed, thus pop will always succeed
-            let new_state = self.state_stack.last_mut().unwrap();
-            new_state.on_start(StateData {
-                world,
-                resources,
-                data,
-            });
+            self.start_active(world, resources, data);
         }
     }
 
@@ -611,16 +919,12 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
                 resources,
                 data,
             } = data;
-            if let Some(mut state) = self.state_stack.pop() {
-                state.on_stop(StateData {
-                    world,
-                    resources,
-                    data,
-                });
+            if let Some(entry) = self.state_stack.pop() {
+                entry.stop(world, resources, data);
             }
 
-            if let Some(state) = self.state_stack.last_mut() {
-                state.on_resume(StateData {
+            if let Some(entry) = self.state_stack.last_mut() {
+                entry.state.on_resume(StateData {
                     world,
                     resources,
                     data,
@@ -640,24 +944,14 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
                 resources,
                 data,
             } = data;
-            while let Some(mut state) = self.state_stack.pop() {
-                state.on_stop(StateData {
-                    world,
-                    resources,
-                    data,
-                });
+            while let Some(entry) = self.state_stack.pop() {
+                entry.stop(world, resources, data);
             }
 
             //Push the new state
-            self.state_stack.push(state);
+            self.state_stack.push(StackEntry::new(state));
 
-            //State was just pushed, thus pop will always succeed
-            let new_state = self.state_stack.last_mut().unwrap();
-            new_state.on_start(StateData {
-                world,
-                resources,
-                data,
-            });
+            self.start_active(world, resources, data);
         }
     }
 
@@ -670,29 +964,22 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
                 resources,
                 data,
             } = data;
-            while let Some(mut state) = self.state_stack.pop() {
-                state.on_stop(StateData {
-                    world,
-                    resources,
-                    data,
-                });
+            while let Some(entry) = self.state_stack.pop() {
+                entry.stop(world, resources, data);
             }
 
             // push the new states
             let state_count = states.len();
             for (count, state) in states.into_iter().enumerate() {
-                self.state_stack.push(state);
+                self.state_stack.push(StackEntry::new(state));
 
-                // State was just pushed, thus pop will always succeed
-                let new_state = self.state_stack.last_mut().unwrap();
-                new_state.on_start(StateData {
-                    world,
-                    resources,
-                    data,
-                });
+                if !self.start_active(world, resources, data) {
+                    return;
+                }
                 if count != state_count - 1 {
                     // pause on each state but the last
-                    new_state.on_pause(StateData {
+                    let new_state = self.state_stack.last_mut().unwrap();
+                    new_state.state.on_pause(StateData {
                         world,
                         resources,
                         data,
@@ -702,6 +989,28 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
         }
     }
 
+    /// Starts the state which was just pushed onto the stack.
+    ///
+    /// If its dispatcher fails to build, the error is logged and the state machine stops, so a
+    /// state never silently runs without its systems. Returns whether the state was started.
+    fn start_active(&mut self, world: &mut World, resources: &mut Resources, data: &mut T) -> bool {
+        // State was just pushed, thus last_mut will always succeed
+        let entry = self.state_stack.last_mut().unwrap();
+        match entry.start(world, resources, data) {
+            Ok(()) => true,
+            Err(err) => {
+                log::error!("{}. Stopping the state machine.", err);
+                // The state was never started, so it isn't stopped either.
+                self.state_stack.pop();
+                while let Some(entry) = self.state_stack.pop() {
+                    entry.stop(world, resources, data);
+                }
+                self.running = false;
+                false
+            }
+        }
+    }
+
     /// Shuts the state machine down.
     pub(crate) fn stop(&mut self, data: StateData<'_, T>) {
         if self.running {
@@ -710,12 +1019,8 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
                 resources,
                 data,
             } = data;
-            while let Some(mut state) = self.state_stack.pop() {
-                state.on_stop(StateData {
-                    world,
-                    resources,
-                    data,
-                });
+            while let Some(entry) = self.state_stack.pop() {
+                entry.stop(world, resources, data);
             }
 
             self.running = false;
@@ -860,4 +1165,189 @@ mod tests {
         sm.update(StateData::new(&mut world, &mut resources, &mut ()));
         assert_eq!(sm.state_stack.len(), 1);
     }
+
+    #[test]
+    fn transitions_are_published() {
+        use crate::ecs::World;
+
+        struct Named(&'static str);
+        impl State<(), ()> for Named {
+            fn name(&self) -> &str {
+                self.0
+            }
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut channel = EventChannel::<StateTransitionEvent>::new();
+        let mut reader = channel.register_reader();
+        resources.insert(channel);
+
+        let mut sm = StateMachine::new(Named("menu"));
+        sm.start(StateData::new(&mut world, &mut resources, &mut ()))
+            .unwrap();
+        sm.transition(
+            Trans::Push(Box::new(Named("game"))),
+            StateData::new(&mut world, &mut resources, &mut ()),
+        );
+        assert_eq!(sm.state_names(), vec!["menu", "game"]);
+        assert_eq!(sm.active_state().map(|state| state.name()), Some("game"));
+
+        sm.transition(
+            Trans::Sequence(vec![
+                Trans::Switch(Box::new(Named("pause"))),
+                Trans::None,
+                Trans::Pop,
+            ]),
+            StateData::new(&mut world, &mut resources, &mut ()),
+        );
+        sm.transition(
+            Trans::Quit,
+            StateData::new(&mut world, &mut resources, &mut ()),
+        );
+        assert!(sm.state_names().is_empty());
+
+        let event = |kind, from: Option<&str>, to: Option<&str>| {
+            StateTransitionEvent {
+                kind,
+                from: from.map(String::from),
+                to: to.map(String::from),
+            }
+        };
+        let events: Vec<_> = resources
+            .get::<EventChannel<StateTransitionEvent>>()
+            .unwrap()
+            .read(&mut reader)
+            .cloned()
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                event(StateTransitionKind::Start, None, Some("menu")),
+                event(StateTransitionKind::Push, Some("menu"), Some("game")),
+                event(StateTransitionKind::Switch, Some("game"), Some("pause")),
+                event(StateTransitionKind::Pop, Some("pause"), Some("menu")),
+                event(StateTransitionKind::Quit, Some("menu"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn default_state_name_is_short_type_name() {
+        assert_eq!(State::<(), ()>::name(&State0), "State0");
+        assert_eq!(State::<(), ()>::name(&StateReplace(0)), "StateReplace");
+    }
+
+    #[test]
+    fn state_dispatchers_live_as_long_as_their_states() {
+        use crate::{ecs::World, error::Error};
+
+        struct Ticks(u32);
+
+        struct TickBundle;
+        impl SystemBundle for TickBundle {
+            fn load(
+                &mut self,
+                _world: &mut World,
+                resources: &mut Resources,
+                builder: &mut DispatcherBuilder,
+            ) -> Result<(), Error> {
+                resources.insert(Ticks(0));
+                builder.add_thread_local_fn(|_, resources| {
+                    resources.get_mut::<Ticks>().unwrap().0 += 1;
+                });
+                Ok(())
+            }
+
+            fn unload(
+                &mut self,
+                _world: &mut World,
+                resources: &mut Resources,
+            ) -> Result<(), Error> {
+                resources.remove::<Ticks>();
+                Ok(())
+            }
+        }
+
+        struct Ticking(bool);
+        impl State<(), ()> for Ticking {
+            fn dispatcher(&mut self) -> Option<DispatcherBuilder> {
+                let mut builder = DispatcherBuilder::default();
+                builder.add_bundle(TickBundle);
+                Some(builder)
+            }
+
+            fn dispatch_when_paused(&self) -> bool {
+                self.0
+            }
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let ticks = |resources: &Resources| resources.get::<Ticks>().map(|ticks| ticks.0);
+
+        let mut sm = StateMachine::new(Ticking(false));
+        sm.start(StateData::new(&mut world, &mut resources, &mut ()))
+            .unwrap();
+        assert_eq!(ticks(&resources), Some(0));
+        sm.update(StateData::new(&mut world, &mut resources, &mut ()));
+        assert_eq!(ticks(&resources), Some(1));
+
+        // Paused states only dispatch if they opt in.
+        sm.transition(
+            Trans::Push(Box::new(State0)),
+            StateData::new(&mut world, &mut resources, &mut ()),
+        );
+        sm.update(StateData::new(&mut world, &mut resources, &mut ()));
+        assert_eq!(ticks(&resources), Some(1));
+
+        sm.transition(
+            Trans::NewStack(vec![Box::new(Ticking(true)), Box::new(State0)]),
+            StateData::new(&mut world, &mut resources, &mut ()),
+        );
+        assert_eq!(ticks(&resources), Some(0));
+        sm.update(StateData::new(&mut world, &mut resources, &mut ()));
+        assert_eq!(ticks(&resources), Some(1));
+
+        sm.transition(
+            Trans::Quit,
+            StateData::new(&mut world, &mut resources, &mut ()),
+        );
+        assert_eq!(ticks(&resources), None);
+    }
+
+    #[test]
+    fn state_dispatcher_build_errors_stop_the_state_machine() {
+        use crate::{core::SystemOrder, ecs::World};
+
+        struct Misordered;
+        impl State<(), ()> for Misordered {
+            fn dispatcher(&mut self) -> Option<DispatcherBuilder> {
+                let mut builder = DispatcherBuilder::default();
+                builder.add_thread_local_fn_ordered(|_, _| {}, SystemOrder::new().after("missing"));
+                Some(builder)
+            }
+
+            fn on_start(&mut self, _: StateData<'_, ()>) {
+                panic!("A state without its dispatcher was started");
+            }
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut sm = StateMachine::new(State0);
+        sm.start(StateData::new(&mut world, &mut resources, &mut ()))
+            .unwrap();
+        sm.transition(
+            Trans::Push(Box::new(Misordered)),
+            StateData::new(&mut world, &mut resources, &mut ()),
+        );
+        assert!(!sm.is_running());
+        assert!(sm.state_names().is_empty());
+
+        let mut sm = StateMachine::new(Misordered);
+        sm.start(StateData::new(&mut world, &mut resources, &mut ()))
+            .unwrap();
+        assert!(!sm.is_running());
+    }
 }